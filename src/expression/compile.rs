@@ -0,0 +1,124 @@
+use num_complex::{Complex64, ComplexFloat};
+
+use crate::{c, e, expression::*, tree::NodeId, Expression};
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Var(usize),
+    Const(Complex64),
+    /// Sum of the top `n` values
+    Add(usize),
+    /// Product of the top `n` values
+    Mul(usize),
+    Exp,
+    Ln,
+    Abs,
+    /// `b^k` with `k` the product of the top `n` values and `b` below them
+    Pow(usize),
+}
+
+/// Expression flattened into a postfix program, for evaluating it many times
+#[derive(Debug, Clone)]
+pub struct Compiled {
+    ops: Vec<Op>,
+    depth: usize,
+}
+
+impl<T: Clone> Expressable<T>
+where
+    Expression: From<Expressable<T>>,
+{
+    pub fn compile(self) -> Compiled {
+        let ex = e!(self);
+        let mut ops = Vec::new();
+        emit(&ex.0, NodeId::ROOT, &mut ops);
+        let mut depth: usize = 0;
+        let mut max = 0;
+        for op in &ops {
+            depth = match *op {
+                Op::Var(_) | Op::Const(_) => depth + 1,
+                Op::Add(n) | Op::Mul(n) => depth + 1 - n,
+                Op::Pow(n) => depth - n,
+                Op::Exp | Op::Ln | Op::Abs => depth,
+            };
+            max = max.max(depth);
+        }
+        Compiled { ops, depth: max }
+    }
+}
+
+fn emit(ex: &Expressand, id: NodeId, ops: &mut Vec<Op>) {
+    let node = ex.tree.node(id);
+    match node.kind {
+        ExprKind::Integral(_) | ExprKind::Func(_) | ExprKind::Derivative(..) => {
+            return ops.push(Op::Const(c!(f64::NAN)))
+        }
+        ExprKind::Exp => {
+            let arg = ex.tree.node(node.children()[0]);
+            let factors = arg.children();
+            let ln = factors
+                .iter()
+                .find(|&&f| ex.tree.node(f).kind == ExprKind::Ln);
+            if let (ExprKind::Mul, Some(&ln)) = (arg.kind, ln) {
+                emit(ex, ex.tree.node(ln).children()[0], ops);
+                for &f in factors.iter().filter(|&&f| f != ln) {
+                    emit(ex, f, ops);
+                }
+                return ops.push(Op::Pow(factors.len() - 1));
+            }
+        }
+        _ => (),
+    }
+    for &child in node.children() {
+        emit(ex, child, ops);
+    }
+    ops.push(match node.kind {
+        ExprKind::ROOT | ExprKind::Add => Op::Add(node.children().len()),
+        ExprKind::Mul => Op::Mul(node.children().len()),
+        ExprKind::Var(v) => Op::Var(v.id),
+        ExprKind::Const(c) => Op::Const(c),
+        ExprKind::Exp => Op::Exp,
+        ExprKind::Ln => Op::Ln,
+        ExprKind::Abs => Op::Abs,
+        ExprKind::Integral(_) | ExprKind::Func(_) | ExprKind::Derivative(..) => unreachable!(),
+    });
+}
+
+impl Compiled {
+    /// Value at `x`, indexed by variable like `System::eval`
+    pub fn eval(&self, x: &[Complex64]) -> Complex64 {
+        let mut stack = Vec::with_capacity(self.depth);
+        for op in &self.ops {
+            match *op {
+                Op::Var(id) => stack.push(x[id]),
+                Op::Const(c) => stack.push(c),
+                Op::Add(n) => {
+                    let sum = stack.drain(stack.len() - n..).sum();
+                    stack.push(sum);
+                }
+                Op::Mul(n) => {
+                    let product = stack.drain(stack.len() - n..).product();
+                    stack.push(product);
+                }
+                Op::Pow(n) => {
+                    let k = stack.drain(stack.len() - n..).product();
+                    let base = stack.pop().unwrap();
+                    stack.push(pow_value(base, k));
+                }
+                Op::Exp => {
+                    let v = stack.pop().unwrap();
+                    stack.push(v.exp());
+                }
+                Op::Ln => {
+                    let v = stack.pop().unwrap();
+                    stack.push(v.ln());
+                }
+                Op::Abs => {
+                    let v = stack.pop().unwrap();
+                    stack.push(c!(v.abs()));
+                }
+            }
+        }
+        stack.pop().unwrap_or(c!())
+    }
+}
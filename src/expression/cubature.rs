@@ -0,0 +1,331 @@
+use std::array;
+
+use num_complex::{Complex64, ComplexFloat};
+
+use crate::{c, e, expression::*, Expression};
+
+const REL_TOL: f64 = 1e-8;
+const ABS_TOL: f64 = 1e-12;
+const MAX_EVALS: usize = 1_000_000;
+const SHIFTS: usize = 16;
+
+/// Region of integration in `N` coordinates
+#[derive(Debug, Clone)]
+pub enum Region<const N: usize> {
+    /// Box between two opposite corners
+    Box([f64; N], [f64; N]),
+    /// Simplex spanned by `N + 1` vertices
+    Simplex(Vec<[f64; N]>),
+}
+
+impl<T: Clone> Expressable<T>
+where
+    Expression: From<Expressable<T>>,
+{
+    /// Integral over `region` in the coordinates `x` by adaptive cubature. Boxes are
+    /// bisected along their roughest axis with the degree 7 Genz–Malik rule, its embedded
+    /// degree 5 rule giving the error. Simplices are mapped onto the unit box first.
+    pub fn cubature<const N: usize>(
+        self,
+        x: [Var; N],
+        region: Region<N>,
+    ) -> Result<Definite, String> {
+        cubature(e!(self), x, region)
+    }
+
+    /// Integral over `region` in the coordinates `x` from `samples` points of randomly shifted
+    /// rank-1 lattices, for dimensions where adaptive cubature gets too expensive. The error
+    /// is the standard error over the shifts.
+    pub fn quasi_monte_carlo<const N: usize>(
+        self,
+        x: [Var; N],
+        region: Region<N>,
+        samples: usize,
+    ) -> Result<Definite, String> {
+        quasi_monte_carlo(e!(self), x, region, samples)
+    }
+}
+
+fn cubature<const N: usize>(
+    ex: Expression,
+    x: [Var; N],
+    region: Region<N>,
+) -> Result<Definite, String> {
+    let f = Mapped::new(ex, x, region)?;
+    let (value, error) = adaptive(&|u: &[f64; N]| f.eval(u))?;
+    Ok(Definite {
+        value: e!(value),
+        error,
+    })
+}
+
+fn quasi_monte_carlo<const N: usize>(
+    ex: Expression,
+    x: [Var; N],
+    region: Region<N>,
+    samples: usize,
+) -> Result<Definite, String> {
+    let f = Mapped::new(ex, x, region)?;
+    let (value, error) = lattice(&|u: &[f64; N]| f.eval(u), samples);
+    match value.is_finite() {
+        true => Ok(Definite {
+            value: e!(value),
+            error,
+        }),
+        false => Err("Integral is not finite".to_string()),
+    }
+}
+
+/// Integrand pulled back to the unit box, Jacobian included
+struct Mapped<const N: usize> {
+    f: Compiled,
+    x: [Var; N],
+    len: usize,
+    region: Region<N>,
+    volume: f64,
+}
+
+impl<const N: usize> Mapped<N> {
+    fn new(ex: Expression, x: [Var; N], region: Region<N>) -> Result<Self, String> {
+        assert!(N > 0, "Cannot integrate over zero coordinates");
+        if ex.vars().iter().any(|v| !x.contains(v)) {
+            return Err("Integrand depends on variables other than the coordinates".to_string());
+        }
+        let volume = match &region {
+            Region::Box(lo, hi) => (0..N).map(|i| hi[i] - lo[i]).product(),
+            Region::Simplex(v) => {
+                assert!(
+                    v.len() == N + 1,
+                    "Inadequate amount of vertices, expected {} got {}",
+                    N + 1,
+                    v.len()
+                );
+                let m: [[f64; N]; N] =
+                    array::from_fn(|i| array::from_fn(|j| v[j + 1][i] - v[0][i]));
+                det(m).abs()
+            }
+        };
+        Ok(Mapped {
+            len: x.iter().map(|v| v.id + 1).max().unwrap_or(0),
+            f: ex.compile(),
+            x,
+            region,
+            volume,
+        })
+    }
+
+    fn eval(&self, u: &[f64; N]) -> Complex64 {
+        let mut point = [0.0; N];
+        let mut jac = self.volume;
+        match &self.region {
+            Region::Box(lo, hi) => {
+                for i in 0..N {
+                    point[i] = lo[i] + u[i] * (hi[i] - lo[i]);
+                }
+            }
+            Region::Simplex(v) => {
+                // Collapsed coordinates `l_k = u_k (1 - u_0) ... (1 - u_(k-1))`
+                point = v[0];
+                let mut rest = 1.0;
+                for k in 0..N {
+                    let l = rest * u[k];
+                    for i in 0..N {
+                        point[i] += l * (v[k + 1][i] - v[0][i]);
+                    }
+                    jac *= rest;
+                    rest *= 1.0 - u[k];
+                }
+            }
+        }
+        let mut values = vec![c!(); self.len];
+        for (x, p) in self.x.iter().zip(point) {
+            values[x.id] = c!(p);
+        }
+        self.f.eval(&values) * jac
+    }
+}
+
+fn det<const N: usize>(mut m: [[f64; N]; N]) -> f64 {
+    let mut det = 1.0;
+    for k in 0..N {
+        let pivot = (k..N)
+            .max_by(|&i, &j| m[i][k].abs().total_cmp(&m[j][k].abs()))
+            .unwrap();
+        if pivot != k {
+            m.swap(pivot, k);
+            det = -det;
+        }
+        det *= m[k][k];
+        if m[k][k] == 0.0 {
+            return 0.0;
+        }
+        let row = m[k];
+        for below in &mut m[k + 1..] {
+            let r = below[k] / row[k];
+            for (a, b) in below[k..].iter_mut().zip(&row[k..]) {
+                *a -= r * b;
+            }
+        }
+    }
+    det
+}
+
+struct Cell<const N: usize> {
+    center: [f64; N],
+    half: [f64; N],
+    value: Complex64,
+    error: f64,
+    axis: usize,
+}
+
+impl<const N: usize> Cell<N> {
+    fn new(
+        f: &impl Fn(&[f64; N]) -> Complex64,
+        center: [f64; N],
+        half: [f64; N],
+    ) -> Result<Self, String> {
+        const L2: f64 = 0.358_568_582_800_318_1; // sqrt(9/70)
+        const L4: f64 = 0.948_683_298_050_513_8; // sqrt(9/10)
+        const L5: f64 = 0.688_247_201_611_685_3; // sqrt(9/19)
+        let n = N as f64;
+        let eval = |p: &[f64; N]| match f(p) {
+            v if v.is_finite() => Ok(v),
+            _ => Err(format!("Integrand is not finite at {p:?}")),
+        };
+        let at = |offsets: &[(usize, f64)]| {
+            let mut p = center;
+            for &(i, o) in offsets {
+                p[i] += o * half[i];
+            }
+            eval(&p)
+        };
+
+        let f0 = eval(&center)?;
+        let (mut s2, mut s3, mut s4, mut s5) = (c!(), c!(), c!(), c!());
+        let mut axis = 0;
+        let mut roughest = -1.0;
+        for i in 0..N {
+            let a = at(&[(i, L2)])? + at(&[(i, -L2)])?;
+            let b = at(&[(i, L4)])? + at(&[(i, -L4)])?;
+            s2 += a;
+            s3 += b;
+            // Fourth difference along the axis
+            let diff = (a - f0 * 2.0 - (b - f0 * 2.0) * (L2 * L2 / (L4 * L4))).abs();
+            if diff > roughest {
+                roughest = diff;
+                axis = i;
+            }
+            for j in i + 1..N {
+                for (si, sj) in [(L4, L4), (L4, -L4), (-L4, L4), (-L4, -L4)] {
+                    s4 += at(&[(i, si), (j, sj)])?;
+                }
+            }
+        }
+        for corner in 0..1usize << N {
+            let offsets = array::from_fn::<_, N, _>(|i| match corner >> i & 1 {
+                0 => (i, L5),
+                _ => (i, -L5),
+            });
+            s5 += at(&offsets)?;
+        }
+
+        let volume = half.iter().map(|h| 2.0 * h).product::<f64>();
+        let w1 = (12824.0 - 9120.0 * n + 400.0 * n * n) / 19683.0;
+        let w3 = (1820.0 - 400.0 * n) / 19683.0;
+        let w5 = 6859.0 / 19683.0 / (1u64 << N) as f64;
+        let value =
+            (f0 * w1 + s2 * (980.0 / 6561.0) + s3 * w3 + s4 * (200.0 / 19683.0) + s5 * w5) * volume;
+        let e1 = (729.0 - 950.0 * n + 50.0 * n * n) / 729.0;
+        let e3 = (265.0 - 100.0 * n) / 1458.0;
+        let degree5 = (f0 * e1 + s2 * (245.0 / 486.0) + s3 * e3 + s4 * (25.0 / 729.0)) * volume;
+        Ok(Cell {
+            center,
+            half,
+            value,
+            error: (value - degree5).abs(),
+            axis,
+        })
+    }
+
+    fn evals() -> usize {
+        1 + 4 * N + 2 * N * (N - 1) + (1 << N)
+    }
+}
+
+/// Globally adaptive cubature over the unit box, always bisecting the cell with the largest
+/// error
+fn adaptive<const N: usize>(
+    f: &impl Fn(&[f64; N]) -> Complex64,
+) -> Result<(Complex64, f64), String> {
+    if N == 1 {
+        return quadrature::quad(&|t| f(&[t; N]), 0.0, 1.0);
+    }
+    let mut cells = vec![Cell::new(f, [0.5; N], [0.5; N])?];
+    let mut evals = Cell::<N>::evals();
+    while evals < MAX_EVALS {
+        let value = cells.iter().map(|c| c.value).sum::<Complex64>();
+        let error = cells.iter().map(|c| c.error).sum::<f64>();
+        if error <= ABS_TOL.max(REL_TOL * value.abs()) {
+            break;
+        }
+        let worst = (0..cells.len())
+            .max_by(|&i, &j| cells[i].error.total_cmp(&cells[j].error))
+            .unwrap();
+        let Cell {
+            center, half, axis, ..
+        } = cells[worst];
+        let mut half = half;
+        half[axis] *= 0.5;
+        let (mut left, mut right) = (center, center);
+        left[axis] -= half[axis];
+        right[axis] += half[axis];
+        cells[worst] = Cell::new(f, left, half)?;
+        cells.push(Cell::new(f, right, half)?);
+        evals += 2 * Cell::<N>::evals();
+    }
+    let value = cells.iter().map(|c| c.value).sum();
+    let error = cells.iter().map(|c| c.error).sum();
+    Ok((value, error))
+}
+
+/// Averages over randomly shifted copies of the `R_d` lattice, folded by the baker's
+/// transform `u -> 1 - |2u - 1|` so that non-periodic integrands converge faster
+fn lattice<const N: usize>(
+    f: &impl Fn(&[f64; N]) -> Complex64,
+    samples: usize,
+) -> (Complex64, f64) {
+    // Generalized golden ratio, the root of `p^(N + 1) = p + 1`
+    let mut p = 2.0f64;
+    for _ in 0..64 {
+        p = (1.0 + p).powf(1.0 / (N + 1) as f64);
+    }
+    let alpha: [f64; N] = array::from_fn(|i| (1.0 / p).powi(i as i32 + 1));
+    let mut state = 0x853c_49e6_748f_ea9bu64;
+    let mut random = || {
+        // splitmix64
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        (z ^ (z >> 31)) as f64 / u64::MAX as f64
+    };
+    let n = (samples / SHIFTS).max(1);
+    let estimates = (0..SHIFTS)
+        .map(|_| {
+            let shift: [f64; N] = array::from_fn(|_| random());
+            (1..=n)
+                .map(|k| {
+                    let u = array::from_fn(|i| {
+                        let u = (shift[i] + k as f64 * alpha[i]).fract();
+                        1.0 - (2.0 * u - 1.0).abs()
+                    });
+                    f(&u)
+                })
+                .sum::<Complex64>()
+                / n as f64
+        })
+        .collect::<Vec<_>>();
+    let mean = estimates.iter().sum::<Complex64>() / SHIFTS as f64;
+    let var = estimates.iter().map(|e| (e - mean).norm_sqr()).sum::<f64>() / (SHIFTS - 1) as f64;
+    (mean, (var / SHIFTS as f64).sqrt())
+}
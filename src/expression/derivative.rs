@@ -0,0 +1,77 @@
+use crate::{e, expression::*, Expression};
+
+impl<T: Clone> Expressable<T>
+where
+    Expression: From<Expressable<T>>,
+{
+    /// Unevaluated derivative, taking each `(x, order)` in turn. Nothing is differentiated
+    /// until `doit`.
+    pub fn derivative<const N: usize>(self, by: [(Var, usize); N]) -> Expression {
+        unevaluated(e!(self), by)
+    }
+
+    /// Evaluates every `Derivative` node, leaving only the derivatives of undefined
+    /// functions
+    pub fn doit(self) -> Expression {
+        doit(&e!(self)).simplify()
+    }
+
+    /// Replaces every call of `f` by `body`, with `params` standing for the arguments
+    #[track_caller]
+    pub fn subs_func<const N: usize>(
+        self,
+        f: Func,
+        params: [Var; N],
+        body: Expression,
+    ) -> Expression {
+        assert!(
+            N == f.arity,
+            "Inadequate amount of parameters, expected {} got {}",
+            f.arity,
+            N
+        );
+        subs_func(&e!(self), f, &params, &body)
+    }
+}
+
+fn unevaluated<const N: usize>(ex: Expression, by: [(Var, usize); N]) -> Expression {
+    by.into_iter()
+        .fold(ex, |ex, (x, n)| derivative(ex, x, n, e!(x)))
+}
+
+fn doit(ex: &Expression) -> Expression {
+    match ex.kind() {
+        ExprKind::Var(_) | ExprKind::Const(_) => ex.clone(),
+        ExprKind::Derivative(x, n) => {
+            let [f, p] = <[Expression; 2]>::try_from(ex.args()).unwrap();
+            let mut d = doit(&f);
+            for _ in 0..n {
+                d = d.diff(x).simplify();
+            }
+            d.subs(x, doit(&p))
+        }
+        kind => Expression::node(kind, ex.args().iter().map(doit)),
+    }
+}
+
+fn subs_func(ex: &Expression, f: Func, params: &[Var], body: &Expression) -> Expression {
+    match ex.kind() {
+        ExprKind::Var(_) | ExprKind::Const(_) => ex.clone(),
+        ExprKind::Func(g) if g == f => {
+            // Through dummies, so that arguments naming other parameters are not replaced twice
+            let args = ex.args();
+            let mut new = body.clone();
+            for (i, &p) in params.iter().enumerate() {
+                new = new.subs(p, Var::slot(i));
+            }
+            for (i, arg) in args.iter().enumerate() {
+                new = new.subs(Var::slot(i), subs_func(arg, f, params, body));
+            }
+            new
+        }
+        kind => Expression::node(
+            kind,
+            ex.args().iter().map(|a| subs_func(a, f, params, body)),
+        ),
+    }
+}
@@ -0,0 +1,30 @@
+use super::{ExprKind, Expression};
+
+/// Undefined function of a fixed number of arguments
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct Func {
+    pub(crate) id: usize,
+    pub(crate) arity: usize,
+}
+
+impl Func {
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// The function applied to `args`
+    #[track_caller]
+    pub fn call<I: IntoIterator<Item = T>, T>(self, args: I) -> Expression
+    where
+        Expression: From<T>,
+    {
+        let args = args.into_iter().map(Expression::from).collect::<Vec<_>>();
+        assert!(
+            args.len() == self.arity,
+            "Inadequate amount of arguments, expected {} got {}",
+            self.arity,
+            args.len()
+        );
+        Expression::node(ExprKind::Func(self), args)
+    }
+}
@@ -0,0 +1,930 @@
+use num_complex::{Complex64, ComplexFloat};
+
+use crate::{c, e, expression::*, Expression};
+
+const MAX_DEPTH: usize = 8;
+const MAX_STEPS: usize = 2_000;
+
+/// Polynomial coefficients, lowest degree first
+type Poly = Vec<Expression>;
+
+impl<T: Clone> Expressable<T>
+where
+    Expression: From<Expressable<T>>,
+{
+    /// Antiderivative in `x`, without the integration constant.
+    ///
+    /// Rational functions are integrated through partial fractions. Everything else goes
+    /// through the exponential table, substitutions of the form `u'(x) g(u(x))`, a
+    /// substitution `t = e^(kx)` for rational functions of exponentials (which covers the
+    /// trigonometric functions), and integration by parts. Terms that resist all of these
+    /// are left inside an unevaluated `Integral` node.
+    pub fn integrate(self, x: Var) -> Expression {
+        integrate(e!(self), x)
+    }
+
+    /// Integral over `x` from `a` to `b`.
+    ///
+    /// The antiderivative is evaluated through one-sided limits at the bounds when
+    /// `integrate` finds one. When the integrand and bounds are numeric, adaptive quadrature
+    /// on `eval` checks that value, and replaces it when it disagrees or no antiderivative
+    /// was found. Infinite bounds are mapped onto finite ones, and integrable endpoint
+    /// singularities are left to tanh-sinh quadrature.
+    ///
+    /// Numeric integrals are first split at the singularities inside the interval, the zeros
+    /// of denominators and of the arguments of logarithms, so that each piece only has them
+    /// at its ends. Fails when a piece diverges, or when quadrature fails on a numeric
+    /// integrand. Singularities are not looked for when the integrand or bounds are symbolic.
+    pub fn integrate_definite(self, x: Var, a: Point, b: Point) -> Result<Definite, String> {
+        integrate_definite(e!(self), x, a, b)
+    }
+}
+
+/// Value of a definite integral, with an estimate of its absolute error
+#[derive(Debug, Clone)]
+pub struct Definite {
+    pub value: Expression,
+    pub error: f64,
+}
+
+fn integrate(ex: Expression, x: Var) -> Expression {
+    let mut integrator = Integrator::new(x);
+    let mut done = Vec::new();
+    let mut rest = Vec::new();
+    for term in terms(&ex.simplify()) {
+        match integrator.integrate(&term, 0) {
+            Some(f) => done.push(f),
+            None => rest.push(term),
+        }
+    }
+    if !rest.is_empty() {
+        done.push(Expression::node(ExprKind::Integral(x), [sum(rest), e!(x)]));
+    }
+    sum(done).simplify()
+}
+
+fn integrate_definite(ex: Expression, x: Var, a: Point, b: Point) -> Result<Definite, String> {
+    let ex = ex.simplify();
+    let numeric = ex.vars().iter().all(|&v| v == x);
+    let (Some(lo), Some(hi), true) = (numeric_bound(&a), numeric_bound(&b), numeric) else {
+        return definite_piece(&ex, x, &a, &b, true);
+    };
+    // Bounds of the pieces, with whether each is exact. Singularities only known as floats
+    // are judged by how fast the integrand grows there instead of through limits.
+    let mut bounds = vec![(a, true)];
+    let mut error = 0.0;
+    for (p, exact) in singularities(&ex, x, lo, hi) {
+        if !exact {
+            error += singular_tail(&ex, x, p).ok_or("Integral diverges")?;
+        }
+        bounds.push((Point::Finite(e!(p)), exact));
+    }
+    bounds.push((b, true));
+    let mut value = e!(c!());
+    for piece in bounds.windows(2) {
+        let [(a, exact_a), (b, exact_b)] = piece else {
+            unreachable!()
+        };
+        let piece = definite_piece(&ex, x, a, b, *exact_a && *exact_b)?;
+        value = value + piece.value;
+        error += piece.error;
+    }
+    Ok(Definite {
+        value: value.simplify(),
+        error,
+    })
+}
+
+/// Integral over an interval without singularities inside it. Bounds that are not `exact`
+/// sit only close to a singularity, so the antiderivative is not used there, and the
+/// quadrature has to converge instead.
+fn definite_piece(
+    ex: &Expression,
+    x: Var,
+    a: &Point,
+    b: &Point,
+    exact_bounds: bool,
+) -> Result<Definite, String> {
+    let exact = match exact_bounds {
+        true => exact_definite(ex, x, a, b)?,
+        false => None,
+    };
+    let checkable = ex.vars().iter().all(|&v| v == x)
+        && numeric_bound(a).is_some()
+        && numeric_bound(b).is_some();
+    let numeric = match checkable {
+        true => numeric_definite(ex, x, a, b),
+        false => Err("Integrand or bounds are symbolic".to_string()),
+    };
+    match (exact, numeric) {
+        (Some(exact), Ok((value, error))) => match exact.as_const() {
+            Some(v) if (v - value).abs() <= 10.0 * error + 1e-9 * value.abs().max(1.0) => {
+                Ok(Definite {
+                    value: exact,
+                    error: 0.0,
+                })
+            }
+            _ => Ok(Definite {
+                value: e!(value),
+                error,
+            }),
+        },
+        (Some(_), Err(e)) if checkable => Err(format!("Quadrature failed: {e}")),
+        (Some(exact), Err(_)) => Ok(Definite {
+            value: exact,
+            error: 0.0,
+        }),
+        (None, Ok((value, error))) if !exact_bounds && error > 1e-6 * value.abs().max(1.0) => {
+            Err("Integral diverges".to_string())
+        }
+        (None, Ok((value, error))) => Ok(Definite {
+            value: e!(value),
+            error,
+        }),
+        (None, Err(e)) => Err(e),
+    }
+}
+
+fn unevaluated(ex: &Expression) -> bool {
+    matches!(ex.kind(), ExprKind::Integral(_)) || ex.args().iter().any(unevaluated)
+}
+
+/// Difference of the antiderivative's limits at the bounds, failing only when one of them
+/// is infinite
+fn exact_definite(
+    ex: &Expression,
+    x: Var,
+    a: &Point,
+    b: &Point,
+) -> Result<Option<Expression>, String> {
+    let f = integrate(ex.clone(), x);
+    if unevaluated(&f) {
+        return Ok(None);
+    }
+    let reversed = match (numeric_bound(a), numeric_bound(b)) {
+        (Some(a), Some(b)) => a > b,
+        _ => matches!((a, b), (Point::PosInf, _) | (_, Point::NegInf)),
+    };
+    let (from, to) = match reversed {
+        false => (Direction::Plus, Direction::Minus),
+        true => (Direction::Minus, Direction::Plus),
+    };
+    let (Ok(fa), Ok(fb)) = (
+        f.clone().limit(x, a.clone(), from),
+        f.limit(x, b.clone(), to),
+    ) else {
+        return Ok(None);
+    };
+    match (fa, fb) {
+        (Point::Finite(fa), Point::Finite(fb)) => Ok(Some((fb - fa).simplify())),
+        _ => Err("Integral diverges".to_string()),
+    }
+}
+
+/// Points strictly between `a` and `b` where `ex` is singular, the zeros of its denominators
+/// and of the arguments of its logarithms, in order from `a` to `b`, with whether they are
+/// exact. They are found on a grid and refined, and are exact when they round to rationals
+/// with small denominators.
+fn singularities(ex: &Expression, x: Var, a: f64, b: f64) -> Vec<(f64, bool)> {
+    const GRID: usize = 512;
+    let mut bases = Vec::new();
+    vanishing(ex, &mut bases);
+    let (lo, hi) = (a.min(b), a.max(b));
+    // `t` in `(0, 1)` onto `(lo, hi)`
+    let at = |t: f64| match (lo.is_finite(), hi.is_finite()) {
+        (true, true) => lo + t * (hi - lo),
+        (true, false) => lo + t / (1.0 - t),
+        (false, true) => hi - (1.0 - t) / t,
+        (false, false) => (2.0 * t - 1.0) / (t * (1.0 - t)),
+    };
+    let mut points: Vec<f64> = Vec::new();
+    for base in bases.iter().filter(|b| b.has(x)) {
+        let u = |t: f64| {
+            let mut values = vec![c!(); x.id + 1];
+            values[x.id] = c!(at(t));
+            base.0.eval(&values)
+        };
+        let grid = (1..GRID)
+            .map(|i| {
+                let t = i as f64 / GRID as f64;
+                (t, u(t))
+            })
+            .collect::<Vec<_>>();
+        let scale = grid
+            .iter()
+            .map(|(_, v)| v.abs())
+            .filter(|v| v.is_finite())
+            .fold(0.0, f64::max);
+        let mut candidates = Vec::new();
+        for w in grid.windows(2) {
+            let [(t0, u0), (t1, u1)] = *w else {
+                unreachable!()
+            };
+            if u0.is_finite() && u1.is_finite() && u0.re.signum() != u1.re.signum() {
+                candidates.push(bisect(|t| u(t).re, t0, t1));
+            }
+        }
+        // Zeros without a sign change show as dips of `|u|`
+        for w in grid.windows(3) {
+            let [(t0, u0), (_, u1), (t2, u2)] = *w else {
+                unreachable!()
+            };
+            if u1.abs() < u0.abs() && u1.abs() <= u2.abs() {
+                candidates.push(golden(|t| u(t).abs(), t0, t2));
+            }
+        }
+        for t in candidates {
+            let p = at(t);
+            let close = |q: f64| (p - q).abs() <= 1e-6 * p.abs().max(1.0);
+            if u(t).abs() <= 1e-9 * scale
+                && !close(lo)
+                && !close(hi)
+                && !points.iter().any(|&q| close(q))
+            {
+                points.push(p);
+            }
+        }
+    }
+    points.sort_by(f64::total_cmp);
+    if a > b {
+        points.reverse();
+    }
+    points
+        .into_iter()
+        .map(|p| exact_point(p).map_or((p, false), |p| (p, true)))
+        .collect()
+}
+
+/// Integral of `|ex|` over the rounding error in the position of its singularity at `p`,
+/// from how it grows like `|x - p|^-k` there, or `None` when `k` is close to 1 or above on
+/// either side and the integral diverges
+fn singular_tail(ex: &Expression, x: Var, p: f64) -> Option<f64> {
+    let f = |t: f64| {
+        let mut values = vec![c!(); x.id + 1];
+        values[x.id] = c!(t);
+        ex.0.eval(&values).abs()
+    };
+    let h = 1e-6 * p.abs().max(1.0);
+    let rounding = 16.0 * f64::EPSILON * p.abs().max(1.0);
+    let mut tail = 0.0;
+    for side in [-1.0, 1.0] {
+        let k = (f(p + side * h * 1e-2) / f(p + side * h)).ln() / 100f64.ln();
+        if k.is_nan() || k >= 0.99 {
+            return None;
+        }
+        // `∫ |x - p|^-k` over `rounding`, scaled to `ex` at the end of that span
+        tail += f(p + side * rounding) * rounding / (1.0 - k);
+    }
+    Some(tail)
+}
+
+/// Denominators and arguments of logarithms in `ex`, which make it singular where they vanish
+fn vanishing(ex: &Expression, found: &mut Vec<Expression>) {
+    match ex.kind() {
+        ExprKind::Exp => {
+            for (base, k) in as_power(ex.clone()) {
+                let Some(base) = base else {
+                    vanishing(&k, found);
+                    continue;
+                };
+                if k.as_const().is_some_and(|k| k.re < 0.0) {
+                    found.push(base.clone());
+                }
+                vanishing(&base, found);
+                vanishing(&k, found);
+            }
+        }
+        ExprKind::Ln => {
+            let arg = ex.args().pop().unwrap();
+            vanishing(&arg, found);
+            found.push(arg);
+        }
+        _ => ex.args().iter().for_each(|arg| vanishing(arg, found)),
+    }
+}
+
+/// Zero of `f` between `a` and `b`, where it changes sign
+fn bisect(f: impl Fn(f64) -> f64, mut a: f64, mut b: f64) -> f64 {
+    let fa = f(a);
+    for _ in 0..100 {
+        let m = 0.5 * (a + b);
+        if m == a || m == b {
+            break;
+        }
+        match f(m).signum() == fa.signum() {
+            true => a = m,
+            false => b = m,
+        }
+    }
+    0.5 * (a + b)
+}
+
+/// Minimum of `f` between `a` and `b` by golden section search
+fn golden(f: impl Fn(f64) -> f64, mut a: f64, mut b: f64) -> f64 {
+    let r = 0.5 * (5f64.sqrt() - 1.0);
+    for _ in 0..100 {
+        let (c, d) = (b - r * (b - a), a + r * (b - a));
+        match f(c) < f(d) {
+            true => b = d,
+            false => a = c,
+        }
+    }
+    0.5 * (a + b)
+}
+
+/// `p` rounded to a rational with a small denominator when it is close to one, or `None`
+fn exact_point(p: f64) -> Option<f64> {
+    (1..=12).find_map(|den| {
+        let num = (p * den as f64).round();
+        let q = num / den as f64;
+        ((p - q).abs() <= 1e-7 * p.abs().max(1.0)).then_some(q)
+    })
+}
+
+fn numeric_bound(p: &Point) -> Option<f64> {
+    match p {
+        Point::Finite(p) => p
+            .clone()
+            .simplify()
+            .as_const()
+            .filter(|c| c.im == 0.0)
+            .map(|c| c.re),
+        Point::PosInf => Some(f64::INFINITY),
+        Point::NegInf => Some(f64::NEG_INFINITY),
+    }
+}
+
+fn numeric_definite(
+    ex: &Expression,
+    x: Var,
+    a: &Point,
+    b: &Point,
+) -> Result<(Complex64, f64), String> {
+    let (Some(a), Some(b)) = (numeric_bound(a), numeric_bound(b)) else {
+        return Err("Bounds are not real numbers".to_string());
+    };
+    let f = |t: f64| {
+        let mut values = vec![c!(); x.id + 1];
+        values[x.id] = c!(t);
+        ex.0.eval(&values)
+    };
+    let neg = |(v, e): (Complex64, f64)| (-v, e);
+    match (a.is_finite(), b.is_finite()) {
+        _ if a == b => Ok((c!(), 0.0)),
+        (true, true) => quadrature::quad(&f, a, b),
+        (true, false) if b > 0.0 => quadrature::half_line(&f, a, 1.0),
+        (true, false) => quadrature::half_line(&f, a, -1.0).map(neg),
+        (false, true) if a < 0.0 => quadrature::half_line(&f, b, -1.0),
+        (false, true) => quadrature::half_line(&f, b, 1.0).map(neg),
+        (false, false) => {
+            let (pv, pe) = quadrature::half_line(&f, 0.0, 1.0)?;
+            let (nv, ne) = quadrature::half_line(&f, 0.0, -1.0)?;
+            Ok(match a < b {
+                true => (pv + nv, pe + ne),
+                false => (-pv - nv, pe + ne),
+            })
+        }
+    }
+}
+
+fn terms(ex: &Expression) -> Vec<Expression> {
+    match ex.kind() {
+        ExprKind::Add => ex.args(),
+        _ => vec![ex.clone()],
+    }
+}
+
+fn factors(ex: &Expression) -> Vec<Expression> {
+    match ex.kind() {
+        ExprKind::Mul => ex.args(),
+        _ => vec![ex.clone()],
+    }
+}
+
+fn sum(terms: Vec<Expression>) -> Expression {
+    match terms.len() {
+        0 => e!(c!()),
+        1 => terms.into_iter().next().unwrap(),
+        _ => Expression::node(ExprKind::Add, terms),
+    }
+}
+
+fn product(factors: Vec<Expression>) -> Expression {
+    match factors.len() {
+        0 => e!(c!(+)),
+        1 => factors.into_iter().next().unwrap(),
+        _ => Expression::node(ExprKind::Mul, factors),
+    }
+}
+
+/// A variable no other variable of `ex` or `x` collides with
+fn fresh(ex: &Expression, x: Var) -> Var {
+    let id = ex
+        .vars()
+        .into_iter()
+        .chain([x])
+        .filter(|v| !v.is_slot())
+        .map(|v| v.id)
+        .max()
+        .unwrap_or(0);
+    Var { id: id + 1 }
+}
+
+/// Replaces every subexpression structurally equal to `key` by `with`
+fn replace(ex: &Expression, key: &str, with: &Expression) -> Expression {
+    if ex.key() == key {
+        return with.clone();
+    }
+    match ex.kind() {
+        ExprKind::Var(_) | ExprKind::Const(_) => ex.clone(),
+        kind => Expression::node(kind, ex.args().iter().map(|a| replace(a, key, with))),
+    }
+}
+
+/// Logarithms, and the arguments of every exponential, logarithm and absolute value and
+/// every base of a power
+fn inner(ex: &Expression, found: &mut Vec<Expression>) {
+    match ex.kind() {
+        ExprKind::Exp => {
+            found.extend(as_power(ex.clone()).into_iter().filter_map(|(b, _)| b));
+            found.push(ex.args().pop().unwrap());
+        }
+        ExprKind::Ln => {
+            found.push(ex.clone());
+            found.push(ex.args().pop().unwrap());
+        }
+        ExprKind::Abs => found.push(ex.args().pop().unwrap()),
+        _ => (),
+    }
+    for arg in ex.args() {
+        inner(&arg, found);
+    }
+}
+
+struct Integrator {
+    x: Var,
+    steps: usize,
+}
+
+type Rule = fn(&mut Integrator, &Expression, usize) -> Option<Expression>;
+
+impl Integrator {
+    fn new(x: Var) -> Self {
+        Integrator { x, steps: 0 }
+    }
+
+    /// Integrates in `t` instead, sharing the step budget
+    fn within(&mut self, t: Var, f: &Expression, depth: usize) -> Option<Expression> {
+        let mut sub = Integrator {
+            x: t,
+            steps: self.steps,
+        };
+        let result = sub.integrate(f, depth);
+        self.steps = sub.steps;
+        result
+    }
+
+    fn integrate(&mut self, f: &Expression, depth: usize) -> Option<Expression> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS || depth > MAX_DEPTH {
+            return None;
+        }
+        let x = self.x;
+        if !f.has(x) {
+            return Some(f.clone() * e!(x));
+        }
+        if f.kind() == ExprKind::Add {
+            let terms = f
+                .args()
+                .iter()
+                .map(|t| self.integrate(t, depth))
+                .collect::<Option<_>>()?;
+            return Some(sum(terms));
+        }
+        let (consts, f): (Vec<_>, Vec<_>) = factors(f).into_iter().partition(|f| !f.has(x));
+        let f = product(f);
+        let rules: [Rule; 6] = [
+            Self::rational,
+            Self::power,
+            Self::exponential,
+            Self::exponential_substitution,
+            Self::substitution,
+            Self::by_parts,
+        ];
+        let result = rules.iter().find_map(|rule| rule(self, &f, depth))?;
+        Some(product(consts) * result)
+    }
+
+    /// Polynomials and quotients of polynomials, through partial fractions
+    fn rational(&mut self, f: &Expression, _: usize) -> Option<Expression> {
+        let x = self.x;
+        let (num, den) = as_rational(f, x)?;
+        let (quot, rem) = divmod(&num, &den);
+        let mut result = vec![poly_integral(&quot, x)];
+        if rem.is_empty() {
+            return Some(sum(result));
+        }
+        let lc = den.last().unwrap().clone();
+        let roots = roots(&den)?;
+        for (i, (r, m)) in roots.iter().enumerate() {
+            let mut rest = vec![lc.clone()];
+            for (j, (s, n)) in roots.iter().enumerate() {
+                if i != j {
+                    for _ in 0..*n {
+                        rest = poly_mul(&rest, &[-s.clone(), e!(c!(+))]);
+                    }
+                }
+            }
+            let coeffs = series_div(&shift(&rem, r), &shift(&rest, r), *m);
+            let h = e!(x) - r.clone();
+            for (k, c) in coeffs.into_iter().enumerate() {
+                let p = (m - k) as f64;
+                result.push(match m - k {
+                    1 => c * h.clone().ln(),
+                    _ => c * h.clone().pow(e!(1.0 - p)) * e!(1.0 / (1.0 - p)),
+                });
+            }
+        }
+        Some(sum(result))
+    }
+
+    /// `u^k` for `u` linear in `x` and `k` free of it
+    fn power(&mut self, f: &Expression, _: usize) -> Option<Expression> {
+        let x = self.x;
+        let [(Some(u), k)] = &as_power(f.clone())[..] else {
+            return None;
+        };
+        let du = u.clone().diff(x).simplify();
+        if k.has(x) || du.has(x) {
+            return None;
+        }
+        let k1 = (k.clone() + e!(c!(+))).simplify();
+        Some(match k1.is_zero() {
+            true => u.clone().ln() / du,
+            false => u.clone().pow(k1.clone()) / (k1 * du),
+        })
+    }
+
+    /// `e^u` for `u` linear in `x`
+    fn exponential(&mut self, f: &Expression, _: usize) -> Option<Expression> {
+        if f.kind() != ExprKind::Exp {
+            return None;
+        }
+        let du = f.args()[0].clone().diff(self.x).simplify();
+        match du.has(self.x) || du.is_zero() {
+            true => None,
+            false => Some(f.clone() / du),
+        }
+    }
+
+    /// Rational functions of `e^(a_i x)` with commensurate `a_i`, through `t = e^(kx)`
+    fn exponential_substitution(&mut self, f: &Expression, depth: usize) -> Option<Expression> {
+        let x = self.x;
+        let mut exps = Vec::new();
+        collect_exps(f, x, &mut exps);
+        let slopes = exps
+            .iter()
+            .map(|u| u.args()[0].clone().diff(x).simplify().as_const())
+            .collect::<Option<Vec<_>>>()?;
+        let &first = slopes.first()?;
+        let ratios = slopes.iter().map(|&a| a / first).collect::<Vec<_>>();
+        let den = (1..=12).find(|&d| {
+            ratios.iter().all(|r| {
+                r.im.abs() < 1e-12 && (r.re * d as f64 - (r.re * d as f64).round()).abs() < 1e-9
+            })
+        })?;
+        let k = first / den as f64;
+        let t = fresh(f, x);
+        let mut g = f.clone();
+        for (u, r) in exps.iter().zip(ratios) {
+            let n = (r.re * den as f64).round();
+            let rest = (u.args()[0].clone() - e!(k * n) * e!(x)).simplify();
+            g = replace(&g, &u.key(), &(rest.exp() * e!(t).pow(e!(n))));
+        }
+        let g = (g / (e!(k) * e!(t))).simplify();
+        if g.has(x) {
+            return None;
+        }
+        let mut sub = Integrator {
+            x: t,
+            steps: self.steps,
+        };
+        let result = sub.rational(&g, depth);
+        self.steps = sub.steps;
+        Some(result?.subs(t, (e!(k) * e!(x)).exp()))
+    }
+
+    /// `u'(x) g(u(x))`, integrated as `g` in `u`
+    fn substitution(&mut self, f: &Expression, depth: usize) -> Option<Expression> {
+        let x = self.x;
+        let mut candidates = Vec::new();
+        inner(f, &mut candidates);
+        let mut seen = Vec::new();
+        let t = fresh(f, x);
+        for u in candidates {
+            let key = u.key();
+            if !u.has(x) || u.kind() == ExprKind::Var(x) || seen.contains(&key) {
+                continue;
+            }
+            seen.push(key.clone());
+            let du = u.clone().diff(x).simplify();
+            if du.is_zero() {
+                continue;
+            }
+            let g = replace(&(f.clone() / du).simplify(), &key, &e!(t)).simplify();
+            if g.has(x) {
+                continue;
+            }
+            if let Some(result) = self.within(t, &g, depth + 1) {
+                return Some(result.subs(t, u));
+            }
+        }
+        None
+    }
+
+    /// `∫u dv = uv - ∫v du`, taking logarithms as `u` first and polynomials second
+    fn by_parts(&mut self, f: &Expression, depth: usize) -> Option<Expression> {
+        let x = self.x;
+        let is_log = |f: &Expression| {
+            f.kind() == ExprKind::Ln
+                || as_power(f.clone())
+                    .iter()
+                    .any(|(b, _)| b.as_ref().is_some_and(|b| b.kind() == ExprKind::Ln))
+        };
+        let is_poly = |f: &Expression| as_rational(f, x).is_some_and(|(_, den)| den.len() == 1);
+        let (u, dv): (Vec<_>, Vec<_>) = factors(f).into_iter().partition(is_log);
+        let (u, dv) = match u.is_empty() {
+            false => (u, dv),
+            true => factors(f).into_iter().partition(is_poly),
+        };
+        if u.is_empty() || (dv.is_empty() && !u.iter().any(is_log)) {
+            return None;
+        }
+        let (u, dv) = (product(u), product(dv));
+        let v = self.integrate(&dv, depth + 1)?.simplify();
+        let vdu = (v.clone() * u.clone().diff(x)).simplify();
+        let rest = self.integrate(&vdu, depth + 1)?;
+        Some(u * v - rest)
+    }
+}
+
+/// Every exponential of `x` that is not a power of some base
+fn collect_exps(ex: &Expression, x: Var, exps: &mut Vec<Expression>) {
+    let pure = ex.kind() == ExprKind::Exp && as_power(ex.clone()).iter().all(|(b, _)| b.is_none());
+    if pure && ex.has(x) {
+        if !exps.iter().any(|e| e.key() == ex.key()) {
+            exps.push(ex.clone());
+        }
+        return;
+    }
+    for arg in ex.args() {
+        collect_exps(&arg, x, exps);
+    }
+}
+
+/// Numerator and denominator of `f` as polynomials in `x`
+fn as_rational(f: &Expression, x: Var) -> Option<(Poly, Poly)> {
+    const MAX_POWER: f64 = 16.0;
+
+    if !f.has(x) {
+        return Some((vec![f.clone()], vec![e!(c!(+))]));
+    }
+    let (num, den) = match f.kind() {
+        ExprKind::Var(_) => (vec![e!(c!()), e!(c!(+))], vec![e!(c!(+))]),
+        ExprKind::Add => {
+            let mut num = vec![];
+            let mut den = vec![e!(c!(+))];
+            for term in f.args() {
+                let (n, d) = as_rational(&term, x)?;
+                if poly_key(&d) == poly_key(&den) {
+                    num = poly_add(&num, &n);
+                } else {
+                    num = poly_add(&poly_mul(&num, &d), &poly_mul(&n, &den));
+                    den = poly_mul(&den, &d);
+                }
+            }
+            (num, den)
+        }
+        ExprKind::Mul => {
+            let mut num = vec![e!(c!(+))];
+            let mut den = vec![e!(c!(+))];
+            for factor in f.args() {
+                let (n, d) = as_rational(&factor, x)?;
+                num = poly_mul(&num, &n);
+                den = poly_mul(&den, &d);
+            }
+            (num, den)
+        }
+        ExprKind::Exp => {
+            let [(Some(base), k)] = &as_power(f.clone())[..] else {
+                return None;
+            };
+            let k = k
+                .as_const()
+                .filter(|k| k.im == 0.0 && k.re.fract() == 0.0 && k.re.abs() <= MAX_POWER)?;
+            let (n, d) = as_rational(base, x)?;
+            let (n, d) = match k.re > 0.0 {
+                true => (n, d),
+                false => (d, n),
+            };
+            let mut num = vec![e!(c!(+))];
+            let mut den = vec![e!(c!(+))];
+            for _ in 0..k.re.abs() as usize {
+                num = poly_mul(&num, &n);
+                den = poly_mul(&den, &d);
+            }
+            (num, den)
+        }
+        _ => return None,
+    };
+    let (num, den) = (trim(num), trim(den));
+    if den.is_empty() {
+        return None;
+    }
+    Some((num, den))
+}
+
+fn poly_key(p: &Poly) -> String {
+    p.iter().map(Expression::key).collect::<Vec<_>>().join(";")
+}
+
+/// Simplifies the coefficients and drops vanishing leading ones
+fn trim(p: Poly) -> Poly {
+    let mut p = p.into_iter().map(|c| c.simplify()).collect::<Vec<_>>();
+    while p.last().is_some_and(Expression::is_zero) {
+        p.pop();
+    }
+    p
+}
+
+fn poly_add(a: &[Expression], b: &[Expression]) -> Poly {
+    let zero = e!(c!());
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            let a = a.get(i).unwrap_or(&zero).clone();
+            let b = b.get(i).unwrap_or(&zero).clone();
+            (a + b).simplify()
+        })
+        .collect()
+}
+
+fn poly_mul(a: &[Expression], b: &[Expression]) -> Poly {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut c = vec![e!(c!()); a.len() + b.len() - 1];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            c[i + j] = c[i + j].clone() + a.clone() * b.clone();
+        }
+    }
+    trim(c)
+}
+
+/// Quotient and remainder of polynomial long division
+fn divmod(num: &[Expression], den: &[Expression]) -> (Poly, Poly) {
+    let n = den.len() - 1;
+    let inv = den[n].clone().inv().simplify();
+    let mut rem = num.to_vec();
+    let mut quot = vec![e!(c!()); num.len().saturating_sub(n)];
+    while rem.len() > n {
+        let k = rem.len() - 1 - n;
+        let q = (rem.last().unwrap().clone() * inv.clone()).simplify();
+        for (i, d) in den.iter().enumerate() {
+            rem[k + i] = (rem[k + i].clone() - q.clone() * d.clone()).simplify();
+        }
+        rem.pop();
+        quot[k] = q;
+        rem = trim(rem);
+    }
+    (quot, rem)
+}
+
+fn poly_integral(p: &[Expression], x: Var) -> Expression {
+    sum(p
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c.clone() * e!(x).pow(e!((i + 1) as f64)) * e!(1.0 / (i + 1) as f64))
+        .collect())
+}
+
+/// Coefficients of `p(r + h)` in `h`
+fn shift(p: &[Expression], r: &Expression) -> Poly {
+    let mut q = p.to_vec();
+    // Repeated synthetic division by `x - r` leaves the Taylor coefficients at `r`
+    for k in 0..q.len() {
+        for i in (k..q.len().saturating_sub(1)).rev() {
+            q[i] = (q[i].clone() + r.clone() * q[i + 1].clone()).simplify();
+        }
+    }
+    q
+}
+
+/// First `n` coefficients of the power series `a / b`
+fn series_div(a: &[Expression], b: &[Expression], n: usize) -> Vec<Expression> {
+    let zero = e!(c!());
+    let inv = b[0].clone().inv().simplify();
+    let mut c: Vec<Expression> = Vec::with_capacity(n);
+    for k in 0..n {
+        let mut s = a.get(k).unwrap_or(&zero).clone();
+        for j in 1..=k {
+            s = s - b.get(j).unwrap_or(&zero).clone() * c[k - j].clone();
+        }
+        c.push((s * inv.clone()).simplify());
+    }
+    c
+}
+
+/// Roots of `p` with their multiplicities, symbolically up to quadratics and numerically
+/// for constant coefficients
+pub(crate) fn roots(p: &[Expression]) -> Option<Vec<(Expression, usize)>> {
+    let zeros = p.iter().take_while(|c| c.is_zero()).count();
+    let p = &p[zeros..];
+    let mut roots = match p.len() {
+        0 => return None,
+        1 => vec![],
+        2 => vec![((-p[0].clone() / p[1].clone()).simplify(), 1)],
+        3 => {
+            let (a, b, c) = (p[2].clone(), p[1].clone(), p[0].clone());
+            let disc = (b.clone() * b.clone() - e!(4.0) * a.clone() * c).simplify();
+            let half = (e!(-0.5) / a).simplify();
+            match disc.is_zero() {
+                true => vec![((b * half).simplify(), 2)],
+                false => {
+                    let sq = disc.pow(e!(0.5));
+                    vec![
+                        ((half.clone() * (b.clone() - sq.clone())).simplify(), 1),
+                        ((half * (b + sq)).simplify(), 1),
+                    ]
+                }
+            }
+        }
+        _ => numeric_roots(
+            &p.iter()
+                .map(Expression::as_const)
+                .collect::<Option<Vec<_>>>()?,
+        )
+        .into_iter()
+        .map(|(r, m)| (e!(r), m))
+        .collect(),
+    };
+    if zeros > 0 {
+        roots.push((e!(c!()), zeros));
+    }
+    Some(roots)
+}
+
+/// Durand–Kerner iteration, with nearby roots merged into multiple ones
+fn numeric_roots(p: &[Complex64]) -> Vec<(Complex64, usize)> {
+    let n = p.len() - 1;
+    let lc = p[n];
+    let eval = |z: Complex64| p.iter().rev().fold(c!(), |acc, &c| acc * z + c / lc);
+    let mut z = (0..n)
+        .map(|k| c!(0.4; 0.9).powi(k as i32))
+        .collect::<Vec<_>>();
+    for _ in 0..1000 {
+        let mut delta = 0.0f64;
+        for i in 0..n {
+            let denom = (0..n)
+                .filter(|&j| j != i)
+                .fold(c!(+), |acc, j| acc * (z[i] - z[j]));
+            let step = eval(z[i]) / denom;
+            if step.is_finite() {
+                z[i] -= step;
+                delta = delta.max(step.abs());
+            }
+        }
+        if delta < 1e-15 {
+            break;
+        }
+    }
+    let mut roots: Vec<(Complex64, usize)> = Vec::new();
+    for z in z {
+        match roots
+            .iter_mut()
+            .find(|(r, _)| (*r - z).abs() < 1e-4 * r.abs().max(1.0))
+        {
+            Some((r, m)) => {
+                *r = (*r * *m as f64 + z) / (*m + 1) as f64;
+                *m += 1;
+            }
+            None => roots.push((z, 1)),
+        }
+    }
+    roots
+        .into_iter()
+        .map(|(r, m)| (c!(snap(r.re); snap(r.im)), m))
+        .collect()
+}
+
+/// Rounds to a nearby fraction with a small denominator
+fn snap(v: f64) -> f64 {
+    for d in 1..=12 {
+        let n = (v * d as f64).round();
+        if (v * d as f64 - n).abs() < 1e-6 {
+            return n / d as f64;
+        }
+    }
+    v
+}
@@ -0,0 +1,428 @@
+use std::cmp::Ordering;
+
+use super::{sample_value, SAMPLES};
+use crate::{c, e, expression::*, Expression};
+
+const MAX_DEPTH: usize = 10;
+const MAX_STEPS: usize = 20_000;
+
+#[derive(Debug, Clone)]
+pub enum Point {
+    Finite(Expression),
+    PosInf,
+    NegInf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Plus,
+    Minus,
+    Both,
+}
+
+impl<T: Clone> Expressable<T>
+where
+    Expression: From<Expressable<T>>,
+{
+    /// Limit of the expression as `x` approaches `point` from `dir`.
+    ///
+    /// The leading term of the series expansion decides the limit when it exists. Otherwise
+    /// the limit is taken structurally: indeterminate `0*inf` forms go through L'Hôpital's
+    /// rule on `diff`, and when that stalls, through a comparison of logarithmic growth rates.
+    /// Fails when the result depends on the sign of a variable other than `x`.
+    pub fn limit(self, x: Var, point: Point, dir: Direction) -> Result<Point, String> {
+        limit(e!(self), x, point, dir)
+    }
+}
+
+fn limit(ex: Expression, x: Var, point: Point, dir: Direction) -> Result<Point, String> {
+    let p = match point {
+        Point::PosInf => return one_sided(ex.simplify(), x, Side::Infinity),
+        Point::NegInf => return one_sided(ex.subs(x, -e!(x)).simplify(), x, Side::Infinity),
+        Point::Finite(p) if p.has(x) => {
+            return Err("Limit point depends on the limit variable".to_string())
+        }
+        Point::Finite(p) => p,
+    };
+    let right = || {
+        one_sided(
+            ex.clone().subs(x, p.clone() + e!(x)).simplify(),
+            x,
+            Side::Zero,
+        )
+    };
+    let left = || {
+        one_sided(
+            ex.clone().subs(x, p.clone() - e!(x)).simplify(),
+            x,
+            Side::Zero,
+        )
+    };
+    match dir {
+        Direction::Plus => right(),
+        Direction::Minus => left(),
+        Direction::Both => match (right()?, left()?) {
+            (Point::Finite(a), Point::Finite(b)) if (a.clone() - b.clone()).is_zero() => {
+                Ok(Point::Finite(a))
+            }
+            (Point::PosInf, Point::PosInf) => Ok(Point::PosInf),
+            (Point::NegInf, Point::NegInf) => Ok(Point::NegInf),
+            _ => Err("One-sided limits differ".to_string()),
+        },
+    }
+}
+
+/// Limit as `x -> 0+` or `x -> +inf`, read off the leading term of the series when it
+/// exists, and computed structurally otherwise
+fn one_sided(ex: Expression, x: Var, side: Side) -> Result<Point, String> {
+    match leading_term(&local(&ex, x, side), x) {
+        Ok((k, c)) if !c.has(x) => match k.cmp(&Rational::ZERO) {
+            Ordering::Less if sign(&c)? > 0.0 => Ok(Point::PosInf),
+            Ordering::Less => Ok(Point::NegInf),
+            Ordering::Equal => Ok(Point::Finite(c.simplify())),
+            Ordering::Greater => Ok(Point::Finite(e!(c!()))),
+        },
+        _ => Limiter::new(x, side).run(ex),
+    }
+}
+
+/// `ex` with `x -> 1/x` for a limit at infinity, so that it is taken as `x -> 0+`
+fn local(ex: &Expression, x: Var, side: Side) -> Expression {
+    match side {
+        Side::Zero => ex.clone(),
+        Side::Infinity => ex.clone().subs(x, e!(x).inv()).simplify(),
+    }
+}
+
+/// Where the variable of a one-sided limit goes, from above. Every limit is brought to one
+/// of these: `x -> p+` by substituting `p + x`, `x -> p-` by substituting `p - x` and
+/// `x -> -inf` by substituting `-x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Zero,
+    Infinity,
+}
+
+struct Limiter {
+    x: Var,
+    side: Side,
+    steps: usize,
+}
+
+impl Limiter {
+    fn new(x: Var, side: Side) -> Self {
+        Limiter { x, side, steps: 0 }
+    }
+
+    fn run(mut self, ex: Expression) -> Result<Point, String> {
+        match self.lim(&ex, 0)? {
+            Point::Finite(v) => Ok(Point::Finite(v.simplify())),
+            inf => Ok(inf),
+        }
+    }
+
+    fn lim(&mut self, ex: &Expression, depth: usize) -> Result<Point, String> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS || depth > MAX_DEPTH {
+            return Err("Could not determine the limit".to_string());
+        }
+        if !ex.has(self.x) {
+            return Ok(Point::Finite(ex.clone()));
+        }
+        match ex.kind() {
+            ExprKind::Var(_) => Ok(match self.side {
+                Side::Infinity => Point::PosInf,
+                Side::Zero => Point::Finite(e!(c!())),
+            }),
+            ExprKind::Add => self.lim_add(ex.args(), depth),
+            ExprKind::Mul => self.lim_mul(ex.args(), depth),
+            ExprKind::Exp => {
+                let u = ex.args().pop().unwrap();
+                if let [(Some(base), k)] = &as_power(ex.clone())[..] {
+                    if !k.has(self.x) {
+                        return self.lim_pow(base, k, depth);
+                    }
+                }
+                Ok(match self.lim(&u, depth)? {
+                    Point::Finite(v) => Point::Finite(v.exp()),
+                    Point::PosInf => Point::PosInf,
+                    Point::NegInf => Point::Finite(e!(c!())),
+                })
+            }
+            ExprKind::Ln => match self.lim(&ex.args()[0], depth)? {
+                Point::Finite(v) if v.is_zero() => Ok(Point::NegInf),
+                Point::Finite(v) => Ok(Point::Finite(v.ln())),
+                Point::PosInf => Ok(Point::PosInf),
+                Point::NegInf => Err("Logarithm of negative infinity".to_string()),
+            },
+            ExprKind::Abs => Ok(match self.lim(&ex.args()[0], depth)? {
+                Point::Finite(v) => Point::Finite(v.abs()),
+                _ => Point::PosInf,
+            }),
+            ExprKind::Integral(_) => Err("Limit of an unevaluated integral".to_string()),
+            ExprKind::Func(f) => {
+                let mut args = Vec::new();
+                for arg in ex.args() {
+                    match self.lim(&arg, depth)? {
+                        Point::Finite(v) => args.push(v),
+                        _ => return Err("Undefined function at infinity".to_string()),
+                    }
+                }
+                Ok(Point::Finite(Expression::node(ExprKind::Func(f), args)))
+            }
+            ExprKind::Derivative(..) => Err("Limit of an unevaluated derivative".to_string()),
+            ExprKind::ROOT | ExprKind::Const(_) => Ok(Point::Finite(ex.clone())),
+        }
+    }
+
+    fn lim_pow(
+        &mut self,
+        base: &Expression,
+        k: &Expression,
+        depth: usize,
+    ) -> Result<Point, String> {
+        let sk = sign(k)?;
+        let integer = k.as_const().filter(|k| k.im == 0.0 && k.re.fract() == 0.0);
+        let odd = integer.map(|k| k.re % 2.0 != 0.0);
+        let negative = |odd: Option<bool>| match odd {
+            Some(true) => Ok(Point::NegInf),
+            Some(false) => Ok(Point::PosInf),
+            None => Err("Limit is complex infinity".to_string()),
+        };
+        match self.lim(base, depth)? {
+            Point::Finite(v) if v.is_zero() => match sk > 0.0 {
+                true => Ok(Point::Finite(e!(c!()))),
+                false if self.sign_near(base)? > 0.0 => Ok(Point::PosInf),
+                false => negative(odd),
+            },
+            Point::Finite(v) => Ok(Point::Finite(v.pow(k.clone()))),
+            Point::PosInf if sk > 0.0 => Ok(Point::PosInf),
+            Point::NegInf if sk > 0.0 => negative(odd),
+            _ => Ok(Point::Finite(e!(c!()))),
+        }
+    }
+
+    fn lim_add(&mut self, terms: Vec<Expression>, depth: usize) -> Result<Point, String> {
+        let err = match self.lim_sum(terms.clone(), depth) {
+            Ok(point) => return Ok(point),
+            Err(err) => err,
+        };
+        let (numerator, denominator) = together(terms);
+        if denominator.as_const().is_some() {
+            return Err(err);
+        }
+        self.lim_mul(vec![numerator, denominator.inv()], depth + 1)
+    }
+
+    fn lim_sum(&mut self, terms: Vec<Expression>, depth: usize) -> Result<Point, String> {
+        let mut finite = e!(c!());
+        let mut pos = Vec::new();
+        let mut neg = Vec::new();
+        for term in terms {
+            match self.lim(&term, depth)? {
+                Point::Finite(v) => finite = finite + v,
+                Point::PosInf => pos.push(term),
+                Point::NegInf => neg.push(term),
+            }
+        }
+        let sum = |terms: Vec<Expression>| terms.into_iter().fold(e!(c!()), |a, b| a + b);
+        match (pos.is_empty(), neg.is_empty()) {
+            (true, true) => Ok(Point::Finite(finite)),
+            (false, true) => Ok(Point::PosInf),
+            (true, false) => Ok(Point::NegInf),
+            (false, false) => {
+                let (a, b) = (sum(pos), sum(neg));
+                let ratio = (b / a.clone()).simplify();
+                match self.lim(&ratio, depth + 1)? {
+                    Point::Finite(v) => {
+                        let w = e!(c!(+)) + v;
+                        if !w.is_zero() {
+                            return Ok(if sign(&w)? > 0.0 {
+                                Point::PosInf
+                            } else {
+                                Point::NegInf
+                            });
+                        }
+                        let rest = (e!(c!(+)) + ratio).simplify();
+                        self.lim_indeterminate(rest, a, 1.0, depth + 1)
+                    }
+                    _ => Ok(Point::NegInf),
+                }
+            }
+        }
+    }
+
+    fn lim_mul(&mut self, factors: Vec<Expression>, depth: usize) -> Result<Point, String> {
+        let mut finite = e!(c!(+));
+        let mut zeros = Vec::new();
+        let mut infs = Vec::new();
+        let mut sgn = 1.0;
+        for factor in factors {
+            match self.lim(&factor, depth)? {
+                Point::Finite(v) if v.is_zero() => zeros.push(factor),
+                Point::Finite(v) => finite = finite * v,
+                Point::PosInf => infs.push(factor),
+                Point::NegInf => {
+                    sgn = -sgn;
+                    infs.push(factor);
+                }
+            }
+        }
+        let product = |factors: Vec<Expression>| factors.into_iter().fold(e!(c!(+)), |a, b| a * b);
+        let result = match (zeros.is_empty(), infs.is_empty()) {
+            (true, true) => return Ok(Point::Finite(finite)),
+            (false, true) => return Ok(Point::Finite(e!(c!()))),
+            (true, false) if sgn > 0.0 => Point::PosInf,
+            (true, false) => Point::NegInf,
+            (false, false) => {
+                self.lim_indeterminate(product(zeros), product(infs), sgn, depth + 1)?
+            }
+        };
+        match result {
+            Point::Finite(v) => Ok(Point::Finite(finite * v)),
+            inf => {
+                let sgn = sign(&finite)? * if let Point::NegInf = inf { -1.0 } else { 1.0 };
+                Ok(if sgn > 0.0 {
+                    Point::PosInf
+                } else {
+                    Point::NegInf
+                })
+            }
+        }
+    }
+
+    /// Limit of `z * i`, where `z` tends to zero and `i` to the infinity of sign `sign_i`
+    fn lim_indeterminate(
+        &mut self,
+        z: Expression,
+        i: Expression,
+        sign_i: f64,
+        depth: usize,
+    ) -> Result<Point, String> {
+        let x = self.x;
+        let lhopital = |f: Expression, g: Expression| {
+            let g = g.inv().simplify();
+            (f.diff(x) / g.diff(x)).simplify()
+        };
+        let mut candidates = [
+            lhopital(i.clone(), z.clone()),
+            lhopital(z.clone(), i.clone()),
+        ];
+        candidates.sort_by_key(Expression::size);
+        for ratio in candidates {
+            if let Ok(point) = self.lim(&ratio, depth) {
+                return Ok(point);
+            }
+        }
+
+        let growth = (z.clone().abs().ln() + i.abs().ln()).simplify();
+        let point = self.lim(&growth, depth)?;
+        if let Point::NegInf = point {
+            return Ok(Point::Finite(e!(c!())));
+        }
+        let sgn = self.sign_near(&z)? * sign_i;
+        match point {
+            Point::Finite(c) => Ok(Point::Finite(e!(sgn) * c.exp())),
+            _ if sgn > 0.0 => Ok(Point::PosInf),
+            _ => Ok(Point::NegInf),
+        }
+    }
+
+    /// Sign of `ex` close to the limit point, from the leading term of its series when there
+    /// is one, and otherwise probing the point from ever further until the value is finite
+    fn sign_near(&self, ex: &Expression) -> Result<f64, String> {
+        if let Ok((_, c)) = leading_term(&local(ex, self.x, self.side), self.x) {
+            if !c.has(self.x) {
+                return sign(&c);
+            }
+        }
+        for at in [1e-8, 1e-4, 1e-2] {
+            let at = match self.side {
+                Side::Zero => at,
+                Side::Infinity => at.recip(),
+            };
+            if let Some(sgn) = sign_at(ex, &[(self.x, c!(at))])? {
+                return Ok(sgn);
+            }
+        }
+        Err("Could not determine the sign near the limit point".to_string())
+    }
+}
+
+/// Sign of a real, nonzero expression free of the limit variable
+fn sign(ex: &Expression) -> Result<f64, String> {
+    sign_at(ex, &[])?.ok_or_else(|| "Limit is complex or oscillating".to_string())
+}
+
+/// Sign of `ex` at `at`, with the other variables at sample points, each also tried with the
+/// opposite sign. `None` when the value is not finite, and an error when it is complex or its
+/// sign depends on those variables. Samples where only a flipped variable makes the value
+/// complex are skipped, taking the variable to be restricted to where `ex` is real.
+fn sign_at(ex: &Expression, at: &[(Var, Complex64)]) -> Result<Option<f64>, String> {
+    let free = ex
+        .vars()
+        .into_iter()
+        .filter(|v| at.iter().all(|(u, _)| u != v))
+        .collect::<Vec<_>>();
+    // No flip, each variable flipped on its own and all of them flipped
+    let mut flips = vec![Vec::new()];
+    if !free.is_empty() {
+        flips.extend(free.iter().map(|&v| vec![v]));
+        flips.push(free);
+    }
+    let mut sgn = None;
+    for flip in flips {
+        for sample in 0..SAMPLES {
+            let mut values = at.to_vec();
+            values.extend(flip.iter().map(|&v| (v, -sample_value(v.id, sample))));
+            let (value, _) = ex.eval_sampled(&values, sample);
+            let real = value.im.abs() <= 1e-9 * value.norm();
+            match (flip.is_empty(), value.is_finite() && real) {
+                (true, false) if value.is_finite() => {
+                    return Err("Limit is complex or oscillating".to_string())
+                }
+                (true, false) => return Ok(None),
+                (false, false) => continue,
+                _ => {}
+            }
+            if sgn.is_some_and(|s| s != value.re.signum()) {
+                return Err("Sign of the limit depends on a free parameter".to_string());
+            }
+            sgn = Some(value.re.signum());
+        }
+    }
+    Ok(sgn)
+}
+
+/// Brings a sum over a common denominator, returning `(numerator, denominator)`
+fn together(terms: Vec<Expression>) -> (Expression, Expression) {
+    let mut denominators: Vec<(String, Expression, f64)> = Vec::new();
+    for term in &terms {
+        let factors = match term.kind() {
+            ExprKind::Mul => term.args(),
+            _ => vec![term.clone()],
+        };
+        for factor in factors {
+            let [(Some(base), k)] = &as_power(factor)[..] else {
+                continue;
+            };
+            let Some(k) = k.as_const().filter(|k| k.im == 0.0 && k.re < 0.0) else {
+                continue;
+            };
+            let key = base.key();
+            match denominators.iter_mut().find(|(b, ..)| *b == key) {
+                Some((.., power)) => *power = power.max(-k.re),
+                None => denominators.push((key, base.clone(), -k.re)),
+            }
+        }
+    }
+    let denominator = denominators
+        .into_iter()
+        .fold(e!(c!(+)), |d, (_, base, k)| d * base.pow(k))
+        .simplify();
+    let numerator = terms
+        .into_iter()
+        .fold(e!(c!()), |n, term| n + term * denominator.clone())
+        .simplify();
+    (numerator, denominator)
+}
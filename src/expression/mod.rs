@@ -1,7 +1,27 @@
+pub mod compile;
+pub mod cubature;
+pub mod derivative;
+pub mod func;
+pub mod integrate;
+pub mod limit;
+pub mod ode;
+pub mod odeint;
+pub mod perturbation;
+pub mod quadrature;
+pub mod series;
 pub mod trig_func;
 pub mod var;
 use crate::{c, tree::*};
+pub use compile::*;
+pub use cubature::*;
+pub use func::*;
+pub use integrate::*;
+pub use limit::*;
 use num_complex::{Complex64, ComplexFloat};
+pub use ode::*;
+pub use odeint::*;
+pub use perturbation::*;
+pub use series::*;
 pub use trig_func::*;
 pub use var::*;
 
@@ -188,279 +208,111 @@ impl Expressand {
                 new_tree.finish_node();
                 new_tree
             }
-        }
-    }
-
-    fn simplify_rec(&self, id: NodeId) -> Tree {
-        match self.tree.node(id).kind {
-            ExprKind::ROOT => {
-                let mut new_tree = Tree::new();
-                for &child in self.tree.node(id).children() {
-                    new_tree.push_tree(self.simplify_rec(child));
+            ExprKind::Integral(v) => {
+                let [f, p] = self.bound_args(id);
+                let mut d = f.clone().subs(v, p.clone()) * p.clone().diff(x);
+                if v != x {
+                    d = d + Expression::node(ExprKind::Integral(v), [f.diff(x), p]);
                 }
-                new_tree
+                d.0.tree
             }
-            ExprKind::Ln => {
-                let mut iter = self.tree.node(id).children().iter();
-                let mut tree = Tree::new();
-                let arg = *iter.next().unwrap();
-                assert!(iter.next().is_none());
-
-                let arg_tree = self.simplify_rec(arg);
-
-                if let [node] = arg_tree.sub_roots()[..] {
-                    match node.kind {
-                        ExprKind::Const(c) if c == c!(+) => {
-                            let mut new_tree = Tree::new();
-                            new_tree.push(ExprKind::Const(c!()));
-                            return new_tree;
-                        }
-                        ExprKind::Exp => {
-                            let mut iter = self.tree.node(id).children().iter();
-                            let mut tree = Tree::new();
-                            let arg = *iter.next().unwrap();
-                            assert!(iter.next().is_none());
-                            tree.push_tree(self.simplify_rec(arg));
-                            return tree;
-                        }
-                        _ => (),
+            ExprKind::Func(f) => {
+                let args = self.args(id);
+                let mut d = e!(c!());
+                for (i, arg) in args.iter().enumerate() {
+                    if arg.has(x) {
+                        d = d + partial(f, &args, i) * arg.clone().diff(x);
                     }
                 }
-
-                tree.start_node(ExprKind::Ln);
-                tree.push_tree(arg_tree);
-                tree.finish_node();
-                tree
+                d.0.tree
             }
-            x @ (ExprKind::Var(_) | ExprKind::Const(_)) => {
-                let mut new_tree = Tree::new();
-                new_tree.push(x);
-                new_tree
-            }
-            ExprKind::Add => {
-                let mut trees = Vec::new();
-                for tree in self
-                    .tree
-                    .node(id)
-                    .children()
-                    .iter()
-                    .map(|&id| self.simplify_rec(id))
-                {
-                    match tree.sub_roots()[..] {
-                        [Node {
-                            kind: ExprKind::Add,
-                            children,
-                            ..
-                        }] => {
-                            for &child in children {
-                                trees.push(treeify_node(&tree, child));
-                            }
-                        }
-                        _ => trees.push(tree),
-                    }
+            ExprKind::Derivative(v, n) => {
+                let [f, p] = self.bound_args(id);
+                let mut d = e!(c!());
+                if p.has(x) {
+                    d = d + derivative(f.clone(), v, n + 1, p.clone()) * p.clone().diff(x);
                 }
-                let mut consts = c!();
-                trees.retain(|tree| {
-                    if let [Node {
-                        kind: ExprKind::Const(x),
-                        ..
-                    }] = tree.sub_roots()[..]
-                    {
-                        consts += x;
-                        false
-                    } else {
-                        true
-                    }
-                });
-                if consts != c!() {
-                    trees.push({
-                        let mut new_tree = Tree::new();
-                        new_tree.push(ExprKind::Const(consts));
-                        new_tree
-                    });
-                }
-                match trees.len() {
-                    0 | 1 => trees.pop().unwrap_or_else(|| {
-                        let mut new_tree = Tree::new();
-                        new_tree.push(ExprKind::Const(c!()));
-                        new_tree
-                    }),
-                    _ => {
-                        let mut new_tree = Tree::new();
-                        new_tree.start_node(ExprKind::Add);
-                        for tree in trees {
-                            new_tree.push_tree(tree);
-                        }
-                        new_tree.finish_node();
-                        new_tree
-                    }
+                if v != x {
+                    d = d + derivative(f.diff(x).simplify(), v, n, p);
                 }
+                d.0.tree
             }
-            ExprKind::Mul => {
-                let mut trees = Vec::new();
-                for tree in self
-                    .tree
-                    .node(id)
-                    .children()
-                    .iter()
-                    .map(|&id| self.simplify_rec(id))
-                {
-                    match tree.sub_roots()[..] {
-                        [Node {
-                            kind: ExprKind::Mul,
-                            children,
-                            ..
-                        }] => {
-                            for &child in children {
-                                trees.push(treeify_node(&tree, child));
-                            }
-                        }
-                        [Node {
-                            kind: ExprKind::Const(x),
-                            ..
-                        }] if *x == c!() => {
-                            trees.clear();
-                            let mut new_tree = Tree::new();
-                            new_tree.push(ExprKind::Const(c!()));
-                            trees.push(new_tree);
-                            break;
-                        }
-                        _ => trees.push(tree),
-                    }
-                }
-                let mut consts = c!(+);
-                trees.retain(|tree| {
-                    if let [Node {
-                        kind: ExprKind::Const(x),
-                        ..
-                    }] = tree.sub_roots()[..]
-                    {
-                        consts *= x;
-                        false
-                    } else {
-                        true
-                    }
-                });
-                if consts != c!(+) {
-                    trees.push({
-                        let mut new_tree = Tree::new();
-                        new_tree.push(ExprKind::Const(consts));
-                        new_tree
-                    });
-                }
-
-                match trees.len() {
-                    0 | 1 => {
-                        return trees.pop().unwrap_or_else(|| {
-                            let mut new_tree = Tree::new();
-                            new_tree.push(ExprKind::Const(c!(+)));
-                            new_tree
-                        })
-                    }
-                    _ => (),
-                }
-
-                let mut adds = Vec::new();
-                let mut others = Vec::new();
-                for tree in trees {
-                    if tree.sub_roots()[0].kind == ExprKind::Add {
-                        let terms = tree.sub_roots()[0]
-                            .children()
-                            .iter()
-                            .map(|&id| treeify_node(&tree, id))
-                            .collect::<Vec<_>>();
-                        adds.push(terms);
-                    } else {
-                        others.push(tree);
-                    }
-                }
+        }
+    }
 
-                fn recurse_open<'a>(
-                    arr: &'a [Vec<Tree>],
-                    idx: usize,
-                    curr: &mut Vec<&'a Tree>,
-                    trees: &mut Vec<Vec<Tree>>,
-                ) {
-                    if idx >= arr.len() {
-                        return trees.push(curr.iter().cloned().cloned().collect());
-                    }
-                    for i in &arr[idx] {
-                        curr.push(i);
-                        recurse_open(arr, idx + 1, curr, trees);
-                        curr.pop();
-                    }
-                }
+    fn args(&self, id: NodeId) -> Vec<Expression> {
+        self.tree
+            .node(id)
+            .children()
+            .iter()
+            .map(|&id| {
+                e!(Expressand {
+                    tree: treeify_node(&self.tree, id),
+                })
+            })
+            .collect()
+    }
 
-                let mut terms = Vec::new();
-                recurse_open(&adds, 0, &mut Vec::new(), &mut terms);
+    /// Operand and evaluation point of an integral or derivative node
+    fn bound_args(&self, id: NodeId) -> [Expression; 2] {
+        <[Expression; 2]>::try_from(self.args(id)).unwrap()
+    }
 
+    fn simplify_rec(&self, id: NodeId) -> Tree {
+        let node = self.tree.node(id);
+        let args = node
+            .children()
+            .iter()
+            .map(|&id| {
+                e!(Expressand {
+                    tree: self.simplify_rec(id),
+                })
+            })
+            .collect::<Vec<_>>();
+        match node.kind {
+            ExprKind::ROOT => match args.len() {
+                0 => e!(c!()).0.tree,
+                1 => args.into_iter().next().unwrap().0.tree,
+                _ => simplify_add(args).0.tree,
+            },
+            x @ (ExprKind::Var(_) | ExprKind::Const(_)) => {
                 let mut new_tree = Tree::new();
-                new_tree.start_node(ExprKind::Add);
-                for trees in terms {
-                    new_tree.start_node(ExprKind::Mul);
-                    for tree in trees {
-                        new_tree.push_tree(tree);
-                    }
-                    for tree in &others {
-                        new_tree.push_tree(tree.clone());
-                    }
-                    new_tree.finish_node();
-                }
-                new_tree.finish_node();
+                new_tree.push(x);
                 new_tree
             }
+            ExprKind::Add => simplify_add(args).0.tree,
+            ExprKind::Mul => simplify_mul(args).0.tree,
             ExprKind::Exp => {
-                let mut iter = self.tree.node(id).children().iter();
-                let exp = *iter.next().unwrap();
-                assert!(iter.next().is_none());
-
-                let exp_tree = self.simplify_rec(exp);
-
-                if let [node] = exp_tree.sub_roots()[..] {
-                    match node.kind {
-                        ExprKind::Const(c) if c == c!() => {
-                            let mut new_tree = Tree::new();
-                            new_tree.push(ExprKind::Const(c!(+)));
-                            return new_tree;
-                        }
-                        ExprKind::Ln => {
-                            let mut iter = self.tree.node(id).children().iter();
-                            let mut tree = Tree::new();
-                            let arg = *iter.next().unwrap();
-                            assert!(iter.next().is_none());
-                            tree.push_tree(self.simplify_rec(arg));
-                            return tree;
-                        }
-                        _ => (),
-                    }
+                let [exp] = <[Expression; 1]>::try_from(args).unwrap();
+                if let Some(c) = exp.as_const() {
+                    return fold(ExprKind::Exp, c.exp(), exp).0.tree;
                 }
-
-                let mut new_tree = Tree::new();
-                new_tree.start_node(ExprKind::Exp);
-                new_tree.push_tree(exp_tree);
-                new_tree.finish_node();
-                new_tree
+                simplify_mul(vec![Expression::node(ExprKind::Exp, [exp])])
+                    .0
+                    .tree
+            }
+            ExprKind::Ln => {
+                let [arg] = <[Expression; 1]>::try_from(args).unwrap();
+                simplify_ln(arg).0.tree
             }
             ExprKind::Abs => {
-                let mut iter = self.tree.node(id).children().iter();
-                let x = *iter.next().unwrap();
-                assert!(iter.next().is_none());
-
-                let x_tree = self.simplify_rec(x);
-
-                if let [node] = x_tree.sub_roots()[..] {
-                    if let ExprKind::Const(c) = node.kind {
-                        let mut new_tree = Tree::new();
-                        new_tree.push(ExprKind::Const(c!(c.abs())));
-                        return new_tree;
-                    }
+                let [x] = <[Expression; 1]>::try_from(args).unwrap();
+                match x.as_const() {
+                    Some(c) => e!(c!(c.abs())).0.tree,
+                    None => Expression::node(ExprKind::Abs, [x]).0.tree,
                 }
-
-                let mut new_tree = Tree::new();
-                new_tree.start_node(ExprKind::Abs);
-                new_tree.push_tree(x_tree);
-                new_tree.finish_node();
-                new_tree
+            }
+            ExprKind::Integral(v) => {
+                let [f, p] = <[Expression; 2]>::try_from(args).unwrap();
+                match f.has(v) {
+                    true => Expression::node(ExprKind::Integral(v), [f, p]).0.tree,
+                    false => simplify_mul(vec![f, p]).0.tree,
+                }
+            }
+            ExprKind::Func(f) => Expression::node(ExprKind::Func(f), args).0.tree,
+            ExprKind::Derivative(v, n) => {
+                let [f, p] = <[Expression; 2]>::try_from(args).unwrap();
+                derivative(f, v, n, p).0.tree
             }
         }
     }
@@ -503,6 +355,9 @@ impl Expressand {
                 let mut iter = self.tree.node(id).children().iter();
                 let exp = *iter.next().unwrap();
                 assert!(iter.next().is_none());
+                if let Some(pow) = self.eval_pow(exp, x) {
+                    return pow;
+                }
                 let exp = self.eval_rec(exp, x);
                 exp.exp()
             }
@@ -513,12 +368,138 @@ impl Expressand {
                 let exp = self.eval_rec(exp, x);
                 c!(exp.abs())
             }
+            ExprKind::Integral(_) | ExprKind::Func(_) | ExprKind::Derivative(..) => c!(f64::NAN),
+        }
+    }
+
+    /// Evaluates an exponent of the form `k ln(b)` as `b^k`
+    fn eval_pow(&self, id: NodeId, x: &[Complex64]) -> Option<Complex64> {
+        let node = self.tree.node(id);
+        if node.kind != ExprKind::Mul {
+            return None;
         }
+        let &ln = node
+            .children()
+            .iter()
+            .find(|&&c| self.tree.node(c).kind == ExprKind::Ln)?;
+        let base = self.eval_rec(self.tree.node(ln).children()[0], x);
+        let k = node
+            .children()
+            .iter()
+            .filter(|&&c| c != ln)
+            .map(|&c| self.eval_rec(c, x))
+            .product::<Complex64>();
+        Some(pow_value(base, k))
     }
 
     pub(crate) fn eval(&self, x: &[Complex64]) -> Complex64 {
         self.eval_rec(NodeId::ROOT, x)
     }
+
+    fn eval_scaled_rec(&self, id: NodeId, x: &[Complex64]) -> (Complex64, f64) {
+        let node = self.tree.node(id);
+        let mut args = node
+            .children()
+            .iter()
+            .map(|&id| self.eval_scaled_rec(id, x));
+        match node.kind {
+            ExprKind::ROOT | ExprKind::Add => {
+                args.fold((c!(), 0.0), |(v, s), (a, b)| (v + a, s + b))
+            }
+            ExprKind::Mul => args.fold((c!(+), 1.0), |(v, s), (a, b)| (v * a, s * b)),
+            ExprKind::Var(v) => (x[v.id], x[v.id].abs()),
+            ExprKind::Const(c) => (c, c.abs()),
+            ExprKind::Exp => {
+                let (a, _) = args.next().unwrap();
+                (a.exp(), a.exp().abs())
+            }
+            ExprKind::Ln => {
+                let (a, s) = args.next().unwrap();
+                (a.ln(), a.ln().abs().max(s / a.abs()))
+            }
+            ExprKind::Abs => {
+                let (a, s) = args.next().unwrap();
+                (c!(a.abs()), s)
+            }
+            ExprKind::Integral(_) | ExprKind::Func(_) | ExprKind::Derivative(..) => {
+                (c!(f64::NAN), f64::NAN)
+            }
+        }
+    }
+
+    fn subs_rec(&self, id: NodeId, x: Var, with: &Tree) -> Tree {
+        match self.tree.node(id).kind {
+            ExprKind::Var(v) if v == x => with.clone(),
+            kind @ (ExprKind::Integral(v) | ExprKind::Derivative(v, _)) if v == x => {
+                let [f, p] = self.bound_args(id);
+                let p = e!(Expressand {
+                    tree: p.0.subs_rec(NodeId::ROOT, x, with),
+                });
+                Expression::node(kind, [f, p]).0.tree
+            }
+            ExprKind::ROOT => {
+                let mut new_tree = Tree::new();
+                for &child in self.tree.node(id).children() {
+                    new_tree.push_tree(self.subs_rec(child, x, with));
+                }
+                new_tree
+            }
+            kind => {
+                let mut new_tree = Tree::new();
+                new_tree.start_node(kind);
+                for &child in self.tree.node(id).children() {
+                    new_tree.push_tree(self.subs_rec(child, x, with));
+                }
+                new_tree.finish_node();
+                new_tree
+            }
+        }
+    }
+
+    fn vars_rec(&self, id: NodeId, vars: &mut Vec<Var>) {
+        match self.tree.node(id).kind {
+            ExprKind::Var(v) if !vars.contains(&v) => vars.push(v),
+            ExprKind::Integral(v) | ExprKind::Derivative(v, _) => {
+                let [f, p] = self.bound_args(id);
+                let bound = !vars.contains(&v);
+                f.0.vars_rec(NodeId::ROOT, vars);
+                if bound {
+                    vars.retain(|&u| u != v);
+                }
+                return p.0.vars_rec(NodeId::ROOT, vars);
+            }
+            _ => (),
+        }
+        for &child in self.tree.node(id).children() {
+            self.vars_rec(child, vars);
+        }
+    }
+
+    fn key_rec(&self, id: NodeId, f: &mut String) {
+        let node = self.tree.node(id);
+        match node.kind {
+            ExprKind::Var(v) => *f += &format!("v{}", v.id),
+            ExprKind::Const(c) => *f += &format!("c{}:{}", c.re + 0.0, c.im + 0.0),
+            kind => {
+                *f += &match kind {
+                    ExprKind::Add => "+(".to_string(),
+                    ExprKind::Mul => "*(".to_string(),
+                    ExprKind::Exp => "e(".to_string(),
+                    ExprKind::Ln => "l(".to_string(),
+                    ExprKind::Abs => "a(".to_string(),
+                    ExprKind::Integral(v) => format!("i{}(", v.id),
+                    ExprKind::Func(f) => format!("f{}(", f.id),
+                    ExprKind::Derivative(v, n) => format!("d{}:{}(", v.id, n),
+                    _ => "(".to_string(),
+                };
+                for &child in node.children() {
+                    self.key_rec(child, f);
+                    *f += ",";
+                }
+                *f += ")";
+            }
+        }
+    }
 }
 
 #[track_caller]
@@ -536,7 +517,14 @@ fn treeify_node(tree: &Tree, id: NodeId) -> Tree {
             new_tree.push(x);
             new_tree
         }
-        kind @ (ExprKind::Add | ExprKind::Mul | ExprKind::Exp | ExprKind::Ln | ExprKind::Abs) => {
+        kind @ (ExprKind::Add
+        | ExprKind::Mul
+        | ExprKind::Exp
+        | ExprKind::Ln
+        | ExprKind::Abs
+        | ExprKind::Integral(_)
+        | ExprKind::Func(_)
+        | ExprKind::Derivative(..)) => {
             let mut new_tree = Tree::new();
             new_tree.start_node(kind);
             for tre in tree
@@ -553,6 +541,486 @@ fn treeify_node(tree: &Tree, id: NodeId) -> Tree {
     }
 }
 
+impl Expression {
+    pub(crate) fn node<I: IntoIterator<Item = Expression>>(kind: ExprKind, args: I) -> Expression {
+        let mut tree = Tree::new();
+        tree.start_node(kind);
+        for arg in args {
+            tree.push_tree(arg.0.tree);
+        }
+        tree.finish_node();
+        Expressable(Expressand { tree })
+    }
+
+    /// Kind of the outermost node
+    pub(crate) fn kind(&self) -> ExprKind {
+        match self.0.tree.root().children()[..] {
+            [id] => self.0.tree.node(id).kind,
+            [] => ExprKind::Const(c!()),
+            _ => ExprKind::Add,
+        }
+    }
+
+    /// Operands of the outermost node
+    pub(crate) fn args(&self) -> Vec<Expression> {
+        let tree = &self.0.tree;
+        let ids = match tree.root().children()[..] {
+            [id] => tree.node(id).children(),
+            _ => tree.root().children(),
+        };
+        ids.iter()
+            .map(|&id| {
+                e!(Expressand {
+                    tree: treeify_node(tree, id),
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn as_const(&self) -> Option<Complex64> {
+        match self.kind() {
+            ExprKind::Const(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn vars(&self) -> Vec<Var> {
+        let mut vars = Vec::new();
+        self.0.vars_rec(NodeId::ROOT, &mut vars);
+        vars
+    }
+
+    pub(crate) fn has(&self, x: Var) -> bool {
+        self.vars().contains(&x)
+    }
+
+    pub(crate) fn key(&self) -> String {
+        let mut f = String::new();
+        self.0.key_rec(NodeId::ROOT, &mut f);
+        f
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        1 + self.args().iter().map(Expression::size).sum::<usize>()
+    }
+
+    /// Evaluates at `x`, taking every variable not given in `x` from a fixed sample point
+    pub(crate) fn eval_sampled(&self, x: &[(Var, Complex64)], sample: usize) -> (Complex64, f64) {
+        let (slots, vars): (Vec<_>, Vec<_>) = self.vars().into_iter().partition(|v| v.is_slot());
+        let len = vars.iter().map(|v| v.id + 1).max().unwrap_or(0);
+        // Free slots are evaluated as the variables following the others
+        let mut ex = self.clone();
+        for (k, &s) in slots.iter().enumerate() {
+            ex = ex.subs(s, Var { id: len + k });
+        }
+        let index = |v: Var| slots.iter().position(|&s| s == v).map_or(v.id, |k| len + k);
+        let mut values = (0..len + slots.len())
+            .map(|id| sample_value(id, sample))
+            .collect::<Vec<_>>();
+        for &(v, value) in x {
+            if let Some(slot) = values.get_mut(index(v)) {
+                *slot = value;
+            }
+        }
+        ex.0.eval_scaled_rec(NodeId::ROOT, &values)
+    }
+
+    /// Heuristic zero test, evaluating the expression at a few sample points
+    pub(crate) fn is_zero(&self) -> bool {
+        if let Some(c) = self.as_const() {
+            return c.abs() <= crate::TOL;
+        }
+        let mut finite = 0;
+        for sample in 0..SAMPLES {
+            let (value, scale) = self.eval_sampled(&[], sample);
+            if !value.is_finite() || !scale.is_finite() {
+                continue;
+            }
+            if value.abs() > 1e-9 * scale.max(crate::TOL) {
+                return false;
+            }
+            finite += 1;
+        }
+        finite > 0
+    }
+}
+
+const SAMPLES: usize = 4;
+
+/// `base^k` as `e^(k ln base)` would evaluate it, but exact for integer powers of negative
+/// bases and finite at a zero base
+pub(crate) fn pow_value(base: Complex64, k: Complex64) -> Complex64 {
+    match k {
+        _ if base == c!() && k.re > 0.0 => c!(),
+        _ if base == c!() && k.re < 0.0 => c!(f64::INFINITY),
+        k if k.im == 0.0 && k.re.fract() == 0.0 && k.re.abs() <= i32::MAX as f64 => {
+            base.powi(k.re as i32)
+        }
+        k => (k * base.ln()).exp(),
+    }
+}
+
+pub(crate) fn sample_value(id: usize, sample: usize) -> Complex64 {
+    let h = ((id + 1) as f64 * 0.754_877_666_246_692_7
+        + (sample + 1) as f64 * 0.569_840_290_998_053_2)
+        .fract();
+    c!(0.5 + 2.0 * h)
+}
+
+fn fold(kind: ExprKind, value: Complex64, arg: Expression) -> Expression {
+    if value.is_finite() {
+        e!(value)
+    } else {
+        Expression::node(kind, [arg])
+    }
+}
+
+pub(crate) fn pow_node(base: Expression, exp: Expression) -> Expression {
+    let mut factors = vec![Expression::node(ExprKind::Ln, [base])];
+    match exp.kind() {
+        ExprKind::Mul => factors.extend(exp.args()),
+        _ => factors.push(exp),
+    }
+    Expression::node(
+        ExprKind::Exp,
+        [Expression::node(ExprKind::Mul, sorted(factors))],
+    )
+}
+
+/// Partial derivative of `f` in its `i`-th argument at `args`, taken in the variable passed
+/// there when no other argument depends on it and in a dummy otherwise
+fn partial(f: Func, args: &[Expression], i: usize) -> Expression {
+    let v = match args[i].kind() {
+        ExprKind::Var(v) if args.iter().enumerate().all(|(j, a)| j == i || !a.has(v)) => v,
+        _ => Var::slot(i),
+    };
+    let mut slots = args.to_vec();
+    slots[i] = e!(v);
+    derivative(
+        Expression::node(ExprKind::Func(f), slots),
+        v,
+        1,
+        args[i].clone(),
+    )
+}
+
+/// `n`-th derivative of `ex` in `v` at `p`, pulled through sums and factors free of `v`, with
+/// nested derivatives at their own variable merged and ordered by variable
+pub(crate) fn derivative(ex: Expression, v: Var, n: usize, p: Expression) -> Expression {
+    if n == 0 {
+        return ex.subs(v, p);
+    }
+    if !ex.has(v) {
+        return e!(c!());
+    }
+    match ex.kind() {
+        ExprKind::Add => {
+            return simplify_add(
+                ex.args()
+                    .into_iter()
+                    .map(|t| derivative(t, v, n, p.clone()))
+                    .collect(),
+            )
+        }
+        ExprKind::Mul => {
+            let (mut free, mut dep): (Vec<_>, Vec<_>) =
+                ex.args().into_iter().partition(|f| !f.has(v));
+            if !free.is_empty() {
+                let dep = match dep.len() {
+                    1 => dep.pop().unwrap(),
+                    _ => Expression::node(ExprKind::Mul, dep),
+                };
+                free.push(derivative(dep, v, n, p));
+                return simplify_mul(free);
+            }
+        }
+        ExprKind::Derivative(u, m) if p.kind() == ExprKind::Var(v) => {
+            let [g, q] = <[Expression; 2]>::try_from(ex.args()).unwrap();
+            if q.kind() == ExprKind::Var(u) && u == v {
+                return Expression::node(ExprKind::Derivative(v, n + m), [g, p]);
+            }
+            if q.kind() == ExprKind::Var(u) && u.id < v.id {
+                return Expression::node(ExprKind::Derivative(u, m), [derivative(g, v, n, p), q]);
+            }
+        }
+        _ => (),
+    }
+    Expression::node(ExprKind::Derivative(v, n), [ex, p])
+}
+
+fn sorted(mut factors: Vec<Expression>) -> Vec<Expression> {
+    factors.sort_by_cached_key(|f| (f.as_const().is_some(), f.key()));
+    factors
+}
+
+/// Splits a factor into `(base, exponent)` pairs, where a base of `None` stands for `e`
+pub(crate) fn as_power(f: Expression) -> Vec<(Option<Expression>, Expression)> {
+    if f.kind() != ExprKind::Exp {
+        return vec![(Some(f), e!(c!(+)))];
+    }
+    let u = f.args().pop().unwrap();
+    let terms = match u.kind() {
+        ExprKind::Add => u.args(),
+        _ => vec![u],
+    };
+    terms
+        .into_iter()
+        .map(|t| match t.kind() {
+            ExprKind::Ln => (t.args().pop(), e!(c!(+))),
+            ExprKind::Mul => {
+                let mut args = t.args();
+                match args.iter().position(|a| a.kind() == ExprKind::Ln) {
+                    Some(i) => {
+                        let base = args.remove(i).args().pop();
+                        let exp = match args.len() {
+                            1 => args.pop().unwrap(),
+                            _ => Expression::node(ExprKind::Mul, args),
+                        };
+                        (base, exp)
+                    }
+                    None => (None, t),
+                }
+            }
+            _ => (None, t),
+        })
+        .collect()
+}
+
+fn simplify_add(terms: Vec<Expression>) -> Expression {
+    let mut consts = c!();
+    let mut groups: Vec<(String, Vec<Expression>, Complex64)> = Vec::new();
+    let mut stack = terms;
+    while let Some(term) = stack.pop() {
+        let (coeff, factors) = match term.kind() {
+            ExprKind::Add => {
+                stack.extend(term.args());
+                continue;
+            }
+            ExprKind::Const(c) => {
+                consts += c;
+                continue;
+            }
+            ExprKind::Mul => {
+                let mut coeff = c!(+);
+                let mut factors = Vec::new();
+                for f in term.args() {
+                    match f.as_const() {
+                        Some(c) => coeff *= c,
+                        None => factors.push(f),
+                    }
+                }
+                (coeff, factors)
+            }
+            _ => (c!(+), vec![term]),
+        };
+        let key = factors
+            .iter()
+            .map(Expression::key)
+            .collect::<Vec<_>>()
+            .join(",");
+        match groups.iter_mut().find(|(k, ..)| *k == key) {
+            Some((.., c)) => *c += coeff,
+            None => groups.push((key, factors, coeff)),
+        }
+    }
+    groups.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    let mut trees = groups
+        .into_iter()
+        .filter(|(.., c)| c.abs() > crate::TOL)
+        .map(|(_, mut factors, coeff)| {
+            if coeff != c!(+) {
+                factors.push(e!(coeff));
+            }
+            match factors.len() {
+                1 => factors.pop().unwrap(),
+                _ => Expression::node(ExprKind::Mul, factors),
+            }
+        })
+        .collect::<Vec<_>>();
+    if consts.abs() > crate::TOL {
+        trees.push(e!(consts));
+    }
+    match trees.len() {
+        0 => e!(c!()),
+        1 => trees.pop().unwrap(),
+        _ => Expression::node(ExprKind::Add, trees),
+    }
+}
+
+fn simplify_mul(factors: Vec<Expression>) -> Expression {
+    const MAX_EXPANSION: f64 = 8.0;
+
+    let mut coeff = c!(+);
+    let mut bases: Vec<(String, Expression, Vec<Expression>)> = Vec::new();
+    let mut exps = Vec::new();
+    let mut stack = factors;
+    while let Some(factor) = stack.pop() {
+        match factor.kind() {
+            ExprKind::Mul => stack.extend(factor.args()),
+            ExprKind::Const(c) => coeff *= c,
+            _ => {
+                for (base, exp) in as_power(factor) {
+                    let Some(base) = base else {
+                        exps.push(exp);
+                        continue;
+                    };
+                    match exp.as_const() {
+                        Some(k) if is_integer(k) && base.kind() == ExprKind::Mul => {
+                            stack.extend(base.args().into_iter().map(|f| match f.as_const() {
+                                Some(c) => e!(pow_value(c, k)),
+                                None => pow_node(f, e!(k)),
+                            }));
+                            continue;
+                        }
+                        Some(k) if is_integer(k) && base.kind() == ExprKind::Exp => {
+                            stack.extend(as_power(base).into_iter().map(|(b, p)| {
+                                let exp = simplify_mul(vec![p, e!(k)]);
+                                match b {
+                                    Some(b) => pow_node(b, exp),
+                                    None => Expression::node(ExprKind::Exp, [exp]),
+                                }
+                            }));
+                            continue;
+                        }
+                        _ => (),
+                    }
+                    let key = base.key();
+                    match bases.iter_mut().find(|(k, ..)| *k == key) {
+                        Some((.., exps)) => exps.push(exp),
+                        None => bases.push((key, base, vec![exp])),
+                    }
+                }
+            }
+        }
+    }
+    if coeff.abs() <= crate::TOL {
+        return e!(c!());
+    }
+
+    let mut others = Vec::new();
+    let mut sums = Vec::new();
+    for (_, base, exps) in bases {
+        let exp = simplify_add(exps);
+        match exp.as_const() {
+            Some(k) if k.abs() <= crate::TOL => (),
+            // `0^k` for `Re k > 0`, as when a solution is evaluated at a point where a factor
+            // vanishes, would otherwise stay as `e^(k ln 0)`
+            Some(k) if k.re > 0.0 && base.as_const() == Some(c!()) => return e!(c!()),
+            Some(k) if k == c!(+) && base.kind() == ExprKind::Add => sums.push(base.args()),
+            Some(k) if k == c!(+) => others.push(base),
+            Some(k)
+                if base.kind() == ExprKind::Add
+                    && k.im == 0.0
+                    && k.re.fract() == 0.0
+                    && (1.0..=MAX_EXPANSION).contains(&k.re) =>
+            {
+                for _ in 0..k.re as usize {
+                    sums.push(base.args());
+                }
+            }
+            _ => others.push(pow_node(base, exp)),
+        }
+    }
+    let exp = simplify_add(exps);
+    match exp.as_const() {
+        Some(c) => coeff *= c.exp(),
+        None => others.push(Expression::node(ExprKind::Exp, [exp])),
+    }
+
+    if !sums.is_empty() {
+        fn recurse_open(
+            sums: &[Vec<Expression>],
+            curr: &mut Vec<Expression>,
+            terms: &mut Vec<Expression>,
+        ) {
+            let Some((first, rest)) = sums.split_first() else {
+                return terms.push(simplify_mul(curr.clone()));
+            };
+            for term in first {
+                curr.push(term.clone());
+                recurse_open(rest, curr, terms);
+                curr.pop();
+            }
+        }
+        others.push(e!(coeff));
+        let mut terms = Vec::new();
+        recurse_open(&sums, &mut others, &mut terms);
+        return simplify_add(terms);
+    }
+
+    let mut factors = sorted(others);
+    if coeff != c!(+) {
+        factors.push(e!(coeff));
+    }
+    match factors.len() {
+        0 => e!(c!(+)),
+        1 => factors.pop().unwrap(),
+        _ => Expression::node(ExprKind::Mul, factors),
+    }
+}
+
+/// `ln`, split only where the principal branch allows it: `ln(c z) = ln c + ln z` for a
+/// positive constant `c`, `ln(c^k) = k ln c` and `ln(e^u) = u`
+fn simplify_ln(arg: Expression) -> Expression {
+    if let Some(c) = arg.as_const() {
+        return fold(ExprKind::Ln, c.ln(), arg);
+    }
+    match arg.kind() {
+        ExprKind::Mul => {
+            let (positive, rest): (Vec<_>, Vec<_>) = arg.args().into_iter().partition(is_positive);
+            if positive.is_empty() {
+                return Expression::node(ExprKind::Ln, [arg]);
+            }
+            let rest = match rest.len() {
+                1 => Expression::node(ExprKind::Ln, rest),
+                _ => Expression::node(ExprKind::Ln, [Expression::node(ExprKind::Mul, rest)]),
+            };
+            simplify_add(
+                positive
+                    .into_iter()
+                    .map(simplify_ln)
+                    .chain([rest])
+                    .collect(),
+            )
+        }
+        ExprKind::Exp => {
+            let powers = as_power(arg.clone());
+            match &powers[..] {
+                _ if powers.iter().all(|(base, _)| base.is_none()) => {
+                    simplify_add(powers.into_iter().map(|(_, exp)| exp).collect())
+                }
+                [(Some(base), k)] if k.as_const() == Some(c!(+)) || is_positive(base) => {
+                    simplify_mul(vec![k.clone(), simplify_ln(base.clone())])
+                }
+                _ => Expression::node(ExprKind::Ln, [arg]),
+            }
+        }
+        _ => Expression::node(ExprKind::Ln, [arg]),
+    }
+}
+
+/// Whether `f` is a positive constant or a power of one
+fn is_positive(f: &Expression) -> bool {
+    let positive = |c: Complex64| c.im == 0.0 && c.re > 0.0;
+    match f.as_const() {
+        Some(c) => positive(c),
+        None => {
+            f.kind() == ExprKind::Exp
+                && as_power(f.clone()).iter().all(|(base, _)| {
+                    base.as_ref()
+                        .and_then(|b| b.as_const())
+                        .is_some_and(positive)
+                })
+        }
+    }
+}
+
+/// Integer `k`, for which `(a b)^k = a^k b^k` and `(b^p)^k = b^(p k)` hold on every branch
+fn is_integer(k: Complex64) -> bool {
+    k.im == 0.0 && k.re.fract() == 0.0
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ExprKind {
     ROOT,
@@ -563,6 +1031,14 @@ pub enum ExprKind {
     Exp,
     Ln,
     Abs,
+    /// Unevaluated antiderivative in the variable, with the integrand and the point it is
+    /// evaluated at as children
+    Integral(Var),
+    /// Undefined function, with its arguments as children
+    Func(Func),
+    /// Unevaluated derivative of the given order in the variable, with the differentiated
+    /// expression and the point it is evaluated at as children
+    Derivative(Var, usize),
 }
 
 #[derive(Debug, Clone)]
@@ -584,6 +1060,15 @@ where
         })
     }
 
+    pub fn subs<U: Clone>(self, x: Var, with: U) -> Expression
+    where
+        Expression: From<U>,
+    {
+        Expressable(Expressand {
+            tree: e!(self).0.subs_rec(NodeId::ROOT, x, &e!(with).0.tree),
+        })
+    }
+
     pub fn ln(self) -> Expression {
         let mut tree = Tree::new();
         tree.start_node(ExprKind::Ln);
@@ -678,6 +1163,29 @@ where
 
 pub type Expression = Expressable<Expressand>;
 
+/// Scalars the matrix and curvature routines can compute with
+pub trait Scalar:
+    Clone + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    fn constant(c: Complex64) -> Self;
+    fn diff(&self, x: Var) -> Self;
+    fn simplify(&self) -> Self;
+}
+
+impl Scalar for Expression {
+    fn constant(c: Complex64) -> Self {
+        e!(c)
+    }
+
+    fn diff(&self, x: Var) -> Self {
+        Expressable::diff(self.clone(), x)
+    }
+
+    fn simplify(&self) -> Self {
+        Expressable::simplify(self.clone())
+    }
+}
+
 impl From<Expressand> for Expression {
     fn from(value: Expressand) -> Self {
         Expressable(value)
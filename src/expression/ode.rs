@@ -0,0 +1,464 @@
+use num_complex::ComplexFloat;
+
+use crate::{c, e, expression::*, Expression, System};
+
+/// Solution of an ordinary differential equation
+#[derive(Debug, Clone)]
+pub enum Solution {
+    /// `y(x)` in closed form
+    Explicit(Expression),
+    /// Relation `F = 0` that `y(x)` satisfies, when it could not be solved for `y(x)`
+    Implicit(Expression),
+}
+
+impl System {
+    /// General solution of `ode = 0` for `y(x)`, with integration constants `C1`, `C2`, ...
+    /// declared in the system.
+    ///
+    /// First order equations are tried as linear, separable, Bernoulli and exact equations in
+    /// turn. Linear equations of higher order are solved when their coefficients are constant
+    /// or of the Euler–Cauchy form `c_k x^k`, the inhomogeneous part by variation of
+    /// parameters.
+    pub fn dsolve(&mut self, ode: Expression, y: Func, x: Var) -> Result<Solution, String> {
+        let ode = Ode::new(ode, y, x)?;
+        let consts = self.constants(ode.order());
+        match ode.order() {
+            0 => Err("Equation does not contain derivatives of the unknown function".to_string()),
+            1 => ode.first_order(consts[0]),
+            _ => ode.linear(&consts).map(Solution::Explicit),
+        }
+    }
+
+    /// Power series solution of `ode = 0` about the ordinary point `x0`, truncated before
+    /// `(x - x0)^order`, with the first derivatives at `x0` as the constants `C1`, `C2`, ...
+    pub fn dsolve_series(
+        &mut self,
+        ode: Expression,
+        y: Func,
+        x: Var,
+        x0: Expression,
+        order: usize,
+    ) -> Result<Expression, String> {
+        let ode = Ode::new(ode, y, x)?;
+        let consts = self.constants(ode.order());
+        ode.series(&consts, x0, order)
+    }
+
+    /// Declares `n` constants, named `C1`, `C2`, ... skipping names already in use
+    fn constants(&mut self, n: usize) -> Vec<Var> {
+        let mut vars = Vec::new();
+        let mut k = 0;
+        while vars.len() < n {
+            k += 1;
+            if let Ok([c]) = self.symbols(&format!("C{k}")) {
+                vars.push(c);
+            }
+        }
+        vars
+    }
+}
+
+/// Equation `F(x, y, y', ..., y^(n)) = 0`, with the derivatives of `y` as plain variables
+struct Ode {
+    x: Var,
+    y: Func,
+    /// `p[k]` is a slot standing for `y^(k)(x)`
+    p: Vec<Var>,
+    f: Expression,
+}
+
+impl Ode {
+    fn new(ode: Expression, y: Func, x: Var) -> Result<Self, String> {
+        if y.arity != 1 {
+            return Err(format!(
+                "Inadequate amount of arguments, expected 1 got {}",
+                y.arity
+            ));
+        }
+        let ode = ode.doit();
+        let mut order = 0;
+        let f = reduce(&ode, y, x, &mut order)?.simplify();
+        let p = (0..=order).map(Var::slot).collect();
+        Ok(Ode { x, y, p, f })
+    }
+
+    fn order(&self) -> usize {
+        self.p.len() - 1
+    }
+
+    fn call(&self) -> Expression {
+        self.y.call([self.x])
+    }
+
+    fn first_order(&self, c: Var) -> Result<Solution, String> {
+        let (x, y, dy) = (self.x, self.p[0], self.p[1]);
+        let a = self.f.clone().diff(dy).simplify();
+        if !a.clone().diff(dy).is_zero() {
+            return Err("Equation is not linear in the derivative".to_string());
+        }
+        let b = self.f.clone().subs(dy, e!(c!())).simplify();
+        let g = (-b.clone() / a.clone()).simplify();
+
+        // Linear, `y' = -P y + Q`
+        let h = g.clone().diff(y).simplify();
+        if h.clone().diff(y).is_zero() {
+            let q = g.subs(y, e!(c!())).simplify();
+            return Ok(Solution::Explicit(linear_first(-h, q, x, c)));
+        }
+
+        // Separable, `y' = X(x) Y(y)`
+        let (gx, gy) = (g.clone().diff(x), g.clone().diff(y));
+        if (g.clone() * gx.clone().diff(y) - gx * gy).is_zero() {
+            let fy = [0.0, 1.0, 2.0, 0.5]
+                .into_iter()
+                .map(|x0| g.clone().subs(x, e!(x0)).simplify())
+                .find(|fy| {
+                    let (v, _) = fy.eval_sampled(&[], 0);
+                    v.is_finite() && v.abs() > 1e-9
+                });
+            if let Some(fy) = fy {
+                let fx = (g.clone() / fy.clone()).subs(y, e!(1.0)).simplify();
+                let lhs = (fy.inv()).integrate(y);
+                let rhs = fx.integrate(x) + e!(c);
+                return Ok(self.implicit(lhs, rhs));
+            }
+        }
+
+        // Bernoulli, `y' = -P y + Q y^m`
+        let u = (g.clone() / e!(y)).simplify();
+        let uy = u.clone().diff(y).simplify();
+        let r = (e!(y) * uy.clone().diff(y) / uy.clone()).simplify();
+        if !uy.is_zero() && r.clone().diff(x).is_zero() && r.clone().diff(y).is_zero() {
+            let m = (r.subs(y, e!(1.0)) + e!(2.0)).simplify();
+            let q = (uy / (m.clone() - e!(1.0))).subs(y, e!(1.0)).simplify();
+            let p = (q.clone() - u.subs(y, e!(1.0))).simplify();
+            // `v = y^(1 - m)` solves `v' = -(1 - m) P v + (1 - m) Q`
+            let k = (e!(1.0) - m).simplify();
+            let v = linear_first(k.clone() * p, k.clone() * q, x, c);
+            return Ok(Solution::Explicit(v.pow(k.inv()).simplify()));
+        }
+
+        // Exact, `M dx + N dy = 0` with `M_y = N_x`
+        let (m, n) = (b, a);
+        if (m.clone().diff(y) - n.clone().diff(x)).is_zero() {
+            let mx = m.integrate(x);
+            let rest = (n - mx.clone().diff(y)).simplify().integrate(y);
+            return Ok(self.implicit(mx + rest, e!(c)));
+        }
+        Err("Unsupported first order equation".to_string())
+    }
+
+    /// Solves `lhs(y) = rhs(x)` for `y` when the relation can be inverted
+    fn implicit(&self, lhs: Expression, rhs: Expression) -> Solution {
+        let y = self.p[0];
+        match isolate(lhs.clone().simplify(), y, rhs.clone().simplify()) {
+            Some(sol) => Solution::Explicit(sol.simplify()),
+            None => Solution::Implicit((lhs - rhs).subs(y, self.call()).simplify()),
+        }
+    }
+
+    /// Coefficients `a_k` and right hand side `r` of `sum a_k y^(k) = r`
+    fn as_linear(&self) -> Result<(Vec<Expression>, Expression), String> {
+        let a = self
+            .p
+            .iter()
+            .map(|&p| self.f.clone().diff(p).simplify())
+            .collect::<Vec<_>>();
+        for a in &a {
+            if self.p.iter().any(|&p| !a.clone().diff(p).is_zero()) {
+                return Err("Equation is not linear".to_string());
+            }
+        }
+        let mut r = self.f.clone();
+        for &p in &self.p {
+            r = r.subs(p, e!(c!()));
+        }
+        Ok((a, (-r).simplify()))
+    }
+
+    fn linear(&self, consts: &[Var]) -> Result<Expression, String> {
+        let x = self.x;
+        let n = self.order();
+        let (a, r) = self.as_linear()?;
+        let basis = if a.iter().all(|a| a.clone().diff(x).is_zero()) {
+            constant_basis(&a, x)?
+        } else {
+            let c = a
+                .iter()
+                .enumerate()
+                .map(|(k, a)| (a.clone() / e!(x).pow(k as f64)).simplify())
+                .collect::<Vec<_>>();
+            if !c.iter().all(|c| c.clone().diff(x).is_zero()) {
+                return Err("Unsupported linear equation, try dsolve_series".to_string());
+            }
+            euler_basis(&c, x)?
+        };
+
+        let mut y = e!(c!());
+        for (&c, b) in consts.iter().zip(&basis) {
+            y = y + e!(c) * b.clone();
+        }
+        if r.is_zero() {
+            return Ok(y.simplify());
+        }
+        // Variation of parameters, `u_i' = (-1)^(n-1+i) M_(n-1, i) / W r / a_n`
+        let w = (0..n)
+            .map(|k| {
+                basis
+                    .iter()
+                    .map(|b| {
+                        let mut d = b.clone();
+                        for _ in 0..k {
+                            d = d.diff(x).simplify();
+                        }
+                        d
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let wronskian = det(&w).simplify();
+        let forcing = (r / a[n].clone() / wronskian).simplify();
+        for (i, b) in basis.iter().enumerate() {
+            let minor = w[..n - 1]
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .enumerate()
+                        .filter(|&(j, _)| j != i)
+                        .map(|(_, v)| v.clone())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let sign = match (n - 1 + i) % 2 {
+                0 => 1.0,
+                _ => -1.0,
+            };
+            let du = (e!(sign) * det(&minor) * forcing.clone()).simplify();
+            y = y + du.integrate(x) * b.clone();
+        }
+        Ok(y.simplify())
+    }
+
+    fn series(&self, consts: &[Var], x0: Expression, order: usize) -> Result<Expression, String> {
+        let x = self.x;
+        let n = self.order();
+        if n == 0 {
+            return Err(
+                "Equation does not contain derivatives of the unknown function".to_string(),
+            );
+        }
+        let a = self.f.clone().diff(self.p[n]).simplify();
+        if !a.clone().diff(self.p[n]).is_zero() {
+            return Err("Equation is not linear in the highest derivative".to_string());
+        }
+        // `y^(n) = G(x, y, ..., y^(n-1))`, differentiated along solutions for the higher ones
+        let g = (-self.f.clone().subs(self.p[n], e!(c!())) / a).simplify();
+        let at = |d: &Expression| {
+            let mut d = d.clone().subs(x, x0.clone());
+            for (&p, &c) in self.p.iter().zip(consts) {
+                d = d.subs(p, c);
+            }
+            d.simplify()
+        };
+        let mut sum = e!(c!());
+        let mut d = g.clone();
+        let mut factorial = 1.0;
+        for k in 0..order {
+            if k > 0 {
+                factorial *= k as f64;
+            }
+            let coeff = match consts.get(k) {
+                Some(&c) => e!(c),
+                None => {
+                    let value = at(&d);
+                    let (v, _) = value.eval_sampled(&[], 0);
+                    if !v.is_finite() {
+                        return Err("Expansion point is a singular point".to_string());
+                    }
+                    if k + 1 < order {
+                        let mut next = d.clone().diff(x);
+                        for j in 0..n {
+                            let dp = match j + 1 < n {
+                                true => e!(self.p[j + 1]),
+                                false => g.clone(),
+                            };
+                            next = next + d.clone().diff(self.p[j]) * dp;
+                        }
+                        d = next.simplify();
+                    }
+                    value
+                }
+            };
+            sum = sum + coeff * (e!(x) - x0.clone()).pow(k as f64) * e!(1.0 / factorial);
+        }
+        Ok(sum.simplify())
+    }
+}
+
+/// Replaces `y(x)` and its `k`-th derivatives by the slots `Var::slot(k)`, recording the
+/// highest order
+fn reduce(ex: &Expression, y: Func, x: Var, order: &mut usize) -> Result<Expression, String> {
+    let at_x = |ex: &Expression| ex.kind() == ExprKind::Var(x);
+    let unsupported =
+        || Err("Unknown function must only appear as y(x) and its derivatives".to_string());
+    match ex.kind() {
+        ExprKind::Func(f) if f == y => match &ex.args()[..] {
+            [arg] if at_x(arg) => Ok(e!(Var::slot(0))),
+            _ => unsupported(),
+        },
+        ExprKind::Derivative(v, n) => {
+            let [f, p] = <[Expression; 2]>::try_from(ex.args()).unwrap();
+            match (f.kind(), &f.args()[..]) {
+                (ExprKind::Func(f), [arg]) if f == y && v == x && at_x(arg) && at_x(&p) => {
+                    *order = (*order).max(n);
+                    Ok(e!(Var::slot(n)))
+                }
+                (ExprKind::Func(f), _) if f == y => unsupported(),
+                _ => Ok(ex.clone()),
+            }
+        }
+        ExprKind::Var(_) | ExprKind::Const(_) => Ok(ex.clone()),
+        kind => Ok(Expression::node(
+            kind,
+            ex.args()
+                .iter()
+                .map(|a| reduce(a, y, x, order))
+                .collect::<Result<Vec<_>, _>>()?,
+        )),
+    }
+}
+
+/// `y' = -P y + Q`, solved through the integrating factor `e^(∫P)`
+fn linear_first(p: Expression, q: Expression, x: Var, c: Var) -> Expression {
+    let mu = p.integrate(x).exp().simplify();
+    let integral = (mu.clone() * q).simplify().integrate(x);
+    ((integral + e!(c)) / mu).simplify()
+}
+
+/// Solves `lhs = rhs` for `y` by undoing the operations on the only term containing it
+fn isolate(mut lhs: Expression, y: Var, mut rhs: Expression) -> Option<Expression> {
+    loop {
+        match lhs.kind() {
+            ExprKind::Var(v) if v == y => return Some(rhs),
+            ExprKind::Add | ExprKind::Mul => {
+                let (free, mut dep): (Vec<_>, Vec<_>) =
+                    lhs.args().into_iter().partition(|a| !a.has(y));
+                if dep.len() != 1 {
+                    return None;
+                }
+                for a in free {
+                    rhs = match lhs.kind() {
+                        ExprKind::Add => rhs - a,
+                        _ => rhs / a,
+                    };
+                }
+                lhs = dep.pop().unwrap();
+            }
+            ExprKind::Ln => {
+                rhs = rhs.exp();
+                lhs = lhs.args().pop().unwrap();
+            }
+            ExprKind::Abs => lhs = lhs.args().pop().unwrap(),
+            ExprKind::Exp => match as_power(lhs.clone()).pop() {
+                Some((Some(base), k)) if !k.has(y) && as_power(lhs.clone()).len() == 1 => {
+                    rhs = rhs.pow(k.inv());
+                    lhs = base;
+                }
+                Some((None, u)) if as_power(lhs.clone()).len() == 1 => {
+                    rhs = rhs.ln();
+                    lhs = u;
+                }
+                _ => return None,
+            },
+            _ => return None,
+        }
+    }
+}
+
+/// Solutions `x^j e^(λx)` for the roots `λ` of the characteristic polynomial, as cosines
+/// and sines for complex pairs
+fn constant_basis(a: &[Expression], x: Var) -> Result<Vec<Expression>, String> {
+    modes(a, |r, j| e!(x).pow(j as f64) * (r * e!(x)).exp(), e!(x))
+}
+
+/// Solutions `x^λ ln(x)^j` for the roots `λ` of the indicial polynomial of
+/// `sum c_k x^k y^(k)`
+fn euler_basis(c: &[Expression], x: Var) -> Result<Vec<Expression>, String> {
+    // `x^k y^(k)` acts on `x^λ` as the falling factorial `λ (λ - 1) ... (λ - k + 1)`
+    let mut indicial = vec![e!(c!()); c.len()];
+    let mut falling = vec![e!(c!(+))];
+    for (k, c) in c.iter().enumerate() {
+        for (i, f) in falling.iter().enumerate() {
+            indicial[i] = (indicial[i].clone() + c.clone() * f.clone()).simplify();
+        }
+        let mut next = vec![e!(c!()); falling.len() + 1];
+        for (i, f) in falling.iter().enumerate() {
+            next[i + 1] = next[i + 1].clone() + f.clone();
+            next[i] = next[i].clone() - e!(k as f64) * f.clone();
+        }
+        falling = next.into_iter().map(|f| f.simplify()).collect();
+    }
+    let ln = e!(x).ln();
+    modes(
+        &indicial,
+        |r, j| ln.clone().pow(j as f64) * e!(x).pow(r),
+        ln.clone(),
+    )
+}
+
+/// One solution per root and multiplicity, built by `mode(λ, j)`, with complex conjugate
+/// pairs turned into `e^(Re λ t) cos(Im λ t)` and `sin` where `mode(Re λ, j)` multiplies
+fn modes(
+    poly: &[Expression],
+    mode: impl Fn(Expression, usize) -> Expression,
+    t: Expression,
+) -> Result<Vec<Expression>, String> {
+    let roots = integrate::roots(poly)
+        .ok_or_else(|| "Could not find the roots of the characteristic polynomial".to_string())?;
+    let mut basis = Vec::new();
+    for (r, m) in roots {
+        for j in 0..m {
+            match r.as_const() {
+                Some(z) if z.im.abs() > crate::TOL => {
+                    if z.im < 0.0 {
+                        continue;
+                    }
+                    let wt = e!(z.im) * t.clone();
+                    basis.push((mode(e!(z.re), j) * cos(wt.clone())).simplify());
+                    basis.push((mode(e!(z.re), j) * sin(wt)).simplify());
+                }
+                _ => basis.push(mode(r.clone(), j).simplify()),
+            }
+        }
+    }
+    Ok(basis)
+}
+
+/// Determinant by cofactor expansion along the first row
+fn det(m: &[Vec<Expression>]) -> Expression {
+    match m.len() {
+        0 => e!(c!(+)),
+        1 => m[0][0].clone(),
+        n => {
+            let mut sum = e!(c!());
+            for j in 0..n {
+                let minor = m[1..]
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .enumerate()
+                            .filter(|&(k, _)| k != j)
+                            .map(|(_, v)| v.clone())
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+                let term = m[0][j].clone() * det(&minor);
+                sum = match j % 2 {
+                    0 => sum + term,
+                    _ => sum - term,
+                };
+            }
+            sum
+        }
+    }
+}
@@ -0,0 +1,682 @@
+use num_complex::Complex64;
+
+use crate::{c, expression::*, Expression};
+
+const MAX_STEPS: usize = 100_000;
+
+/// Integration scheme for `OdeSystem::solve`
+#[derive(Debug, Clone, Copy)]
+pub enum Method {
+    /// Classical Runge–Kutta with a fixed step
+    Rk4(f64),
+    /// Adaptive Dormand–Prince 5(4)
+    Rk45 { rtol: f64, atol: f64 },
+    /// Adaptive, L-stable Rosenbrock 2(3) for stiff systems
+    Stiff { rtol: f64, atol: f64 },
+}
+
+/// Zero crossing of `g(t, y)` to be located along the solution, stopping the integration
+/// there when `terminal`
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub g: Expression,
+    pub terminal: bool,
+}
+
+/// Located zero crossing of the event with index `event`
+#[derive(Debug, Clone)]
+pub struct Crossing {
+    pub event: usize,
+    pub t: f64,
+    pub y: Vec<f64>,
+}
+
+/// System `y' = F(t, y)` with its right hand side compiled
+#[derive(Debug, Clone)]
+pub struct OdeSystem {
+    t: Var,
+    y: Vec<Var>,
+    rhs: Vec<Expression>,
+    f: Vec<Compiled>,
+    len: usize,
+}
+
+/// Accepted steps of a solution, with the data to interpolate inside each of them
+#[derive(Debug, Clone)]
+pub struct Trajectory {
+    pub t: Vec<f64>,
+    pub y: Vec<Vec<f64>>,
+    pub crossings: Vec<Crossing>,
+    /// Full step size and interpolant of the step starting at each `t[i]`, as the last one
+    /// may have been cut short by an event
+    steps: Vec<(f64, Dense)>,
+}
+
+#[derive(Debug, Clone)]
+enum Dense {
+    /// Cubic through both ends with their slopes
+    Hermite {
+        y1: Vec<f64>,
+        f0: Vec<f64>,
+        f1: Vec<f64>,
+    },
+    /// Dormand–Prince continuous extension of order 4
+    Dopri([Vec<f64>; 4]),
+    /// Rosenbrock continuous extension from the first two stages
+    Rosenbrock { k1: Vec<f64>, k2: Vec<f64> },
+}
+
+impl OdeSystem {
+    #[track_caller]
+    pub fn new(t: Var, y: Vec<Var>, rhs: Vec<Expression>) -> Result<Self, String> {
+        assert!(
+            y.len() == rhs.len(),
+            "Inadequate amount of equations, expected {} got {}",
+            y.len(),
+            rhs.len()
+        );
+        let len = y.iter().chain([&t]).map(|v| v.id + 1).max().unwrap();
+        let system = OdeSystem {
+            f: Vec::new(),
+            t,
+            y,
+            rhs,
+            len,
+        };
+        let f = system
+            .rhs
+            .iter()
+            .map(|f| system.compile(f.clone()))
+            .collect::<Result<_, _>>()?;
+        Ok(OdeSystem { f, ..system })
+    }
+
+    fn compile(&self, ex: Expression) -> Result<Compiled, String> {
+        if ex
+            .vars()
+            .iter()
+            .any(|v| *v != self.t && !self.y.contains(v))
+        {
+            return Err("Equation depends on variables other than t and y".to_string());
+        }
+        Ok(ex.compile())
+    }
+
+    fn values(&self, t: f64, y: &[f64]) -> Vec<Complex64> {
+        let mut values = vec![c!(); self.len];
+        values[self.t.id] = c!(t);
+        for (v, &y) in self.y.iter().zip(y) {
+            values[v.id] = c!(y);
+        }
+        values
+    }
+
+    fn eval(&self, t: f64, y: &[f64]) -> Vec<f64> {
+        let values = self.values(t, y);
+        self.f.iter().map(|f| f.eval(&values).re).collect()
+    }
+
+    /// Values of `ex`, a function of `t` and `y`, at every step of `trajectory`
+    pub fn along(&self, ex: Expression, trajectory: &Trajectory) -> Result<Vec<f64>, String> {
+        let f = self.compile(ex)?;
+        Ok(trajectory
+            .t
+            .iter()
+            .zip(&trajectory.y)
+            .map(|(&t, y)| f.eval(&self.values(t, y)).re)
+            .collect())
+    }
+
+    /// Solution from `y(t0) = y0` to `t1`, or to the first terminal event
+    #[track_caller]
+    pub fn solve(
+        &self,
+        method: Method,
+        t0: f64,
+        y0: Vec<f64>,
+        t1: f64,
+        events: &[Event],
+    ) -> Result<Trajectory, String> {
+        assert!(
+            y0.len() == self.y.len(),
+            "Inadequate amount of initial values, expected {} got {}",
+            self.y.len(),
+            y0.len()
+        );
+        let events = events
+            .iter()
+            .map(|e| Ok((self.compile(e.g.clone())?, e.terminal)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let mut stepper: Box<dyn Stepper> = match method {
+            Method::Rk4(h) => {
+                assert!(h > 0.0, "Step size must be positive");
+                Box::new(Rk4(h))
+            }
+            Method::Rk45 { rtol, atol } => Box::new(Dopri::new(rtol, atol)),
+            Method::Stiff { rtol, atol } => Box::new(Rosenbrock::new(self, rtol, atol)?),
+        };
+        let mut sol = Trajectory {
+            t: vec![t0],
+            y: vec![y0],
+            crossings: Vec::new(),
+            steps: Vec::new(),
+        };
+        let dir = (t1 - t0).signum();
+        let mut g = events
+            .iter()
+            .map(|(g, _)| g.eval(&self.values(t0, &sol.y[0])).re)
+            .collect::<Vec<_>>();
+        let mut h = dir
+            * match method {
+                Method::Rk4(h) => h,
+                _ => initial_step(self, t0, &sol.y[0], t1),
+            };
+        while dir * (t1 - sol.t.last().unwrap()) > 0.0 {
+            if sol.steps.len() >= MAX_STEPS {
+                return Err("Too many steps".to_string());
+            }
+            let t = *sol.t.last().unwrap();
+            let y = sol.y.last().unwrap().clone();
+            if dir * (t + h - t1) > 0.0 {
+                h = t1 - t;
+            }
+            let (taken, y1, dense, next) = stepper.step(self, t, &y, h)?;
+            h = next;
+            let t_end = match dir * (t1 - (t + taken)) < 1e-12 * t1.abs().max(1.0) {
+                true => t1,
+                false => t + taken,
+            };
+            sol.t.push(t_end);
+            sol.y.push(y1);
+            sol.steps.push((taken, dense));
+            if let Some(stop) = sol.locate(self, &events, &mut g) {
+                let y = sol.at(stop);
+                *sol.t.last_mut().unwrap() = stop;
+                *sol.y.last_mut().unwrap() = y;
+                break;
+            }
+        }
+        Ok(sol)
+    }
+}
+
+impl Trajectory {
+    /// Records the events crossed in the last step, returning the first terminal one
+    fn locate(
+        &mut self,
+        system: &OdeSystem,
+        events: &[(Compiled, bool)],
+        g: &mut [f64],
+    ) -> Option<f64> {
+        let n = self.t.len();
+        let (ta, tb) = (self.t[n - 2], self.t[n - 1]);
+        let value = |e: &Compiled, t: f64| e.eval(&system.values(t, &self.at(t))).re;
+        let mut found = Vec::new();
+        for (i, (e, terminal)) in events.iter().enumerate() {
+            let gb = value(e, tb);
+            let ga = std::mem::replace(&mut g[i], gb);
+            if ga == 0.0 || ga.signum() == gb.signum() {
+                continue;
+            }
+            // Illinois variant of regula falsi on the interpolant
+            let (mut a, mut b, mut fa, mut fb) = (ta, tb, ga, gb);
+            let mut side = 0;
+            for _ in 0..100 {
+                let c = (a * fb - b * fa) / (fb - fa);
+                let fc = value(e, c);
+                if fc == 0.0 || (b - a).abs() <= 1e-14 * c.abs().max(1.0) {
+                    a = c;
+                    b = c;
+                    break;
+                }
+                if fc.signum() == fb.signum() {
+                    b = c;
+                    fb = fc;
+                    if side == -1 {
+                        fa *= 0.5;
+                    }
+                    side = -1;
+                } else {
+                    a = c;
+                    fa = fc;
+                    if side == 1 {
+                        fb *= 0.5;
+                    }
+                    side = 1;
+                }
+            }
+            found.push((0.5 * (a + b), i, *terminal));
+        }
+        found.sort_by(|a, b| ((a.0 - ta).abs()).total_cmp(&(b.0 - ta).abs()));
+        for (t, event, terminal) in found {
+            self.crossings.push(Crossing {
+                event,
+                t,
+                y: self.at(t),
+            });
+            if terminal {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    /// Interpolated solution at `t`, which must lie in the integrated range
+    #[track_caller]
+    pub fn at(&self, t: f64) -> Vec<f64> {
+        let (first, last) = (self.t[0], *self.t.last().unwrap());
+        assert!(
+            (t - first) * (t - last) <= 0.0,
+            "Time {t} lies outside the solution from {first} to {last}"
+        );
+        let dir = (last - first).signum();
+        let i = self.t[1..]
+            .partition_point(|&s| dir * (s - t) < 0.0)
+            .min(self.steps.len().saturating_sub(1));
+        let Some((h, dense)) = self.steps.get(i) else {
+            return self.y[0].clone();
+        };
+        let (y0, s) = (&self.y[i], (t - self.t[i]) / h);
+        match dense {
+            Dense::Hermite { y1, f0, f1 } => (0..y0.len())
+                .map(|j| {
+                    let h00 = (1.0 + 2.0 * s) * (1.0 - s) * (1.0 - s);
+                    let h10 = s * (1.0 - s) * (1.0 - s);
+                    let h01 = s * s * (3.0 - 2.0 * s);
+                    let h11 = s * s * (s - 1.0);
+                    h00 * y0[j] + h10 * h * f0[j] + h01 * y1[j] + h11 * h * f1[j]
+                })
+                .collect(),
+            Dense::Dopri([r2, r3, r4, r5]) => (0..y0.len())
+                .map(|j| {
+                    y0[j] + s * (r2[j] + (1.0 - s) * (r3[j] + s * (r4[j] + (1.0 - s) * r5[j])))
+                })
+                .collect(),
+            Dense::Rosenbrock { k1, k2 } => {
+                let d = ROS_D;
+                let a = s * (1.0 - s) / (1.0 - 2.0 * d);
+                let b = s * (s - 2.0 * d) / (1.0 - 2.0 * d);
+                (0..y0.len())
+                    .map(|j| y0[j] + h * (a * k1[j] + b * k2[j]))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Rough first step, a hundredth of the scale on which `y` changes
+fn initial_step(system: &OdeSystem, t0: f64, y0: &[f64], t1: f64) -> f64 {
+    let f0 = system.eval(t0, y0);
+    let d0 = norm(y0);
+    let d1 = norm(&f0);
+    let span = (t1 - t0).abs();
+    let h = match d0 > 1e-5 && d1 > 1e-5 {
+        true => 0.01 * d0 / d1,
+        false => 1e-6 * span.max(1.0),
+    };
+    h.min(span)
+}
+
+fn norm(v: &[f64]) -> f64 {
+    (v.iter().map(|v| v * v).sum::<f64>() / v.len().max(1) as f64).sqrt()
+}
+
+/// Root mean square of `e` relative to the tolerance at `y0` and `y1`
+fn error_norm(e: &[f64], y0: &[f64], y1: &[f64], rtol: f64, atol: f64) -> f64 {
+    let scaled = (0..e.len())
+        .map(|i| e[i] / (atol + rtol * y0[i].abs().max(y1[i].abs())))
+        .collect::<Vec<_>>();
+    norm(&scaled)
+}
+
+fn axpy(y: &[f64], terms: &[(f64, &[f64])]) -> Vec<f64> {
+    (0..y.len())
+        .map(|i| y[i] + terms.iter().map(|(a, k)| a * k[i]).sum::<f64>())
+        .collect()
+}
+
+trait Stepper {
+    /// Attempts steps from `t` of size `h` downwards until one is accepted, returning the
+    /// size taken, the new value, its interpolant and the size to try next
+    fn step(
+        &mut self,
+        system: &OdeSystem,
+        t: f64,
+        y: &[f64],
+        h: f64,
+    ) -> Result<(f64, Vec<f64>, Dense, f64), String>;
+}
+
+struct Rk4(f64);
+
+impl Stepper for Rk4 {
+    fn step(
+        &mut self,
+        system: &OdeSystem,
+        t: f64,
+        y: &[f64],
+        h: f64,
+    ) -> Result<(f64, Vec<f64>, Dense, f64), String> {
+        let h = h.signum() * h.abs().min(self.0);
+        let k1 = system.eval(t, y);
+        let k2 = system.eval(t + 0.5 * h, &axpy(y, &[(0.5 * h, &k1)]));
+        let k3 = system.eval(t + 0.5 * h, &axpy(y, &[(0.5 * h, &k2)]));
+        let k4 = system.eval(t + h, &axpy(y, &[(h, &k3)]));
+        let y1 = axpy(
+            y,
+            &[
+                (h / 6.0, &k1),
+                (h / 3.0, &k2),
+                (h / 3.0, &k3),
+                (h / 6.0, &k4),
+            ],
+        );
+        if !y1.iter().all(|y| y.is_finite()) {
+            return Err(format!("Solution is not finite at {}", t + h));
+        }
+        let f1 = system.eval(t + h, &y1);
+        let dense = Dense::Hermite {
+            y1: y1.clone(),
+            f0: k1,
+            f1,
+        };
+        Ok((h, y1, dense, h.signum() * self.0))
+    }
+}
+
+/// Controls the step from the error norm of an embedded pair whose lower order is `order`
+fn next_step(h: f64, err: f64, order: f64) -> f64 {
+    let factor = match err {
+        0.0 => 5.0,
+        err => (0.9 * err.powf(-1.0 / (order + 1.0))).clamp(0.2, 5.0),
+    };
+    h * factor
+}
+
+fn check_step(t: f64, h: f64) -> Result<(), String> {
+    match h.abs() > 1e-14 * t.abs().max(1.0) {
+        true => Ok(()),
+        false => Err(format!("Step size underflow at {t}")),
+    }
+}
+
+struct Dopri {
+    rtol: f64,
+    atol: f64,
+}
+
+impl Dopri {
+    fn new(rtol: f64, atol: f64) -> Self {
+        Dopri { rtol, atol }
+    }
+}
+
+const A: [[f64; 6]; 6] = [
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+        0.0,
+        0.0,
+    ],
+    [
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+        0.0,
+    ],
+    [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+const C: [f64; 6] = [1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+/// Difference of the fifth and fourth order weights
+const E: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+/// Weights of the continuous extension
+const D: [f64; 7] = [
+    -12715105075.0 / 11282082432.0,
+    0.0,
+    87487479700.0 / 32700410799.0,
+    -10690763975.0 / 1880347072.0,
+    701980252875.0 / 199316789632.0,
+    -1453857185.0 / 822651844.0,
+    69997945.0 / 29380423.0,
+];
+
+impl Stepper for Dopri {
+    fn step(
+        &mut self,
+        system: &OdeSystem,
+        t: f64,
+        y: &[f64],
+        mut h: f64,
+    ) -> Result<(f64, Vec<f64>, Dense, f64), String> {
+        let k0 = system.eval(t, y);
+        loop {
+            check_step(t, h)?;
+            let mut k = vec![k0.clone()];
+            for (a, c) in A.iter().zip(C) {
+                let terms = k
+                    .iter()
+                    .zip(a)
+                    .map(|(k, &a)| (h * a, &k[..]))
+                    .collect::<Vec<_>>();
+                let yi = axpy(y, &terms);
+                k.push(system.eval(t + c * h, &yi));
+            }
+            // The last stage is evaluated at the fifth order solution
+            let y1 = axpy(
+                y,
+                &k[..6]
+                    .iter()
+                    .zip(A[5])
+                    .map(|(k, a)| (h * a, &k[..]))
+                    .collect::<Vec<_>>(),
+            );
+            let e = axpy(
+                &vec![0.0; y.len()],
+                &k.iter()
+                    .zip(E)
+                    .map(|(k, e)| (h * e, &k[..]))
+                    .collect::<Vec<_>>(),
+            );
+            let err = error_norm(&e, y, &y1, self.rtol, self.atol);
+            if !err.is_finite() || err > 1.0 {
+                h = match err.is_finite() {
+                    true => next_step(h, err, 4.0).abs().min(0.9 * h.abs()) * h.signum(),
+                    false => 0.25 * h,
+                };
+                continue;
+            }
+            let diff = axpy(&y1, &[(-1.0, y)]);
+            let r3 = axpy(&k[0], &[(-1.0 / h, &diff)])
+                .iter()
+                .map(|v| h * v)
+                .collect::<Vec<_>>();
+            let r4 = (0..y.len())
+                .map(|i| diff[i] - h * k[6][i] - r3[i])
+                .collect::<Vec<_>>();
+            let r5 = axpy(
+                &vec![0.0; y.len()],
+                &k.iter()
+                    .zip(D)
+                    .map(|(k, d)| (h * d, &k[..]))
+                    .collect::<Vec<_>>(),
+            );
+            let dense = Dense::Dopri([diff, r3, r4, r5]);
+            return Ok((h, y1, dense, next_step(h, err, 4.0)));
+        }
+    }
+}
+
+/// `1 / (2 + sqrt 2)`
+const ROS_D: f64 = 0.292_893_218_813_452_5;
+/// `6 + sqrt 2`
+const ROS_E32: f64 = 7.414_213_562_373_095;
+
+/// Rosenbrock pair of Shampine and Reichelt, with the Jacobian compiled from the
+/// derivatives of the right hand side
+struct Rosenbrock {
+    rtol: f64,
+    atol: f64,
+    jac: Vec<Vec<Compiled>>,
+    dt: Vec<Compiled>,
+}
+
+impl Rosenbrock {
+    fn new(system: &OdeSystem, rtol: f64, atol: f64) -> Result<Self, String> {
+        let jac = system
+            .rhs
+            .iter()
+            .map(|f| {
+                system
+                    .y
+                    .iter()
+                    .map(|&y| system.compile(f.clone().diff(y).simplify()))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        let dt = system
+            .rhs
+            .iter()
+            .map(|f| system.compile(f.clone().diff(system.t).simplify()))
+            .collect::<Result<_, _>>()?;
+        Ok(Rosenbrock {
+            rtol,
+            atol,
+            jac,
+            dt,
+        })
+    }
+}
+
+impl Stepper for Rosenbrock {
+    fn step(
+        &mut self,
+        system: &OdeSystem,
+        t: f64,
+        y: &[f64],
+        mut h: f64,
+    ) -> Result<(f64, Vec<f64>, Dense, f64), String> {
+        let n = y.len();
+        let values = system.values(t, y);
+        let jac = self
+            .jac
+            .iter()
+            .map(|row| row.iter().map(|j| j.eval(&values).re).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let dt = self
+            .dt
+            .iter()
+            .map(|d| d.eval(&values).re)
+            .collect::<Vec<_>>();
+        let f0 = system.eval(t, y);
+        loop {
+            check_step(t, h)?;
+            // `W = I - h d J`
+            let w = (0..n)
+                .map(|i| {
+                    (0..n)
+                        .map(|j| {
+                            let id = if i == j { 1.0 } else { 0.0 };
+                            id - h * ROS_D * jac[i][j]
+                        })
+                        .collect()
+                })
+                .collect();
+            let Some(lu) = Lu::new(w) else {
+                h *= 0.5;
+                continue;
+            };
+            let k1 = lu.solve(axpy(&f0, &[(h * ROS_D, &dt)]));
+            let f1 = system.eval(t + 0.5 * h, &axpy(y, &[(0.5 * h, &k1)]));
+            let k2 = axpy(&lu.solve(axpy(&f1, &[(-1.0, &k1)])), &[(1.0, &k1)]);
+            let y1 = axpy(y, &[(h, &k2)]);
+            let f2 = system.eval(t + h, &y1);
+            let rhs = (0..n)
+                .map(|i| {
+                    f2[i] - ROS_E32 * (k2[i] - f1[i]) - 2.0 * (k1[i] - f0[i]) + h * ROS_D * dt[i]
+                })
+                .collect();
+            let k3 = lu.solve(rhs);
+            let e = (0..n)
+                .map(|i| h / 6.0 * (k1[i] - 2.0 * k2[i] + k3[i]))
+                .collect::<Vec<_>>();
+            let err = error_norm(&e, y, &y1, self.rtol, self.atol);
+            if !err.is_finite() || err > 1.0 {
+                h = match err.is_finite() {
+                    true => next_step(h, err, 2.0).abs().min(0.9 * h.abs()) * h.signum(),
+                    false => 0.25 * h,
+                };
+                continue;
+            }
+            return Ok((h, y1, Dense::Rosenbrock { k1, k2 }, next_step(h, err, 2.0)));
+        }
+    }
+}
+
+/// LU decomposition with partial pivoting
+struct Lu {
+    m: Vec<Vec<f64>>,
+    perm: Vec<usize>,
+}
+
+impl Lu {
+    fn new(mut m: Vec<Vec<f64>>) -> Option<Self> {
+        let n = m.len();
+        let mut perm = (0..n).collect::<Vec<_>>();
+        for k in 0..n {
+            let pivot = (k..n).max_by(|&i, &j| m[i][k].abs().total_cmp(&m[j][k].abs()))?;
+            if m[pivot][k] == 0.0 || !m[pivot][k].is_finite() {
+                return None;
+            }
+            m.swap(k, pivot);
+            perm.swap(k, pivot);
+            let row = m[k].clone();
+            for below in &mut m[k + 1..] {
+                below[k] /= row[k];
+                let r = below[k];
+                for (a, b) in below[k + 1..].iter_mut().zip(&row[k + 1..]) {
+                    *a -= r * b;
+                }
+            }
+        }
+        Some(Lu { m, perm })
+    }
+
+    fn solve(&self, b: Vec<f64>) -> Vec<f64> {
+        let n = b.len();
+        let mut x = self.perm.iter().map(|&i| b[i]).collect::<Vec<_>>();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] -= self.m[i][j] * x[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                x[i] -= self.m[i][j] * x[j];
+            }
+            x[i] /= self.m[i][i];
+        }
+        x
+    }
+}
@@ -0,0 +1,307 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::{c, e, expression::*, Expression, SqMatrix};
+
+/// Polynomial `c_0 + c_1 ε + ...` in a bookkeeping parameter `ε`, truncated before `ε^order`.
+///
+/// Every operation drops the terms at or beyond the order as it builds them, so products of
+/// long chains never carry terms they will not keep.
+#[derive(Debug, Clone)]
+pub struct Perturbation {
+    coeffs: Vec<Expression>,
+    /// `None` for an exact value that is never truncated, as constants are
+    order: Option<usize>,
+}
+
+impl Perturbation {
+    pub fn from_coeffs(coeffs: Vec<Expression>, order: usize) -> Self {
+        Perturbation {
+            coeffs,
+            order: Some(order),
+        }
+        .truncated()
+    }
+
+    /// Expands `ex` in powers of `eps` about zero
+    pub fn new(ex: Expression, eps: Var, order: usize) -> Result<Self, String> {
+        let series = ex.series(eps, Point::Finite(e!(c!())), order as i64)?;
+        let mut coeffs = vec![e!(c!()); order];
+        for (k, c) in series.terms() {
+            if k < 0.0 || k.fract() != 0.0 {
+                return Err("Expression is not analytic in the perturbation parameter".to_string());
+            }
+            coeffs[k as usize] = c;
+        }
+        Ok(Perturbation::from_coeffs(coeffs, order))
+    }
+
+    fn truncated(mut self) -> Self {
+        self.coeffs.truncate(bound(self.coeffs.len(), self.order));
+        self
+    }
+
+    /// Order the polynomial is truncated before, or `None` for an exact value
+    pub fn order(&self) -> Option<usize> {
+        self.order
+    }
+
+    /// Coefficient of `ε^k`
+    pub fn coeff(&self, k: usize) -> Expression {
+        self.coeffs.get(k).cloned().unwrap_or_else(|| e!(c!()))
+    }
+
+    /// Recombines the orders into `c_0 + c_1 eps + ...`
+    pub fn expr(&self, eps: Var) -> Expression {
+        let mut sum = e!(c!());
+        for (k, c) in self.coeffs.iter().enumerate() {
+            sum = sum + c.clone() * e!(eps).pow(k as f64);
+        }
+        sum.simplify()
+    }
+
+    #[track_caller]
+    pub fn inv(&self) -> Self {
+        let c0 = self.coeff(0);
+        assert!(
+            !c0.is_zero(),
+            "Inverting a perturbation with a vanishing zeroth order"
+        );
+        let c0_inv = c0.inv().simplify();
+        let mut coeffs: Vec<Expression> = vec![c0_inv.clone()];
+        let len = match (self.coeffs.len(), self.order) {
+            (0 | 1, _) => 1,
+            (_, Some(order)) => order,
+            (_, None) => panic!("Inverting an untruncated perturbation"),
+        };
+        for k in 1..len {
+            let mut sum = e!(c!());
+            for j in 1..=k {
+                sum = sum + self.coeff(j) * coeffs[k - j].clone();
+            }
+            coeffs.push((-c0_inv.clone() * sum).simplify());
+        }
+        Perturbation {
+            coeffs,
+            order: self.order,
+        }
+        .truncated()
+    }
+}
+
+/// `len` cut down to `order` when there is one
+fn bound(len: usize, order: Option<usize>) -> usize {
+    order.map_or(len, |order| len.min(order))
+}
+
+/// Lower of two orders, `None` standing for no truncation
+fn min_order(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    a.into_iter().chain(b).min()
+}
+
+impl<T: Clone> Expressable<T>
+where
+    Expression: From<Expressable<T>>,
+{
+    /// Multivariate Taylor polynomial about `point`, keeping the terms of total degree below `order`
+    pub fn taylor<const N: usize>(
+        self,
+        x: [Var; N],
+        point: [Expression; N],
+        order: usize,
+    ) -> Expression {
+        taylor(e!(self), x, point, order)
+    }
+}
+
+fn taylor<const N: usize>(
+    ex: Expression,
+    x: [Var; N],
+    point: [Expression; N],
+    order: usize,
+) -> Expression {
+    assert!(
+        point.iter().all(|p| x.iter().all(|&v| !p.has(v))),
+        "Expansion point depends on the expansion variables"
+    );
+    fn terms<const N: usize>(
+        d: Expression,
+        start: usize,
+        alpha: &mut [usize; N],
+        (x, point, order): (&[Var; N], &[Expression; N], usize),
+        sum: &mut Expression,
+    ) {
+        let mut coeff = d.clone();
+        let mut factorial = 1.0;
+        let mut monomial = e!(c!(+));
+        for i in 0..N {
+            coeff = coeff.subs(x[i], point[i].clone());
+            for k in 1..=alpha[i] {
+                factorial *= k as f64;
+                monomial = monomial * (e!(x[i]) - point[i].clone());
+            }
+        }
+        *sum = sum.clone() + coeff.simplify() * monomial * e!(c!(1.0 / factorial));
+        if alpha.iter().sum::<usize>() + 1 >= order {
+            return;
+        }
+        for i in start..N {
+            alpha[i] += 1;
+            terms(
+                d.clone().diff(x[i]).simplify(),
+                i,
+                alpha,
+                (x, point, order),
+                sum,
+            );
+            alpha[i] -= 1;
+        }
+    }
+    let mut sum = e!(c!());
+    if order > 0 {
+        terms(ex, 0, &mut [0; N], (&x, &point, order), &mut sum);
+    }
+    sum.simplify()
+}
+
+impl Scalar for Perturbation {
+    fn constant(c: Complex64) -> Self {
+        Perturbation {
+            coeffs: vec![e!(c)],
+            order: None,
+        }
+    }
+
+    fn diff(&self, x: Var) -> Self {
+        Perturbation {
+            coeffs: self.coeffs.iter().map(|c| c.clone().diff(x)).collect(),
+            order: self.order,
+        }
+    }
+
+    fn simplify(&self) -> Self {
+        Perturbation {
+            coeffs: self.coeffs.iter().map(|c| c.clone().simplify()).collect(),
+            order: self.order,
+        }
+    }
+}
+
+impl Add for Perturbation {
+    type Output = Perturbation;
+    fn add(self, rhs: Self) -> Self::Output {
+        let order = min_order(self.order, rhs.order);
+        let len = bound(self.coeffs.len().max(rhs.coeffs.len()), order);
+        let coeffs = (0..len).map(|k| self.coeff(k) + rhs.coeff(k)).collect();
+        Perturbation { coeffs, order }
+    }
+}
+
+impl Neg for Perturbation {
+    type Output = Perturbation;
+    fn neg(self) -> Self::Output {
+        Perturbation {
+            coeffs: self.coeffs.into_iter().map(Neg::neg).collect(),
+            order: self.order,
+        }
+    }
+}
+
+impl Sub for Perturbation {
+    type Output = Perturbation;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Mul for Perturbation {
+    type Output = Perturbation;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, rhs: Self) -> Self::Output {
+        let order = min_order(self.order, rhs.order);
+        let len = bound(
+            (self.coeffs.len() + rhs.coeffs.len()).saturating_sub(1),
+            order,
+        );
+        let coeffs = (0..len)
+            .map(|k| {
+                let mut sum = e!(c!());
+                for j in 0..=k {
+                    if j < self.coeffs.len() && k - j < rhs.coeffs.len() {
+                        sum = sum + self.coeffs[j].clone() * rhs.coeffs[k - j].clone();
+                    }
+                }
+                sum
+            })
+            .collect();
+        Perturbation { coeffs, order }
+    }
+}
+
+impl Div for Perturbation {
+    type Output = Perturbation;
+    #[track_caller]
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inv()
+    }
+}
+
+impl<const N: usize> SqMatrix<N, Perturbation> {
+    /// Expands every entry of `mat` in powers of `eps`
+    pub fn perturbation(mat: &SqMatrix<N>, eps: Var, order: usize) -> Result<Self, String> {
+        let mut new = Self::zeroes();
+        for i in 0..N {
+            for j in 0..N {
+                new[i][j] = Perturbation::new(mat[i][j].clone(), eps, order)?;
+            }
+        }
+        Ok(new)
+    }
+
+    /// Zeroth order of the matrix
+    pub fn background(&self) -> SqMatrix<N> {
+        let mut new = SqMatrix::zeroes();
+        for i in 0..N {
+            for j in 0..N {
+                new[i][j] = self[i][j].coeff(0);
+            }
+        }
+        new
+    }
+
+    /// Inverse order by order, `X_k = -g_0^{-1} sum_{j >= 1} g_j X_{k-j}`
+    pub fn inv(&self) -> Self {
+        let order = self
+            .0
+            .iter()
+            .flatten()
+            .filter_map(Perturbation::order)
+            .min();
+        let g0_inv = self.background().inv().simplify();
+        let part = |k: usize| {
+            let mut new = SqMatrix::<N>::zeroes();
+            for i in 0..N {
+                for j in 0..N {
+                    new[i][j] = self[i][j].coeff(k);
+                }
+            }
+            new
+        };
+        let mut parts = vec![g0_inv.clone()];
+        for k in 1..order.unwrap_or(1) {
+            let mut sum = SqMatrix::<N>::zeroes();
+            for j in 1..=k {
+                sum = sum + part(j) * parts[k - j].clone();
+            }
+            parts.push((g0_inv.clone() * sum * e!(c!(-))).simplify());
+        }
+        let mut new = Self::zeroes();
+        for i in 0..N {
+            for j in 0..N {
+                let coeffs = parts.iter().map(|p| p[i][j].clone()).collect();
+                new[i][j] = Perturbation { coeffs, order }.truncated();
+            }
+        }
+        new
+    }
+}
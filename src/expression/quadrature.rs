@@ -0,0 +1,192 @@
+use std::f64::consts::FRAC_PI_2;
+
+use num_complex::{Complex64, ComplexFloat};
+
+use crate::c;
+
+const REL_TOL: f64 = 1e-10;
+const ABS_TOL: f64 = 1e-13;
+const MAX_INTERVALS: usize = 2_000;
+const MAX_LEVEL: usize = 12;
+
+/// Kronrod nodes on `[0, 1]`, the odd ones shared with the 7 point Gauss rule
+const XGK: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_5,
+    0.0,
+];
+const WGK: [f64; 8] = [
+    0.022_935_322_010_529_22,
+    0.063_092_092_629_978_55,
+    0.104_790_010_322_250_2,
+    0.140_653_259_715_525_9,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_4,
+    0.204_432_940_075_298_9,
+    0.209_482_141_084_727_8,
+];
+const WG: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_7,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+fn tolerance(value: Complex64) -> f64 {
+    ABS_TOL.max(REL_TOL * value.abs())
+}
+
+/// Integral of `f` over `[a, b]` with an estimate of its absolute error. Adaptive
+/// Gauss–Kronrod is tried first, and tanh-sinh, which copes better with endpoint
+/// singularities, when that does not converge.
+pub(crate) fn quad(
+    f: &impl Fn(f64) -> Complex64,
+    a: f64,
+    b: f64,
+) -> Result<(Complex64, f64), String> {
+    let gk = gauss_kronrod(f, a, b);
+    if let Ok((value, error)) = gk {
+        if error <= tolerance(value) {
+            return gk;
+        }
+    }
+    match (gk, tanh_sinh(f, a, b)) {
+        (Ok(gk), Ok(ts)) if gk.1 <= ts.1 => Ok(gk),
+        (_, Ok(ts)) => Ok(ts),
+        (gk, Err(_)) => gk,
+    }
+}
+
+/// 15 point Kronrod estimate over `[a, b]`, with the difference to the embedded Gauss rule
+/// as the error
+fn kronrod(f: &impl Fn(f64) -> Complex64, a: f64, b: f64) -> Result<(Complex64, f64), String> {
+    let c = 0.5 * (a + b);
+    let h = 0.5 * (b - a);
+    let eval = |x: f64| match f(x) {
+        v if v.is_finite() => Ok(v),
+        _ => Err(format!("Integrand is not finite at {x}")),
+    };
+    let fc = eval(c)?;
+    let mut k = fc * WGK[7];
+    let mut g = fc * WG[3];
+    for j in 0..7 {
+        let pair = eval(c - h * XGK[j])? + eval(c + h * XGK[j])?;
+        k += pair * WGK[j];
+        if j % 2 == 1 {
+            g += pair * WG[j / 2];
+        }
+    }
+    Ok((k * h, ((k - g) * h).abs()))
+}
+
+/// Globally adaptive bisection, always splitting the interval with the largest error
+fn gauss_kronrod(
+    f: &impl Fn(f64) -> Complex64,
+    a: f64,
+    b: f64,
+) -> Result<(Complex64, f64), String> {
+    let (value, error) = kronrod(f, a, b)?;
+    let mut intervals = vec![(a, b, value, error)];
+    while intervals.len() < MAX_INTERVALS {
+        let value = intervals.iter().map(|i| i.2).sum::<Complex64>();
+        let error = intervals.iter().map(|i| i.3).sum::<f64>();
+        if error <= tolerance(value) {
+            break;
+        }
+        let worst = (0..intervals.len())
+            .max_by(|&i, &j| intervals[i].3.total_cmp(&intervals[j].3))
+            .unwrap();
+        let (a, b, ..) = intervals[worst];
+        let m = 0.5 * (a + b);
+        if m == a || m == b {
+            break;
+        }
+        let (lv, le) = kronrod(f, a, m)?;
+        let (rv, re) = kronrod(f, m, b)?;
+        intervals[worst] = (a, m, lv, le);
+        intervals.push((m, b, rv, re));
+    }
+    let value = intervals.iter().map(|i| i.2).sum();
+    let error = intervals.iter().map(|i| i.3).sum();
+    Ok((value, error))
+}
+
+/// Double exponential quadrature over `[a, b]`, halving the step until two levels agree.
+/// Nodes are placed relative to the endpoint they approach, so that they can crowd it
+/// without rounding onto it. Where they still do, the part of the integral beyond the last
+/// node is bounded as that of an `|x|^(-3/4)` singularity and counted as error.
+fn tanh_sinh(f: &impl Fn(f64) -> Complex64, a: f64, b: f64) -> Result<(Complex64, f64), String> {
+    let h = 0.5 * (b - a);
+    // Weighted sum over `t = ±k step` for `k = first, first + by, ...` until the weights
+    // vanish, with the estimated tail beyond the nodes
+    let nodes = |step: f64, first: usize, by: usize| {
+        let mut sum = c!();
+        let mut tail = 0.0;
+        for left in [true, false] {
+            let mut last = 0.0;
+            for k in (first..).step_by(by) {
+                let t = k as f64 * step;
+                let u = FRAC_PI_2 * t.sinh();
+                let w = FRAC_PI_2 * t.cosh() / (u.cosh() * u.cosh());
+                let d = h / (u.exp() * u.cosh());
+                let x = if left { a + d } else { b - d };
+                if w < 1e-300 {
+                    break;
+                }
+                if x == a || x == b {
+                    tail += 4.0 * last;
+                    break;
+                }
+                let v = f(x);
+                if v.is_finite() {
+                    sum += v * w;
+                    last = (v * d).abs();
+                }
+            }
+        }
+        (sum, tail)
+    };
+    let closest = |tail: f64, t: f64| if t > 0.0 { tail.min(t) } else { tail };
+    let mut step = 1.0;
+    let (mut sum, t) = nodes(step, 1, 1);
+    let mut tail = closest(f64::INFINITY, t);
+    sum += f(a + h) * FRAC_PI_2;
+    let mut value = sum * h * step;
+    let mut error = f64::INFINITY;
+    for _ in 0..MAX_LEVEL {
+        step *= 0.5;
+        let (odd, t) = nodes(step, 1, 2);
+        sum += odd;
+        tail = closest(tail, t);
+        let next = sum * h * step;
+        error = (next - value).abs();
+        value = next;
+        if error <= tolerance(value) {
+            break;
+        }
+    }
+    match value.is_finite() {
+        true if tail.is_finite() => Ok((value, error + tail)),
+        true => Ok((value, error)),
+        false => Err("Integral is not finite".to_string()),
+    }
+}
+
+/// Integral of `f` over `[a, inf)` when `dir` is 1 and over `(-inf, a]` when it is -1,
+/// mapped onto `[0, 1)` through `x = a + dir t / (1 - t)`
+pub(crate) fn half_line(
+    f: &impl Fn(f64) -> Complex64,
+    a: f64,
+    dir: f64,
+) -> Result<(Complex64, f64), String> {
+    let g = |t: f64| {
+        let s = 1.0 / (1.0 - t);
+        f(a + dir * t * s) * s * s
+    };
+    quad(&g, 0.0, 1.0)
+}
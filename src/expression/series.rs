@@ -0,0 +1,554 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use crate::{c, e, expression::*, Expression};
+
+const MAX_LEAD_ORDER: i64 = 16;
+
+/// Exponent of a Puiseux series term
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Rational {
+    num: i64,
+    den: i64,
+}
+
+impl Rational {
+    pub(crate) const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub(crate) const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub(crate) fn new(num: i64, den: i64) -> Self {
+        fn gcd(a: i64, b: i64) -> i64 {
+            if b == 0 {
+                a.abs()
+            } else {
+                gcd(b, a % b)
+            }
+        }
+        let g = gcd(num, den).max(1) * den.signum();
+        Rational {
+            num: num / g,
+            den: den / g,
+        }
+    }
+
+    pub(crate) fn int(n: i64) -> Self {
+        Rational { num: n, den: 1 }
+    }
+
+    /// Recovers a rational with a small denominator from a float
+    pub(crate) fn from_f64(x: f64) -> Option<Self> {
+        let (mut h0, mut h1, mut k0, mut k1) = (0i64, 1i64, 1i64, 0i64);
+        let mut y = x;
+        for _ in 0..32 {
+            let a = y.floor();
+            if a.abs() > 1e12 {
+                break;
+            }
+            let a = a as i64;
+            (h0, h1) = (h1, a * h1 + h0);
+            (k0, k1) = (k1, a * k1 + k0);
+            if k1 > 1000 {
+                return None;
+            }
+            if (x - h1 as f64 / k1 as f64).abs() < 1e-12 * x.abs().max(1.0) {
+                return Some(Rational::new(h1, k1));
+            }
+            y = 1.0 / (y - a as f64);
+        }
+        None
+    }
+
+    pub(crate) fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num as i128 * other.den as i128).cmp(&(other.num as i128 * self.den as i128))
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+    fn add(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+    fn mul(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+    fn div(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.den, self.den * rhs.num)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self) -> Self::Output {
+        Rational::new(-self.num, self.den)
+    }
+}
+
+/// Truncated Taylor, Laurent or Puiseux series about a point, carrying an explicit
+/// `O(h^order)` remainder where `h` is the displacement from the point (`1/x` at infinity).
+///
+/// Coefficients are free of the expansion variable, except for `ln(h)` terms produced by
+/// expanding logarithms at a branch point.
+#[derive(Debug, Clone)]
+pub struct Series {
+    pub(crate) x: Var,
+    pub(crate) point: Point,
+    pub(crate) terms: Vec<(Rational, Expression)>,
+    pub(crate) order: Option<Rational>,
+}
+
+impl<T: Clone> Expressable<T>
+where
+    Expression: From<Expressable<T>>,
+{
+    /// Expands about `point` in `x`, up to but excluding `(x - point)^order`
+    pub fn series(self, x: Var, point: Point, order: i64) -> Result<Series, String> {
+        let h = displacement(e!(self), x, &point)?;
+        let mut series = Expander::new(x).expand(&h, Rational::int(order))?;
+        series.point = point;
+        Ok(series.truncate(Rational::int(order)))
+    }
+}
+
+/// Rewrites `ex` so that the expansion point of `x` sits at `x = 0`
+fn displacement(ex: Expression, x: Var, point: &Point) -> Result<Expression, String> {
+    let h = match point {
+        Point::Finite(p) if p.has(x) => {
+            return Err("Expansion point depends on the expansion variable".to_string())
+        }
+        Point::Finite(p) => ex.subs(x, p.clone() + e!(x)),
+        Point::PosInf => ex.subs(x, e!(x).inv()),
+        Point::NegInf => ex.subs(x, -e!(x).inv()),
+    };
+    Ok(h.simplify())
+}
+
+/// Leading term `(exponent, coefficient)` of `ex` as `x -> 0+`
+pub(crate) fn leading_term(ex: &Expression, x: Var) -> Result<(Rational, Expression), String> {
+    Expander::new(x).lead(ex)
+}
+
+impl Series {
+    fn exact(x: Var, terms: Vec<(Rational, Expression)>) -> Self {
+        Series {
+            x,
+            point: Point::Finite(e!(c!())),
+            terms,
+            order: None,
+        }
+        .normalized()
+    }
+
+    fn constant(x: Var, c: Expression) -> Self {
+        Series::exact(x, vec![(Rational::ZERO, c)])
+    }
+
+    fn normalized(mut self) -> Self {
+        self.terms.sort_by_key(|&(k, _)| k);
+        let mut terms: Vec<(Rational, Expression)> = Vec::new();
+        for (k, c) in self.terms {
+            match terms.last_mut() {
+                Some((last, sum)) if *last == k => *sum = sum.clone() + c,
+                _ => terms.push((k, c)),
+            }
+        }
+        self.terms = terms
+            .into_iter()
+            .filter(|(k, _)| self.order.is_none_or(|order| *k < order))
+            .map(|(k, c)| (k, c.simplify()))
+            .filter(|(_, c)| !c.is_zero())
+            .collect();
+        self
+    }
+
+    fn truncate(mut self, order: Rational) -> Self {
+        self.order = Some(self.order.map_or(order, |o| o.min(order)));
+        self.normalized()
+    }
+
+    fn lead(&self) -> Option<Rational> {
+        self.terms.first().map(|&(k, _)| k).or(self.order)
+    }
+
+    fn shift(mut self, by: Rational) -> Self {
+        for (k, _) in &mut self.terms {
+            *k = *k + by;
+        }
+        self.order = self.order.map(|o| o + by);
+        self
+    }
+
+    fn scale(mut self, c: Expression) -> Self {
+        for (_, coeff) in &mut self.terms {
+            *coeff = coeff.clone() * c.clone();
+        }
+        self.normalized()
+    }
+
+    /// Splits off the leading term, returning `(a, c, w)` with `self = c h^a (1 + w)`
+    fn factor_lead(&self) -> Result<(Rational, Expression, Series), String> {
+        let (a, c) = self
+            .terms
+            .first()
+            .cloned()
+            .ok_or("Series has no nonvanishing terms to factor")?;
+        let mut w = self.clone().shift(-a).scale(c.clone().inv());
+        w.terms.retain(|(k, _)| *k != Rational::ZERO);
+        Ok((a, c, w.normalized()))
+    }
+
+    /// `sum coeffs[j] w^j`, with as many terms as the order of `w` allows
+    fn compose(w: &Series, mut coeffs: impl FnMut(i64) -> Expression) -> Series {
+        let mut result = Series::constant(w.x, coeffs(0));
+        result.order = w.order;
+        let Some(lead) = w.terms.first().map(|&(k, _)| k) else {
+            return result.normalized();
+        };
+        let mut power = Series::constant(w.x, e!(c!(+)));
+        let mut j = 1;
+        while w.order.is_none_or(|o| lead * Rational::int(j) < o) && j <= MAX_LEAD_ORDER * 4 {
+            power = power * w.clone();
+            result = result + power.clone().scale(coeffs(j));
+            j += 1;
+        }
+        result
+    }
+
+    pub fn exp(self) -> Result<Series, String> {
+        if self.terms.first().is_some_and(|&(k, _)| k < Rational::ZERO) {
+            return Err("Essential singularity in exponential".to_string());
+        }
+        let mut w = self.clone();
+        w.terms.retain(|(k, _)| *k != Rational::ZERO);
+        let c0 = self
+            .terms
+            .iter()
+            .find(|(k, _)| *k == Rational::ZERO)
+            .map_or(e!(c!()), |(_, c)| c.clone());
+
+        let (shift, c0) = split_log(c0, self.x);
+        let mut factorial = 1.0;
+        let series = Series::compose(&w, |j| {
+            factorial *= j.max(1) as f64;
+            e!(c!(1.0 / factorial))
+        });
+        Ok(series.scale(c0.exp()).shift(shift))
+    }
+
+    pub fn ln(self) -> Result<Series, String> {
+        let (a, c, w) = self.factor_lead()?;
+        let mut series = Series::compose(&w, |j| match j {
+            0 => e!(c!()),
+            j => e!(c!(if j % 2 == 0 { -1.0 } else { 1.0 } / j as f64)),
+        });
+        let c0 = c.ln() + e!(c!(a.to_f64())) * e!(self.x).ln();
+        series.terms.push((Rational::ZERO, c0));
+        Ok(series.normalized())
+    }
+
+    pub fn pow(self, k: Expression) -> Result<Series, String> {
+        let k = k.simplify();
+        let (a, c, w) = self.factor_lead()?;
+        let ak = match a == Rational::ZERO {
+            true => Rational::ZERO,
+            false => {
+                k.as_const()
+                    .filter(|k| k.im == 0.0)
+                    .and_then(|k| Rational::from_f64(k.re))
+                    .ok_or("Non-rational power of a series with a zero or pole")?
+                    * a
+            }
+        };
+        let mut binom = e!(c!(+));
+        let series = Series::compose(&w, |j| {
+            if j > 0 {
+                binom =
+                    binom.clone() * (k.clone() - e!(c!((j - 1) as f64))) * e!(c!(1.0 / j as f64));
+            }
+            binom.clone()
+        });
+        Ok(series.scale(c.pow(k)).shift(ak))
+    }
+
+    pub fn abs(self) -> Result<Series, String> {
+        let Some((_, c)) = self.terms.first() else {
+            return Ok(self);
+        };
+        match c.as_const() {
+            Some(c) if c.im == 0.0 => Ok(self.scale(e!(c!(c.re.signum())))),
+            _ => Err("Sign of the leading coefficient is unknown".to_string()),
+        }
+    }
+
+    /// Order of the remainder term, or `None` for an exact expansion
+    pub fn order(&self) -> Option<f64> {
+        self.order.map(Rational::to_f64)
+    }
+
+    /// Terms as `(exponent, coefficient)` pairs, in increasing powers of the displacement
+    pub fn terms(&self) -> impl Iterator<Item = (f64, Expression)> + '_ {
+        let h = self.h();
+        self.terms
+            .iter()
+            .map(move |(k, c)| (k.to_f64(), c.clone().subs(self.x, h.clone())))
+    }
+
+    /// Displacement from the expansion point, the variable the series is in
+    pub(crate) fn h(&self) -> Expression {
+        match &self.point {
+            Point::Finite(p) => e!(self.x) - p.clone(),
+            Point::PosInf => e!(self.x).inv(),
+            Point::NegInf => -e!(self.x).inv(),
+        }
+    }
+
+    #[track_caller]
+    fn check(&self, other: &Series) {
+        let same_point = match (&self.point, &other.point) {
+            (Point::Finite(p), Point::Finite(q)) => (p.clone() - q.clone()).simplify().is_zero(),
+            (Point::PosInf, Point::PosInf) | (Point::NegInf, Point::NegInf) => true,
+            _ => false,
+        };
+        assert!(
+            self.x == other.x && same_point,
+            "Series are in different variables or about different points"
+        );
+    }
+}
+
+/// Separates `a ln(x)` terms out of `c`, returning `(a, rest)`
+fn split_log(c: Expression, x: Var) -> (Rational, Expression) {
+    let c = c.simplify();
+    let terms = match c.kind() {
+        ExprKind::Add => c.args(),
+        _ => vec![c],
+    };
+    let mut a = Rational::ZERO;
+    let mut rest = e!(c!());
+    for term in terms {
+        let k = match term.kind() {
+            ExprKind::Ln if term.args()[0].kind() == ExprKind::Var(x) => Some(Rational::ONE),
+            ExprKind::Mul => match &term.args()[..] {
+                [l, k] if l.kind() == ExprKind::Ln && l.args()[0].kind() == ExprKind::Var(x) => k
+                    .as_const()
+                    .filter(|k| k.im == 0.0)
+                    .and_then(|k| Rational::from_f64(k.re)),
+                _ => None,
+            },
+            _ => None,
+        };
+        match k {
+            Some(k) => a = a + k,
+            None => rest = rest + term,
+        }
+    }
+    (a, rest)
+}
+
+impl From<Series> for Expression {
+    fn from(value: Series) -> Self {
+        let h = value.h().simplify();
+        let mut terms = value
+            .terms
+            .iter()
+            .map(|(k, c)| {
+                let c = c.clone().subs(value.x, h.clone()).simplify();
+                let power = match *k {
+                    Rational::ZERO => return c,
+                    Rational::ONE => h.clone(),
+                    k => pow_node(h.clone(), e!(c!(k.to_f64()))),
+                };
+                match c.as_const() {
+                    Some(c) if c == c!(+) => power,
+                    _ => Expression::node(ExprKind::Mul, [c, power]),
+                }
+            })
+            .collect::<Vec<_>>();
+        match terms.len() {
+            0 => e!(c!()),
+            1 => terms.pop().unwrap(),
+            _ => Expression::node(ExprKind::Add, terms),
+        }
+    }
+}
+
+impl Add for Series {
+    type Output = Series;
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self.check(&rhs);
+        self.order = match (self.order, rhs.order) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.terms.extend(rhs.terms);
+        self.normalized()
+    }
+}
+
+impl Neg for Series {
+    type Output = Series;
+    fn neg(self) -> Self::Output {
+        self.scale(e!(c!(-)))
+    }
+}
+
+impl Sub for Series {
+    type Output = Series;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Mul for Series {
+    type Output = Series;
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.check(&rhs);
+        let order = match (self.order, rhs.order, self.lead(), rhs.lead()) {
+            (Some(a), Some(b), Some(la), Some(lb)) => Some((a + lb).min(b + la)),
+            (Some(a), _, _, Some(lb)) => Some(a + lb),
+            (_, Some(b), Some(la), _) => Some(b + la),
+            _ => None,
+        };
+        let mut terms = Vec::new();
+        for (a, x) in &self.terms {
+            for (b, y) in &rhs.terms {
+                if order.is_none_or(|o| *a + *b < o) {
+                    terms.push((*a + *b, x.clone() * y.clone()));
+                }
+            }
+        }
+        Series {
+            terms,
+            order,
+            ..self
+        }
+        .normalized()
+    }
+}
+
+impl Div for Series {
+    type Output = Series;
+    #[track_caller]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.pow(e!(c!(-))).expect("Division by a vanishing series")
+    }
+}
+
+struct Expander {
+    x: Var,
+    leads: HashMap<String, (Rational, Expression)>,
+}
+
+impl Expander {
+    fn new(x: Var) -> Self {
+        Expander {
+            x,
+            leads: HashMap::new(),
+        }
+    }
+
+    fn lead(&mut self, ex: &Expression) -> Result<(Rational, Expression), String> {
+        let key = ex.key();
+        if let Some(lead) = self.leads.get(&key) {
+            return Ok(lead.clone());
+        }
+        let mut order = 1;
+        while order <= MAX_LEAD_ORDER {
+            if let Some(term) = self.expand(ex, Rational::int(order))?.terms.first() {
+                self.leads.insert(key, term.clone());
+                return Ok(term.clone());
+            }
+            order *= 2;
+        }
+        Err("Could not find the leading term of the series".to_string())
+    }
+
+    /// Series of `ex` in `x` about `x = 0`, accurate up to `O(x^order)`
+    fn expand(&mut self, ex: &Expression, order: Rational) -> Result<Series, String> {
+        let x = self.x;
+        if !ex.has(x) {
+            return Ok(Series::constant(x, ex.clone()));
+        }
+        let series = match ex.kind() {
+            ExprKind::Var(_) => Series::exact(x, vec![(Rational::ONE, e!(c!(+)))]),
+            ExprKind::Add => {
+                let mut sum = Series::constant(x, e!(c!()));
+                for term in ex.args() {
+                    sum = sum + self.expand(&term, order)?;
+                }
+                sum
+            }
+            ExprKind::Mul => {
+                let factors = ex.args();
+                let leads = factors
+                    .iter()
+                    .map(|f| self.lead(f).map(|(k, _)| k))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let total = leads.iter().fold(Rational::ZERO, |a, &b| a + b);
+                let mut product = Series::constant(x, e!(c!(+)));
+                for (factor, lead) in factors.iter().zip(leads) {
+                    product = product * self.expand(factor, order - total + lead)?;
+                }
+                product
+            }
+            ExprKind::Exp => match &as_power(ex.clone())[..] {
+                [(Some(base), k)] if !k.has(x) => {
+                    let (a, _) = self.lead(base)?;
+                    let ak = k
+                        .as_const()
+                        .filter(|k| k.im == 0.0)
+                        .and_then(|k| Rational::from_f64(k.re))
+                        .map_or(Rational::ZERO, |k| k * a);
+                    if ak >= order {
+                        return Ok(Series::exact(x, Vec::new()).truncate(order));
+                    }
+                    self.expand(base, order - ak + a)?.pow(k.clone())?
+                }
+                _ => self.expand(&ex.args()[0], order)?.exp()?,
+            },
+            ExprKind::Ln => {
+                let arg = &ex.args()[0];
+                let (a, _) = self.lead(arg)?;
+                self.expand(arg, order + a)?.ln()?
+            }
+            ExprKind::Abs => self.expand(&ex.args()[0], order)?.abs()?,
+            ExprKind::Integral(_) => {
+                return Err("Cannot expand an unevaluated integral".to_string())
+            }
+            ExprKind::Func(_) | ExprKind::Derivative(..) => {
+                return Err("Cannot expand an undefined function".to_string())
+            }
+            ExprKind::ROOT | ExprKind::Const(_) => Series::constant(x, ex.clone()),
+        };
+        Ok(series.truncate(order))
+    }
+}
@@ -12,3 +12,16 @@ impl From<Var> for Expression {
         Expressable(Expressand { tree })
     }
 }
+
+impl Var {
+    /// Dummy standing for the `i`-th argument of a function, bound by the derivative taken in
+    /// that argument
+    pub(crate) fn slot(i: usize) -> Var {
+        Var { id: usize::MAX - i }
+    }
+
+    /// Slots lie above every variable a system can declare
+    pub(crate) fn is_slot(self) -> bool {
+        self.id > usize::MAX / 2
+    }
+}
@@ -0,0 +1,309 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::{c, e, tensor::permutations, Expressable, Expression, SqMatrix, Tensor, Var, Variance};
+
+/// Differential `p`-form `α = α_I dx^I` in `N` dimensions, `I` running over increasing index
+/// tuples. Only those components are stored, in lexicographic order, and they are the
+/// components `α_μν..` of the corresponding antisymmetric covariant tensor.
+#[derive(Debug, Clone)]
+pub struct Form<const N: usize> {
+    degree: usize,
+    components: Vec<Expression>,
+}
+
+impl<const N: usize> Form<N> {
+    /// Form with the components `f(indices)`, called for every increasing tuple of indices
+    pub fn from_fn(degree: usize, mut f: impl FnMut(&[usize]) -> Expression) -> Self {
+        Form {
+            degree,
+            components: subsets(N, degree).iter().map(|i| f(i)).collect(),
+        }
+    }
+
+    pub fn zero(degree: usize) -> Self {
+        Self::from_fn(degree, |_| e!(c!()))
+    }
+
+    /// 0-form with the value `f`
+    pub fn scalar<T: Clone>(f: Expressable<T>) -> Self
+    where
+        Expression: From<Expressable<T>>,
+    {
+        Form {
+            degree: 0,
+            components: vec![f.into()],
+        }
+    }
+
+    /// 1-form `α_μ dx^μ`
+    pub fn one_form(a: &[Expression; N]) -> Self {
+        Self::from_fn(1, |i| a[i[0]].clone())
+    }
+
+    /// Coordinate basis 1-form `dx^i`
+    #[track_caller]
+    pub fn dx(i: usize) -> Self {
+        assert!(i < N, "Index {i} out of range for {N} dimensions");
+        Self::from_fn(1, |j| e!(if j[0] == i { 1.0 } else { 0.0 }))
+    }
+
+    /// Form with the components of the antisymmetric covariant tensor `t`
+    #[track_caller]
+    pub fn from_tensor(t: &Tensor<N>) -> Self {
+        assert!(
+            t.variance().iter().all(|&v| v == Variance::Down),
+            "Forms are built from covariant tensors"
+        );
+        Self::from_fn(t.rank(), |i| t[i].clone())
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Components with their increasing indices
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<usize>, &Expression)> {
+        subsets(N, self.degree).into_iter().zip(&self.components)
+    }
+
+    /// Component `α_μν..` for indices in any order
+    #[track_caller]
+    pub fn get(&self, index: &[usize]) -> Expression {
+        assert!(
+            index.len() == self.degree,
+            "Inadequate amount of indices, expected {} got {}",
+            self.degree,
+            index.len()
+        );
+        assert!(
+            index.iter().all(|&i| i < N),
+            "Index {index:?} out of range for {N} dimensions"
+        );
+        match sorted(index) {
+            Some((index, true)) => -self.components[rank(N, &index)].clone(),
+            Some((index, false)) => self.components[rank(N, &index)].clone(),
+            None => e!(c!()),
+        }
+    }
+
+    pub fn simplify(&self) -> Self {
+        self.map(|e| e.clone().simplify())
+    }
+
+    fn map(&self, f: impl FnMut(&Expression) -> Expression) -> Self {
+        Form {
+            degree: self.degree,
+            components: self.components.iter().map(f).collect(),
+        }
+    }
+
+    /// `α ∧ β`
+    pub fn wedge(&self, other: &Self) -> Self {
+        let (p, q) = (self.degree, other.degree);
+        // Every split of the result's indices into `p` for `α` and `q` for `β`
+        let splits = subsets(p + q, p)
+            .into_iter()
+            .map(|s| {
+                let rest = (0..p + q).filter(|k| !s.contains(k));
+                let order = s.iter().copied().chain(rest).collect::<Vec<_>>();
+                let (_, odd) = sorted(&order).unwrap();
+                (order, odd)
+            })
+            .collect::<Vec<_>>();
+        Self::from_fn(p + q, |i| {
+            let mut sum = e!(c!());
+            for (order, odd) in &splits {
+                let (a, b) = order.split_at(p);
+                let term = self.get(&a.iter().map(|&k| i[k]).collect::<Vec<_>>())
+                    * other.get(&b.iter().map(|&k| i[k]).collect::<Vec<_>>());
+                sum = match odd {
+                    true => sum - term,
+                    false => sum + term,
+                };
+            }
+            sum
+        })
+    }
+
+    /// Exterior derivative `dα` in the coordinates `x`
+    pub fn d(&self, x: [Var; N]) -> Self {
+        Self::from_fn(self.degree + 1, |i| {
+            let mut sum = e!(c!());
+            for k in 0..i.len() {
+                let mut rest = i.to_vec();
+                let j = rest.remove(k);
+                let term = self.get(&rest).diff(x[j]);
+                sum = match k % 2 {
+                    0 => sum + term,
+                    _ => sum - term,
+                };
+            }
+            sum
+        })
+    }
+
+    /// Interior product `ι_v α`, `v` contracted into the first slot
+    #[track_caller]
+    pub fn interior(&self, v: &[Expression; N]) -> Self {
+        assert!(self.degree > 0, "Interior product of a 0-form");
+        Self::from_fn(self.degree - 1, |i| {
+            let mut sum = e!(c!());
+            for (j, v) in v.iter().enumerate() {
+                let index = [&[j], i].concat();
+                sum = sum + v.clone() * self.get(&index);
+            }
+            sum
+        })
+    }
+
+    /// Hodge dual `⋆α` with respect to the metric `g`, `⋆1` being the volume form
+    /// `√|g| dx^0 ∧ .. ∧ dx^N-1`
+    #[track_caller]
+    pub fn hodge(&self, g: &SqMatrix<N>) -> Self {
+        let p = self.degree;
+        assert!(p <= N, "Degree {p} out of range for {N} dimensions");
+        let g_inv = g.inv();
+        let volume = g.det().abs().pow(0.5);
+        let perms = permutations(p);
+        // `α^I = Σ_K det(g^IK) α_K`, the minors of `g_inv` raising each index
+        let raised = |i: &[usize]| {
+            let mut sum = e!(c!());
+            for (k, a) in self.iter() {
+                let mut minor = e!(c!());
+                for (perm, odd) in &perms {
+                    let mut term = e!(1.0);
+                    for (r, &s) in perm.iter().enumerate() {
+                        term = term * g_inv[i[r]][k[s]].clone();
+                    }
+                    minor = match odd {
+                        true => minor - term,
+                        false => minor + term,
+                    };
+                }
+                sum = sum + minor * a.clone();
+            }
+            sum
+        };
+        // `(⋆α)_J = √|g| ε_IJ α^I` with `I` the complement of `J`
+        Self::from_fn(N - p, |j| {
+            let i = (0..N).filter(|k| !j.contains(k)).collect::<Vec<_>>();
+            let (_, odd) = sorted(&[&i[..], j].concat()).unwrap();
+            let dual = volume.clone() * raised(&i);
+            match odd {
+                true => -dual,
+                false => dual,
+            }
+        })
+    }
+
+    #[track_caller]
+    fn zip(&self, other: &Self, f: impl Fn(&Expression, &Expression) -> Expression) -> Self {
+        assert!(
+            self.degree == other.degree,
+            "Mismatched degrees {} and {}",
+            self.degree,
+            other.degree
+        );
+        Form {
+            degree: self.degree,
+            components: self
+                .components
+                .iter()
+                .zip(&other.components)
+                .map(|(a, b)| f(a, b))
+                .collect(),
+        }
+    }
+}
+
+/// Increasing `p`-tuples of `0..n`, in lexicographic order
+fn subsets(n: usize, p: usize) -> Vec<Vec<usize>> {
+    match p {
+        0 => vec![Vec::new()],
+        _ => (0..n)
+            .flat_map(|first| {
+                subsets(n, p - 1)
+                    .into_iter()
+                    .filter(move |rest| rest.first().is_none_or(|&r| r > first))
+                    .map(move |rest| [&[first], &rest[..]].concat())
+            })
+            .collect(),
+    }
+}
+
+/// Position of the increasing tuple `index` among the `subsets(n, index.len())`
+fn rank(n: usize, index: &[usize]) -> usize {
+    let p = index.len();
+    let mut rank = 0;
+    let mut start = 0;
+    for (k, &i) in index.iter().enumerate() {
+        for j in start..i {
+            rank += binomial(n - 1 - j, p - 1 - k);
+        }
+        start = i + 1;
+    }
+    rank
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    (0..k).fold(1, |b, i| b * (n - i) / (i + 1))
+}
+
+/// `index` sorted, with whether that took an odd permutation, or `None` for a repeated index
+fn sorted(index: &[usize]) -> Option<(Vec<usize>, bool)> {
+    let mut index = index.to_vec();
+    let mut odd = false;
+    for i in 0..index.len() {
+        for j in 0..index.len() - 1 - i {
+            if index[j] > index[j + 1] {
+                index.swap(j, j + 1);
+                odd = !odd;
+            }
+        }
+    }
+    match index.windows(2).any(|w| w[0] == w[1]) {
+        true => None,
+        false => Some((index, odd)),
+    }
+}
+
+impl<const N: usize> From<&Form<N>> for Tensor<N> {
+    fn from(form: &Form<N>) -> Self {
+        Tensor::from_fn(vec![Variance::Down; form.degree], |i| form.get(i))
+    }
+}
+
+impl<const N: usize> Add for Form<N> {
+    type Output = Form<N>;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip(&rhs, |a, b| a.clone() + b.clone())
+    }
+}
+
+impl<const N: usize> Sub for Form<N> {
+    type Output = Form<N>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip(&rhs, |a, b| a.clone() - b.clone())
+    }
+}
+
+impl<const N: usize> Neg for Form<N> {
+    type Output = Form<N>;
+    fn neg(self) -> Self::Output {
+        self.map(|a| -a.clone())
+    }
+}
+
+impl<const N: usize, T: Clone> Mul<Expressable<T>> for Form<N>
+where
+    Expression: From<Expressable<T>>,
+{
+    type Output = Form<N>;
+    fn mul(self, rhs: Expressable<T>) -> Self::Output {
+        scale(&self, rhs.into())
+    }
+}
+
+fn scale<const N: usize>(form: &Form<N>, k: Expression) -> Form<N> {
+    form.map(|a| a.clone() * k.clone())
+}
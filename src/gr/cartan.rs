@@ -0,0 +1,217 @@
+use std::{array, cell::OnceCell};
+
+use crate::{c, e, Expression, Form, SqMatrix, Var};
+
+use super::{Metric, RiemannCurvature};
+
+/// First structure equation, the torsion `T^a = dθ^a + ω^a_b ∧ θ^b` of the coframe `theta`
+/// with the connection 1-forms `omega[a][b] = ω^a_b`
+pub fn torsion<const N: usize>(
+    theta: &[Form<N>; N],
+    omega: &[[Form<N>; N]; N],
+    x: [Var; N],
+) -> [Form<N>; N] {
+    array::from_fn(|a| {
+        let mut t = theta[a].d(x);
+        for (omega, theta) in omega[a].iter().zip(theta) {
+            t = t + omega.wedge(theta);
+        }
+        t.simplify()
+    })
+}
+
+/// Second structure equation, the curvature 2-forms `Ω^a_b = dω^a_b + ω^a_c ∧ ω^c_b`
+pub fn curvature_forms<const N: usize>(
+    omega: &[[Form<N>; N]; N],
+    x: [Var; N],
+) -> [[Form<N>; N]; N] {
+    array::from_fn(|a| {
+        array::from_fn(|b| {
+            let mut o = omega[a][b].d(x);
+            for (omega_ac, omega_c) in omega[a].iter().zip(omega) {
+                o = o + omega_ac.wedge(&omega_c[b]);
+            }
+            o.simplify()
+        })
+    })
+}
+
+/// Coframe `θ^a = e^a_μ dx^μ` orthonormal with respect to the constant frame metric `eta`, so
+/// that `g_μν = η_ab e^a_μ e^b_ν`. The connection and curvature are found through Cartan's
+/// structure equations without any Christoffel symbols, and kept once computed.
+#[derive(Debug, Clone)]
+pub struct Tetrad<const N: usize> {
+    e: SqMatrix<N>,
+    eta: SqMatrix<N>,
+    x: [Var; N],
+    e_inv: OnceCell<SqMatrix<N>>,
+    omega: OnceCell<[[Form<N>; N]; N]>,
+    riemann: OnceCell<RiemannCurvature<N>>,
+}
+
+impl<const N: usize> Tetrad<N> {
+    /// Coframe with the rows `e[a] = e^a_μ`
+    pub fn new(e: SqMatrix<N>, eta: SqMatrix<N>, x: [Var; N]) -> Self {
+        Tetrad {
+            e: e.simplify(),
+            eta,
+            x,
+            e_inv: OnceCell::new(),
+            omega: OnceCell::new(),
+            riemann: OnceCell::new(),
+        }
+    }
+
+    /// Coframe `θ^a = √(s_a g_aa) dx^a` of the diagonal metric `g`, `signature` giving the signs
+    /// `s_a` of its entries
+    pub fn diagonal(g: &SqMatrix<N>, signature: [f64; N], x: [Var; N]) -> Self {
+        let mut e = SqMatrix::zeroes();
+        let mut eta = SqMatrix::zeroes();
+        for (a, &s) in signature.iter().enumerate() {
+            e[a][a] = (e!(s) * g[a][a].clone()).pow(0.5);
+            eta[a][a] = e!(s);
+        }
+        Self::new(e, eta, x)
+    }
+
+    /// `e^a_μ`
+    pub fn coframe(&self) -> &SqMatrix<N> {
+        &self.e
+    }
+
+    /// Frame vectors `e_a^μ`, as the columns
+    pub fn frame(&self) -> &SqMatrix<N> {
+        self.e_inv.get_or_init(|| self.e.inv().simplify())
+    }
+
+    pub fn eta(&self) -> &SqMatrix<N> {
+        &self.eta
+    }
+
+    /// Metric `g_μν = η_ab e^a_μ e^b_ν`
+    pub fn metric(&self) -> Metric<N> {
+        let g = array::from_fn(|m| {
+            array::from_fn(|n| {
+                let mut sum = e!(0.0);
+                for a in 0..N {
+                    for b in 0..N {
+                        sum = sum
+                            + self.eta[a][b].clone() * self.e[a][m].clone() * self.e[b][n].clone();
+                    }
+                }
+                sum
+            })
+        });
+        Metric::new(SqMatrix(g), self.x)
+    }
+
+    /// Coframe 1-forms `θ^a`
+    pub fn theta(&self) -> [Form<N>; N] {
+        array::from_fn(|a| Form::one_form(&self.e[a]))
+    }
+
+    /// Connection 1-forms `ω^a_b` of the torsion-free, metric-compatible connection, from
+    /// `ω_abc = (D_abc + D_bca - D_cab) / 2` where `dθ^a = D^a_bc θ^b ∧ θ^c / 2`
+    pub fn connection_forms(&self) -> &[[Form<N>; N]; N] {
+        self.omega.get_or_init(|| {
+            let frame = self.frame();
+            let d_theta = self.theta().map(|t| t.d(self.x).simplify());
+            // `D^a_bc = dθ^a(e_b, e_c)`, each stage simplified before the next one
+            let d_up = cube::<N>(|a, b, c| {
+                sum(d_theta[a].iter().flat_map(|(i, d)| {
+                    let (m, n) = (i[0], i[1]);
+                    [
+                        [d.clone(), frame[m][b].clone(), frame[n][c].clone()],
+                        [-d.clone(), frame[n][b].clone(), frame[m][c].clone()],
+                    ]
+                }))
+            });
+            let d = cube::<N>(|a, b, c| {
+                sum((0..N).map(|f| [self.eta[a][f].clone(), d_up[f][b][c].clone()]))
+            });
+            let omega = cube::<N>(|a, b, c| {
+                (d[a][b][c].clone() + d[b][c][a].clone() - d[c][a][b].clone()) * e!(0.5)
+            });
+            let eta_inv = self.eta.inv();
+            array::from_fn(|a| {
+                array::from_fn(|b| {
+                    let components = array::from_fn(|m| {
+                        sum((0..N)
+                            .flat_map(|f| (0..N).map(move |c| [f, c]))
+                            .map(|[f, c]| {
+                                [
+                                    eta_inv[a][f].clone(),
+                                    omega[f][b][c].clone(),
+                                    self.e[c][m].clone(),
+                                ]
+                            }))
+                    });
+                    Form::one_form(&components)
+                })
+            })
+        })
+    }
+
+    /// Curvature 2-forms `Ω^a_b`
+    pub fn curvature_forms(&self) -> [[Form<N>; N]; N] {
+        curvature_forms(self.connection_forms(), self.x)
+    }
+
+    /// Frame components `R_abcd`, from `Ω^a_b = R^a_bcd θ^c ∧ θ^d / 2`
+    pub fn riemann(&self) -> &RiemannCurvature<N> {
+        self.riemann.get_or_init(|| {
+            let omega = self.curvature_forms();
+            let frame = self.frame();
+            RiemannCurvature::from_fn(|[a, b, c, d]| {
+                sum((0..N).flat_map(|f| {
+                    let eta = self.eta[a][f].clone();
+                    omega[f][b].iter().flat_map(move |(i, o)| {
+                        let (m, n) = (i[0], i[1]);
+                        [
+                            [
+                                eta.clone(),
+                                o.clone(),
+                                frame[m][c].clone(),
+                                frame[n][d].clone(),
+                            ],
+                            [
+                                eta.clone(),
+                                -o.clone(),
+                                frame[n][c].clone(),
+                                frame[m][d].clone(),
+                            ],
+                        ]
+                    })
+                }))
+            })
+        })
+    }
+
+    /// Coordinate components `R_μνρσ = e^a_μ e^b_ν e^c_ρ e^d_σ R_abcd`
+    pub fn coordinate_riemann(&self) -> RiemannCurvature<N> {
+        self.riemann().raise(self.e.transpose())
+    }
+}
+
+/// `f(a, b, c)` for every index, simplified
+fn cube<const N: usize>(
+    f: impl Fn(usize, usize, usize) -> Expression,
+) -> [[[Expression; N]; N]; N] {
+    array::from_fn(|a| array::from_fn(|b| array::from_fn(|c| f(a, b, c).simplify())))
+}
+
+/// Sum of the products of `terms`, skipping those with a vanishing constant factor
+fn sum<const K: usize>(terms: impl Iterator<Item = [Expression; K]>) -> Expression {
+    let mut sum = e!(0.0);
+    for factors in terms {
+        if factors.iter().any(|f| f.as_const() == Some(c!())) {
+            continue;
+        }
+        let mut term = e!(1.0);
+        for f in factors {
+            term = term * f;
+        }
+        sum = sum + term;
+    }
+    sum.simplify()
+}
@@ -0,0 +1,237 @@
+use std::ops::Index;
+
+use crate::{c, Expression, Scalar, SqMatrix, Tensor, Variance};
+
+/// Christoffel symbols `Γ^r_mn`, indexed by `(r, m, n)`. Only `m <= n` is stored, the
+/// symbols being symmetric in their lower indices.
+///
+/// Not a `Tensor`, which stores all `N^rank` components and transforms as a tensor, neither
+/// of which holds here. `Tensor::from` gives the full `Γ^r_mn` for the index operations.
+#[derive(Debug, Clone)]
+pub struct Christoffel<const N: usize, T = Expression> {
+    components: Vec<T>,
+}
+
+impl<const N: usize, T> Christoffel<N, T> {
+    /// Symbols given by `f(r, m, n)`, called once for every `m <= n`
+    pub fn from_fn(mut f: impl FnMut(usize, usize, usize) -> T) -> Self {
+        let mut components = Vec::with_capacity(N * N * (N + 1) / 2);
+        for r in 0..N {
+            for m in 0..N {
+                for n in m..N {
+                    components.push(f(r, m, n));
+                }
+            }
+        }
+        Christoffel { components }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Christoffel<N, U> {
+        Christoffel {
+            components: self.components.into_iter().map(f).collect(),
+        }
+    }
+
+    /// Stored symbols with their indices, `m <= n`
+    pub fn iter(&self) -> impl Iterator<Item = ([usize; 3], &T)> {
+        (0..N)
+            .flat_map(|r| (0..N).flat_map(move |m| (m..N).map(move |n| [r, m, n])))
+            .zip(&self.components)
+    }
+}
+
+impl<const N: usize, T> Index<(usize, usize, usize)> for Christoffel<N, T> {
+    type Output = T;
+
+    #[track_caller]
+    fn index(&self, (r, m, n): (usize, usize, usize)) -> &T {
+        assert!(
+            r < N && m < N && n < N,
+            "Index ({r}, {m}, {n}) out of range for {N} dimensions"
+        );
+        let (m, n) = (m.min(n), m.max(n));
+        &self.components[r * N * (N + 1) / 2 + m * (2 * N + 1 - m) / 2 + n - m]
+    }
+}
+
+/// Tensor `R_abcd` with the algebraic symmetries of the covariant Riemann tensor:
+/// antisymmetric within each index pair, symmetric under exchange of the pairs and with
+/// `R_abcd + R_acdb + R_adbc = 0`. Only the `N²(N² - 1) / 12` independent components are
+/// stored, `R_adbc` being dropped for every `a < b < c < d`.
+///
+/// Kept apart from `Tensor` for this symmetry-reduced storage, which operations such as
+/// `Tensor::permute` would not preserve. `Tensor::from` gives all `N⁴` components.
+#[derive(Debug, Clone)]
+pub struct RiemannCurvature<const N: usize, T = Expression> {
+    components: Vec<T>,
+}
+
+impl<const N: usize, T> RiemannCurvature<N, T> {
+    /// Indices `[a, b, c, d]` of the stored components, `a < b`, `c < d` and `(a, b) <= (c, d)`
+    pub fn independent() -> impl Iterator<Item = [usize; 4]> {
+        let pairs = pairs::<N>();
+        let mut independent = Vec::new();
+        for (p, &[a, b]) in pairs.iter().enumerate() {
+            for &[c, d] in &pairs[p..] {
+                if !dependent([a, b, c, d]) {
+                    independent.push([a, b, c, d]);
+                }
+            }
+        }
+        independent.into_iter()
+    }
+
+    /// Tensor given by `f([a, b, c, d])`, called once for every independent component
+    pub fn from_fn(f: impl FnMut([usize; 4]) -> T) -> Self {
+        RiemannCurvature {
+            components: Self::independent().map(f).collect(),
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> RiemannCurvature<N, U> {
+        RiemannCurvature {
+            components: self.components.into_iter().map(f).collect(),
+        }
+    }
+
+    /// Independent components with their indices
+    pub fn iter(&self) -> impl Iterator<Item = ([usize; 4], &T)> {
+        Self::independent().zip(&self.components)
+    }
+}
+
+impl<const N: usize, T: Scalar> RiemannCurvature<N, T> {
+    /// Component `R_abcd` for any indices, recovered from the stored ones
+    #[track_caller]
+    pub fn get(&self, [a, b, c, d]: [usize; 4]) -> T {
+        assert!(
+            a < N && b < N && c < N && d < N,
+            "Index [{a}, {b}, {c}, {d}] out of range for {N} dimensions"
+        );
+        if a == b || c == d {
+            return T::constant(c!());
+        }
+        let (mut i, mut negate) = ([a, b, c, d], false);
+        if a > b {
+            i.swap(0, 1);
+            negate = !negate;
+        }
+        if c > d {
+            i.swap(2, 3);
+            negate = !negate;
+        }
+        if (i[0], i[1]) > (i[2], i[3]) {
+            i = [i[2], i[3], i[0], i[1]];
+        }
+        let value = match dependent(i) {
+            // `R_adbc = R_acbd - R_abcd`
+            true => {
+                let [a, d, b, c] = i;
+                self.get([a, c, b, d]) - self.get([a, b, c, d])
+            }
+            false => self.components[slot::<N>(i)].clone(),
+        };
+        match negate {
+            true => -value,
+            false => value,
+        }
+    }
+
+    /// Same tensor with every index moved by `m`, `R^abcd` for `m = g_inv`. Works on index
+    /// pairs, where `m` acts as the matrix `m^ae m^bf - m^af m^be`.
+    pub fn raise(&self, m: SqMatrix<N, T>) -> Self {
+        let pairs = pairs::<N>();
+        let bivector = |[a, b]: [usize; 2], [e, f]: [usize; 2]| {
+            m[a][e].clone() * m[b][f].clone() - m[a][f].clone() * m[b][e].clone()
+        };
+        let full = pairs
+            .iter()
+            .map(|p| {
+                pairs
+                    .iter()
+                    .map(|q| self.get([p[0], p[1], q[0], q[1]]))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        // Left factor first, `h^P_S = M^PR R_RS`
+        let h = pairs
+            .iter()
+            .map(|&p| {
+                (0..pairs.len())
+                    .map(|s| {
+                        let mut sum = T::constant(c!());
+                        for (r, &pr) in pairs.iter().enumerate() {
+                            sum = sum + bivector(p, pr) * full[r][s].clone();
+                        }
+                        sum.simplify()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let index = |a: usize, b: usize| pairs.iter().position(|&p| p == [a, b]).unwrap();
+        Self::from_fn(|[a, b, c, d]| {
+            let mut sum = T::constant(c!());
+            for (s, &ps) in pairs.iter().enumerate() {
+                sum = sum + h[index(a, b)][s].clone() * bivector([c, d], ps);
+            }
+            sum.simplify()
+        })
+    }
+}
+
+/// Index pairs `[a, b]` with `a < b`, in lexicographic order
+fn pairs<const N: usize>() -> Vec<[usize; 2]> {
+    (0..N)
+        .flat_map(|a| (a + 1..N).map(move |b| [a, b]))
+        .collect()
+}
+
+/// Position of `[a, b, c, d]` among the independent components, in the order of
+/// `RiemannCurvature::independent`
+fn slot<const N: usize>([a, b, c, d]: [usize; 4]) -> usize {
+    let pairs = N * (N - 1) / 2;
+    let rank = |a: usize, b: usize| a * (2 * N - a - 1) / 2 + b - a - 1;
+    let (p, q) = (rank(a, b), rank(c, d));
+    // Index pairs up to `(a, b), (c, d)`, less the dependent ones among them. Those are
+    // `(a', d'), (b', c')` with `a' < b' < c' < d'`, counted for the first pairs before
+    // `(a, b)`, then for `(a, b)` itself with the second pairs before `(c, d)`.
+    let before = p * pairs - p * p.saturating_sub(1) / 2 + q - p;
+    let earlier = binomial(N, 4) - binomial(N - a, 4) + binomial(b - a - 1, 3);
+    let e = c.min(b);
+    let mut within = match e > a + 1 {
+        true => binomial(b - a - 1, 2) - binomial(b - e, 2),
+        false => 0,
+    };
+    if a < c && c < b {
+        within += d.min(b).saturating_sub(c + 1);
+    }
+    before - earlier - within
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    match k > n {
+        true => 0,
+        false => (0..k).fold(1, |b, i| b * (n - i) / (i + 1)),
+    }
+}
+
+/// Whether `[a, d, b, c]` with `a < b < c < d`, the component fixed by the others through the
+/// first Bianchi identity
+fn dependent([a, d, b, c]: [usize; 4]) -> bool {
+    a < b && b < c && c < d
+}
+
+impl<const N: usize> From<&Christoffel<N>> for Tensor<N> {
+    fn from(gamma: &Christoffel<N>) -> Self {
+        let variance = vec![Variance::Up, Variance::Down, Variance::Down];
+        Tensor::from_fn(variance, |i| gamma[(i[0], i[1], i[2])].clone())
+    }
+}
+
+impl<const N: usize> From<&RiemannCurvature<N>> for Tensor<N> {
+    fn from(riemann: &RiemannCurvature<N>) -> Self {
+        Tensor::from_fn(vec![Variance::Down; 4], |i| {
+            riemann.get([i[0], i[1], i[2], i[3]])
+        })
+    }
+}
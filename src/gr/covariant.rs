@@ -0,0 +1,51 @@
+use crate::{SqMatrix, Tensor, Var, Variance};
+
+use super::Christoffel;
+
+/// `∇_c T`, the derivative index `c` appended last. Every upper index `a` gains `Γ^a_ce T^e`
+/// and every lower index `b` loses `Γ^e_cb T_e`.
+pub fn covariant_derivative<const N: usize>(
+    t: &Tensor<N>,
+    gamma: &Christoffel<N>,
+    x: [Var; N],
+) -> Tensor<N> {
+    let mut variance = t.variance().to_vec();
+    variance.push(Variance::Down);
+    Tensor::from_fn(variance, |index| {
+        let (i, c) = (&index[..t.rank()], index[t.rank()]);
+        let mut sum = t[i].clone().diff(x[c]);
+        for (k, v) in t.variance().iter().enumerate() {
+            let mut j = i.to_vec();
+            for e in 0..N {
+                j[k] = e;
+                sum = match v {
+                    Variance::Up => sum + gamma[(i[k], c, e)].clone() * t[&j[..]].clone(),
+                    Variance::Down => sum - gamma[(e, c, i[k])].clone() * t[&j[..]].clone(),
+                };
+            }
+        }
+        sum
+    })
+}
+
+/// `∇_a T^..a..`, contracting the derivative with index `index`, which is raised first if
+/// it is lower
+#[track_caller]
+pub fn divergence<const N: usize>(
+    t: &Tensor<N>,
+    index: usize,
+    gamma: &Christoffel<N>,
+    g_inv: &SqMatrix<N>,
+    x: [Var; N],
+) -> Tensor<N> {
+    assert!(
+        index < t.rank(),
+        "Index {index} out of range for a rank {} tensor",
+        t.rank()
+    );
+    let t = match t.variance()[index] {
+        Variance::Up => t.clone(),
+        Variance::Down => t.raise(index, g_inv).simplify(),
+    };
+    covariant_derivative(&t, gamma, x).contract(index, t.rank())
+}
@@ -0,0 +1,245 @@
+use crate::{c, einsum, Expression, Scalar, SqMatrix, System, Tensor, Var, Variance};
+
+use super::{Christoffel, RiemannCurvature};
+
+/// Simplified components of `curvature` as rows, like `System::strmat`
+pub fn strcurvature<const N: usize>(curvature: RicciCurvature<N>, sys: &System) -> String {
+    let mut f = String::new();
+    for x in curvature.into_iter() {
+        f += "[";
+        for x in x.into_iter() {
+            f.push_str(&format!("{:5}", sys.str(x.simplify())));
+        }
+        f += "]\n";
+    }
+    f
+}
+
+/// Christoffel symbols of the first kind `Γ_smn`, the lowered index first
+pub fn christoffel_first<const N: usize, T: Scalar>(
+    g: SqMatrix<N, T>,
+    x: [Var; N],
+) -> Christoffel<N, T> {
+    Christoffel::from_fn(|s, m, n| {
+        (g[m][s].diff(x[n]) + g[n][s].diff(x[m]) - g[m][n].diff(x[s])) * T::constant(c!(0.5))
+    })
+}
+
+/// Christoffel symbols of the second kind `Γ^r_mn`
+pub fn christoffel<const N: usize, T: Scalar>(
+    g: SqMatrix<N, T>,
+    g_inv: SqMatrix<N, T>,
+    x: [Var; N],
+) -> Christoffel<N, T> {
+    let first = christoffel_first(g, x);
+    Christoffel::from_fn(|r, m, n| {
+        let mut sum = T::constant(c!());
+        for s in 0..N {
+            sum = sum + g_inv[r][s].clone() * first[(s, m, n)].clone();
+        }
+        sum
+    })
+}
+
+/// Covariant Riemann tensor, each independent component computed once from
+/// `R_abcd = (g_ad,bc + g_bc,ad - g_ac,bd - g_bd,ac) / 2 + Γ_fbc Γ^f_ad - Γ_fbd Γ^f_ac`
+pub fn riemann_tensor<const N: usize, T: Scalar>(
+    g: SqMatrix<N, T>,
+    gamma: &Christoffel<N, T>,
+    x: [Var; N],
+) -> RiemannCurvature<N, T> {
+    let first = christoffel_first(g.clone(), x);
+    let dd = |m: [usize; 2], a: usize, b: usize| g[m[0]][m[1]].diff(x[a]).diff(x[b]);
+    RiemannCurvature::from_fn(|[a, b, c, d]| {
+        let mut sum = (dd([a, d], b, c) + dd([b, c], a, d) - dd([a, c], b, d) - dd([b, d], a, c))
+            * T::constant(c!(0.5));
+        for f in 0..N {
+            sum = sum + first[(f, b, c)].clone() * gamma[(f, a, d)].clone()
+                - first[(f, b, d)].clone() * gamma[(f, a, c)].clone();
+        }
+        sum
+    })
+}
+
+pub type RicciCurvature<const N: usize, T = Expression> = [[T; N]; N];
+/// `R_bd = g^ac R_abcd`
+pub fn ricci_tensor<const N: usize, T: Scalar>(
+    riemann_tensor: &RiemannCurvature<N, T>,
+    g_inv: SqMatrix<N, T>,
+) -> RicciCurvature<N, T> {
+    let mut r: RicciCurvature<N, T> =
+        std::array::from_fn(|_| std::array::from_fn(|_| T::constant(c!())));
+    for (b, d) in (0..N).flat_map(|b| (b..N).map(move |d| (b, d))) {
+        let mut sum = T::constant(c!());
+        for a in 0..N {
+            for c in 0..N {
+                sum = sum + g_inv[a][c].clone() * riemann_tensor.get([a, b, c, d]);
+            }
+        }
+        r[b][d] = sum.clone();
+        r[d][b] = sum;
+    }
+    r
+}
+
+/// `R = g^mn R_mn`
+pub fn scalar_curvature<const N: usize, T: Scalar>(
+    ricci_tensor: &RicciCurvature<N, T>,
+    g_inv: SqMatrix<N, T>,
+) -> T {
+    let mut r = T::constant(c!());
+    for m in 0..N {
+        for n in 0..N {
+            r = r + g_inv[m][n].clone() * ricci_tensor[m][n].clone();
+        }
+    }
+    r
+}
+
+/// `R = g^mn R_mn` contracted with `einsum`, for expressions
+pub(crate) fn scalar_curvature_einsum<const N: usize>(
+    ricci_tensor: &RicciCurvature<N>,
+    g_inv: &SqMatrix<N>,
+) -> Expression {
+    let g_inv = Tensor::matrix(&g_inv.0, [Variance::Up, Variance::Up]);
+    let ricci = Tensor::matrix(ricci_tensor, [Variance::Down, Variance::Down]);
+    einsum!("mn,mn->", g_inv, ricci)[[]].clone()
+}
+
+pub type EinsteinTensor<const N: usize, T = Expression> = [[T; N]; N];
+/// `G_mn = R_mn - R g_mn / 2`
+pub fn einstein_tensor<const N: usize, T: Scalar>(
+    ricci_tensor: &RicciCurvature<N, T>,
+    scalar_curvature: T,
+    g: SqMatrix<N, T>,
+) -> EinsteinTensor<N, T> {
+    std::array::from_fn(|m| {
+        std::array::from_fn(|n| {
+            ricci_tensor[m][n].clone()
+                - scalar_curvature.clone() * g[m][n].clone() * T::constant(c!(0.5))
+        })
+    })
+}
+
+/// `R_mn - R g_mn / N`
+pub fn trace_free_ricci<const N: usize, T: Scalar>(
+    ricci_tensor: &RicciCurvature<N, T>,
+    scalar_curvature: T,
+    g: SqMatrix<N, T>,
+) -> RicciCurvature<N, T> {
+    std::array::from_fn(|m| {
+        std::array::from_fn(|n| {
+            ricci_tensor[m][n].clone()
+                - scalar_curvature.clone() * g[m][n].clone() * T::constant(c!(1.0 / N as f64))
+        })
+    })
+}
+
+/// `t` with its `index`-th slot contracted against the first index of `m`, which raises it
+/// for `m = g_inv` and lowers it for `m = g`
+#[track_caller]
+pub fn contract_index<const N: usize, T: Scalar>(
+    t: &[[T; N]; N],
+    m: SqMatrix<N, T>,
+    index: usize,
+) -> [[T; N]; N] {
+    assert!(index < 2, "Index {index} out of range for a rank 2 tensor");
+    std::array::from_fn(|i| {
+        std::array::from_fn(|j| {
+            let mut sum = T::constant(c!());
+            for k in 0..N {
+                sum = sum
+                    + match index {
+                        0 => m[i][k].clone() * t[k][j].clone(),
+                        _ => m[j][k].clone() * t[i][k].clone(),
+                    };
+            }
+            sum
+        })
+    })
+}
+
+/// `T^mn` from `T_mn`
+pub fn raise_indices<const N: usize, T: Scalar>(
+    t: &[[T; N]; N],
+    g_inv: SqMatrix<N, T>,
+) -> [[T; N]; N] {
+    contract_index(&contract_index(t, g_inv.clone(), 0), g_inv, 1)
+}
+
+/// `T_mn` from `T^mn`
+pub fn lower_indices<const N: usize, T: Scalar>(t: &[[T; N]; N], g: SqMatrix<N, T>) -> [[T; N]; N] {
+    raise_indices(t, g)
+}
+
+/// Weyl conformal tensor `C_abcd` from the covariant Riemann tensor, for `N >= 3`
+#[track_caller]
+pub fn weyl_tensor<const N: usize, T: Scalar>(
+    riemann_tensor: &RiemannCurvature<N, T>,
+    ricci_tensor: &RicciCurvature<N, T>,
+    scalar_curvature: T,
+    g: SqMatrix<N, T>,
+) -> RiemannCurvature<N, T> {
+    assert!(N >= 3, "Weyl tensor needs at least 3 dimensions, got {N}");
+    let n = N as f64;
+    let a = T::constant(c!(1.0 / (n - 2.0)));
+    let b = scalar_curvature * T::constant(c!(1.0 / ((n - 1.0) * (n - 2.0))));
+    let (g, r) = (&g, ricci_tensor);
+    RiemannCurvature::from_fn(|[i, j, k, l]| {
+        let ricci = g[i][k].clone() * r[j][l].clone()
+            - g[i][l].clone() * r[j][k].clone()
+            - g[j][k].clone() * r[i][l].clone()
+            + g[j][l].clone() * r[i][k].clone();
+        let metric = g[i][k].clone() * g[j][l].clone() - g[i][l].clone() * g[j][k].clone();
+        riemann_tensor.get([i, j, k, l]) - a.clone() * ricci + b.clone() * metric
+    })
+}
+
+/// `T_abcd T^abcd`, the Kretschmann scalar for the Riemann tensor and the square of the Weyl
+/// tensor for the Weyl tensor
+pub fn square_rank4<const N: usize, T: Scalar>(
+    t: &RiemannCurvature<N, T>,
+    g_inv: SqMatrix<N, T>,
+) -> T {
+    // Four orderings of every pair of index pairs `a < b`, `c < d`
+    let up = t.raise(g_inv);
+    let mut sum = T::constant(c!());
+    for a in 0..N {
+        for b in a + 1..N {
+            for c in 0..N {
+                for d in c + 1..N {
+                    sum = sum + t.get([a, b, c, d]) * up.get([a, b, c, d]);
+                }
+            }
+        }
+    }
+    sum * T::constant(c!(4.0))
+}
+
+/// `R_mn R^mn`
+pub fn ricci_squared<const N: usize, T: Scalar>(
+    ricci_tensor: &RicciCurvature<N, T>,
+    g_inv: SqMatrix<N, T>,
+) -> T {
+    let up = raise_indices(ricci_tensor, g_inv);
+    let mut sum = T::constant(c!());
+    for (r, up) in ricci_tensor.iter().flatten().zip(up.iter().flatten()) {
+        sum = sum + r.clone() * up.clone();
+    }
+    sum
+}
+
+/// `C_ijkl C^ijkl` from the other invariants, `K - 4 R_mn R^mn / (N - 2) + 2 R² / ((N - 1)(N - 2))`
+#[track_caller]
+pub fn weyl_squared<const N: usize, T: Scalar>(
+    kretschmann: T,
+    ricci_squared: T,
+    scalar_curvature: T,
+) -> T {
+    assert!(N >= 3, "Weyl tensor needs at least 3 dimensions, got {N}");
+    let n = N as f64;
+    kretschmann - ricci_squared * T::constant(c!(4.0 / (n - 2.0)))
+        + scalar_curvature.clone()
+            * scalar_curvature
+            * T::constant(c!(2.0 / ((n - 1.0) * (n - 2.0))))
+}
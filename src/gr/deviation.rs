@@ -0,0 +1,120 @@
+use std::array;
+
+use crate::{c, e, Expression, SqMatrix, Var};
+
+use super::{Christoffel, RiemannCurvature};
+
+/// `K_μρ = R_μνρσ u^ν u^σ`
+fn electric<const N: usize>(riemann: &RiemannCurvature<N>, u: &[Expression; N]) -> SqMatrix<N> {
+    SqMatrix(array::from_fn(|m| {
+        array::from_fn(|r| {
+            let mut sum = e!(c!());
+            for n in 0..N {
+                for s in 0..N {
+                    sum = sum + riemann.get([m, n, r, s]) * u[n].clone() * u[s].clone();
+                }
+            }
+            sum.simplify()
+        })
+    }))
+}
+
+/// `g(a, b)`
+fn dot<const N: usize>(g: &SqMatrix<N>, a: &[Expression; N], b: &[Expression; N]) -> Expression {
+    let mut sum = e!(c!());
+    for m in 0..N {
+        for n in 0..N {
+            sum = sum + g[m][n].clone() * a[m].clone() * b[n].clone();
+        }
+    }
+    sum
+}
+
+/// Orthonormal frame `e_a^μ` of the observer with the timelike velocity `u`, as the rows.
+/// `e_0` is `u` normalised and the others follow from the coordinate directions by
+/// Gram–Schmidt.
+pub fn observer_frame<const N: usize>(g: &SqMatrix<N>, u: &[Expression; N]) -> SqMatrix<N> {
+    let mut frame: [[Expression; N]; N] = array::from_fn(|_| array::from_fn(|_| e!(c!())));
+    // `η_aa`, the velocity being timelike
+    let eta = |a: usize| if a == 0 { -1.0 } else { 1.0 };
+    for a in 0..N {
+        let mut v: [Expression; N] = match a {
+            0 => u.clone(),
+            _ => array::from_fn(|m| e!(if m == a { 1.0 } else { 0.0 })),
+        };
+        for (b, e_b) in frame.iter().enumerate().take(a) {
+            let projection = (dot(g, &v, e_b) * e!(eta(b))).simplify();
+            // Orthogonal already, as for every direction of a diagonal metric
+            if projection.is_zero() {
+                continue;
+            }
+            v = array::from_fn(|m| v[m].clone() - projection.clone() * e_b[m].clone());
+        }
+        let length = (dot(g, &v, &v) * e!(eta(a))).simplify().pow(0.5);
+        frame[a] = v.map(|v| (v / length.clone()).simplify());
+    }
+    SqMatrix(frame)
+}
+
+/// Tidal tensor `E_ab = R_a0b0` in `frame`, whose first row is the observer's velocity. Its
+/// components along `a = 0` or `b = 0` vanish, leaving the spatial `E_ij`.
+pub fn tidal_tensor<const N: usize>(
+    riemann: &RiemannCurvature<N>,
+    frame: &SqMatrix<N>,
+) -> SqMatrix<N> {
+    let k = electric(riemann, &frame[0]);
+    SqMatrix(array::from_fn(|a| {
+        array::from_fn(|b| match a == 0 || b == 0 {
+            true => e!(c!()),
+            false => dot(&k, &frame[a], &frame[b]).simplify(),
+        })
+    }))
+}
+
+/// Right hand sides of the geodesic deviation equations `D²ξ^μ/dτ² = -R^μ_νρσ u^ν ξ^ρ u^σ`,
+/// with `xi` standing for the deviation `ξ`
+pub fn deviation_equations<const N: usize>(
+    riemann: &RiemannCurvature<N>,
+    g_inv: &SqMatrix<N>,
+    u: &[Expression; N],
+    xi: [Var; N],
+) -> [Expression; N] {
+    let k = electric(riemann, u);
+    array::from_fn(|m| {
+        let mut sum = e!(c!());
+        for a in 0..N {
+            for (r, &xi) in xi.iter().enumerate() {
+                sum = sum + g_inv[m][a].clone() * k[a][r].clone() * e!(xi);
+            }
+        }
+        (-sum).simplify()
+    })
+}
+
+/// Right hand sides of the Jacobi equations in coordinates,
+/// `ξ̈^μ = -∂_γ Γ^μ_αβ ξ^γ ẋ^α ẋ^β - 2 Γ^μ_αβ ẋ^α ξ̇^β`, the geodesic equations linearised
+/// about a geodesic. `v`, `xi` and `w` stand for `ẋ`, `ξ` and `ξ̇`.
+pub fn jacobi_equations<const N: usize>(
+    gamma: &Christoffel<N>,
+    x: [Var; N],
+    v: [Var; N],
+    xi: [Var; N],
+    w: [Var; N],
+) -> [Expression; N] {
+    array::from_fn(|mu| {
+        let mut sum = e!(c!());
+        for a in 0..N {
+            for b in 0..N {
+                let gamma = &gamma[(mu, a, b)];
+                let mut shift = e!(c!());
+                for (&x, &xi) in x.iter().zip(&xi) {
+                    shift = shift + gamma.clone().diff(x) * e!(xi);
+                }
+                sum = sum
+                    + shift * e!(v[a]) * e!(v[b])
+                    + e!(2.0) * gamma.clone() * e!(v[a]) * e!(w[b]);
+            }
+        }
+        (-sum).simplify()
+    })
+}
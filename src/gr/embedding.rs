@@ -0,0 +1,163 @@
+use std::{array, cell::OnceCell};
+
+use crate::{e, Expression, SqMatrix, Var};
+
+use super::{transform::substitute, Metric};
+
+/// Surface `x^μ = X^μ(u)` of `K` parameters `u` embedded in the space of `ambient`. The
+/// hypersurface quantities need `K = N - 1`.
+#[derive(Debug, Clone)]
+pub struct Embedding<const K: usize, const N: usize> {
+    ambient: Metric<N>,
+    map: [Expression; N],
+    u: [Var; K],
+    /// `∂_a X^μ`
+    tangents: [[Expression; N]; K],
+    induced: OnceCell<SqMatrix<K>>,
+    normal: OnceCell<[Expression; N]>,
+    second: OnceCell<SqMatrix<K>>,
+}
+
+impl<const K: usize, const N: usize> Embedding<K, N> {
+    /// Embedding given by `map[μ] = X^μ(u)`
+    pub fn new(ambient: &Metric<N>, map: [Expression; N], u: [Var; K]) -> Self {
+        let tangents = u.map(|u| array::from_fn(|m| map[m].clone().diff(u).simplify()));
+        Embedding {
+            ambient: ambient.clone(),
+            map,
+            u,
+            tangents,
+            induced: OnceCell::new(),
+            normal: OnceCell::new(),
+            second: OnceCell::new(),
+        }
+    }
+
+    pub fn params(&self) -> [Var; K] {
+        self.u
+    }
+
+    /// `∂_a X^μ` for each parameter `a`
+    pub fn tangents(&self) -> &[[Expression; N]; K] {
+        &self.tangents
+    }
+
+    /// `ex` evaluated on the surface, in terms of the parameters
+    pub fn on_surface(&self, ex: Expression) -> Expression {
+        substitute(ex, &self.ambient.coords(), &self.map)
+    }
+
+    /// First fundamental form `h_ab = g_μν ∂_a X^μ ∂_b X^ν`
+    pub fn induced_metric(&self) -> &SqMatrix<K> {
+        self.induced.get_or_init(|| {
+            let g = self
+                .ambient
+                .g()
+                .0
+                .clone()
+                .map(|r| r.map(|e| self.on_surface(e)));
+            let t = &self.tangents;
+            SqMatrix(array::from_fn(|a| {
+                array::from_fn(|b| {
+                    let mut sum = e!(0.0);
+                    for m in 0..N {
+                        for n in 0..N {
+                            sum = sum + g[m][n].clone() * t[a][m].clone() * t[b][n].clone();
+                        }
+                    }
+                    sum.simplify()
+                })
+            }))
+        })
+    }
+
+    /// Induced metric as a metric of its own, for the intrinsic curvature
+    pub fn intrinsic(&self) -> Metric<K> {
+        Metric::new(self.induced_metric().clone(), self.u)
+    }
+
+    /// Unit normal covector `n_μ` of a hypersurface, along `ε_μν..ρ ∂_1 X^ν .. ∂_K X^ρ`
+    #[track_caller]
+    pub fn normal(&self) -> &[Expression; N] {
+        assert!(
+            K + 1 == N,
+            "Inadequate amount of parameters for a hypersurface, expected {} got {}",
+            N - 1,
+            K
+        );
+        self.normal.get_or_init(|| {
+            // Cofactors along a first row of unit vectors
+            let n: [Expression; N] = array::from_fn(|m| {
+                let mut rows = SqMatrix::zeroes();
+                rows[0][m] = e!(1.0);
+                for (a, t) in self.tangents.iter().enumerate() {
+                    rows[a + 1] = t.clone();
+                }
+                rows.det().simplify()
+            });
+            let g_inv = self.ambient.g_inv();
+            let mut norm = e!(0.0);
+            for m in 0..N {
+                for k in 0..N {
+                    let g_inv = self.on_surface(g_inv[m][k].clone());
+                    norm = norm + g_inv * n[m].clone() * n[k].clone();
+                }
+            }
+            let norm = norm.abs().pow(0.5);
+            n.map(|n| (n / norm.clone()).simplify())
+        })
+    }
+
+    /// Second fundamental form `K_ab = ∂_a X^μ ∂_b X^ν ∇_μ n_ν`, that is
+    /// `-n_μ (∂_a ∂_b X^μ + Γ^μ_νρ ∂_a X^ν ∂_b X^ρ)`, of a hypersurface. Positive for a sphere
+    /// with its outward normal.
+    #[track_caller]
+    pub fn second_fundamental_form(&self) -> &SqMatrix<K> {
+        let n = self.normal();
+        self.second.get_or_init(|| {
+            let gamma = self.ambient.christoffel();
+            let gamma = |m, a, b| self.on_surface(gamma[(m, a, b)].clone());
+            let t = &self.tangents;
+            let mut k: SqMatrix<K> = SqMatrix::zeroes();
+            for a in 0..K {
+                for b in a..K {
+                    let mut sum = e!(0.0);
+                    for m in 0..N {
+                        let mut acc = t[a][m].clone().diff(self.u[b]);
+                        for v in 0..N {
+                            for r in 0..N {
+                                acc = acc + gamma(m, v, r) * t[a][v].clone() * t[b][r].clone();
+                            }
+                        }
+                        sum = sum - n[m].clone() * acc;
+                    }
+                    k[a][b] = sum.simplify();
+                    k[b][a] = k[a][b].clone();
+                }
+            }
+            k
+        })
+    }
+
+    /// Mean curvature `h^ab K_ab / K` of a hypersurface, the mean of the principal curvatures
+    #[track_caller]
+    pub fn mean_curvature(&self) -> Expression {
+        let k = self.second_fundamental_form();
+        let h_inv = self.induced_metric().inv();
+        let mut sum = e!(0.0);
+        for a in 0..K {
+            for b in 0..K {
+                sum = sum + h_inv[a][b].clone() * k[a][b].clone();
+            }
+        }
+        (sum / e!(K as f64)).simplify()
+    }
+
+    /// Gaussian curvature `det K_ab / det h_ab` of a hypersurface, the product of the
+    /// principal curvatures
+    #[track_caller]
+    pub fn gaussian_curvature(&self) -> Expression {
+        let k = self.second_fundamental_form();
+        (k.det() / self.induced_metric().det()).simplify()
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{c, e, Expression, Method, OdeSystem, SqMatrix, System, Var};
+
+use super::{jacobi_equations, Christoffel};
+
+/// Right hand sides of the geodesic equations `ẍ^μ = -Γ^μ_αβ ẋ^α ẋ^β`, with `v` standing
+/// for the velocities `ẋ`
+pub fn geodesic_equations<const N: usize>(gamma: &Christoffel<N>, v: [Var; N]) -> [Expression; N] {
+    std::array::from_fn(|mu| {
+        let mut sum = e!(c!());
+        for a in 0..N {
+            for b in 0..N {
+                sum = sum + gamma[(mu, a, b)].clone() * e!(v[a]) * e!(v[b]);
+            }
+        }
+        (-sum).simplify()
+    })
+}
+
+/// Geodesic through `x0` with velocity `v0`, integrated in the affine parameter `tau` from
+/// `tau0` to `tau1`. `v` are the variables standing for the velocities in the equations.
+#[allow(clippy::too_many_arguments)]
+pub fn geodesic<const N: usize>(
+    g: &SqMatrix<N>,
+    gamma: &Christoffel<N>,
+    tau: Var,
+    x: [Var; N],
+    v: [Var; N],
+    method: Method,
+    span: (f64, f64),
+    x0: [f64; N],
+    v0: [f64; N],
+) -> Result<GeodesicPath<N>, String> {
+    let extra = (&[][..], Vec::new(), &[][..]);
+    let (path, _, _) = integrate(g, gamma, tau, [x, v], [x0, v0], extra, &[], method, span)?;
+    Ok(path)
+}
+
+/// Geodesic through `x0` with velocity `v0` as in `geodesic`, carrying the deviation vector
+/// `ξ` that starts at `xi0` with rate `w0`, integrated through `jacobi_equations`. `xi` and `w`
+/// stand for `ξ` and `ξ̇`.
+pub fn geodesic_deviation<const N: usize>(
+    g: &SqMatrix<N>,
+    gamma: &Christoffel<N>,
+    tau: Var,
+    [x, v, xi, w]: [[Var; N]; 4],
+    method: Method,
+    span: (f64, f64),
+    [x0, v0, xi0, w0]: [[f64; N]; 4],
+) -> Result<DeviationPath<N>, String> {
+    let rhs = w
+        .iter()
+        .map(|&w| e!(w))
+        .chain(jacobi_equations(gamma, x, v, xi, w))
+        .collect();
+    let vars = [xi, w].concat();
+    let y0 = [xi0, w0].concat();
+    let mut separation = e!(c!());
+    for m in 0..N {
+        for n in 0..N {
+            separation = separation + g[m][n].clone() * e!(xi[m]) * e!(xi[n]);
+        }
+    }
+    let extra = (&vars[..], rhs, &y0[..]);
+    let (geodesic, extra, observed) = integrate(
+        g,
+        gamma,
+        tau,
+        [x, v],
+        [x0, v0],
+        extra,
+        &[separation],
+        method,
+        span,
+    )?;
+
+    let mut path = DeviationPath {
+        geodesic,
+        xi: Vec::new(),
+        w: Vec::new(),
+        separation: observed[0].iter().map(|s| s.abs().sqrt()).collect(),
+    };
+    for y in extra {
+        path.xi.push(std::array::from_fn(|i| y[i]));
+        path.w.push(std::array::from_fn(|i| y[N + i]));
+    }
+    Ok(path)
+}
+
+/// Integrates the geodesic equations together with the variables `extra`, which obey
+/// `extra_rhs` and start at `extra0`. Returns the path, the `extra` values at each step and
+/// each of `observe` along the path.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn integrate<const N: usize>(
+    g: &SqMatrix<N>,
+    gamma: &Christoffel<N>,
+    tau: Var,
+    [x, v]: [[Var; N]; 2],
+    [x0, v0]: [[f64; N]; 2],
+    (extra, extra_rhs, extra0): (&[Var], Vec<Expression>, &[f64]),
+    observe: &[Expression],
+    method: Method,
+    (tau0, tau1): (f64, f64),
+) -> Result<(GeodesicPath<N>, Vec<Vec<f64>>, Vec<Vec<f64>>), String> {
+    let rhs = v
+        .iter()
+        .map(|&v| e!(v))
+        .chain(geodesic_equations(gamma, v))
+        .chain(extra_rhs)
+        .collect();
+    let vars = x.iter().chain(&v).chain(extra).copied().collect();
+    let system = OdeSystem::new(tau, vars, rhs)?;
+    let y0 = x0.iter().chain(&v0).chain(extra0).copied().collect();
+    let trajectory = system.solve(method, tau0, y0, tau1, &[])?;
+
+    let mut norm = e!(c!());
+    for m in 0..N {
+        for n in 0..N {
+            norm = norm + g[m][n].clone() * e!(v[m]) * e!(v[n]);
+        }
+    }
+    let norm = system.along(norm, &trajectory)?;
+    let observed = observe
+        .iter()
+        .map(|o| system.along(o.clone(), &trajectory))
+        .collect::<Result<_, _>>()?;
+
+    let mut path = GeodesicPath {
+        tau: trajectory.t.clone(),
+        x: Vec::new(),
+        v: Vec::new(),
+        norm,
+        names: [x, v],
+    };
+    let mut extra = Vec::new();
+    for y in trajectory.y {
+        path.x.push(std::array::from_fn(|i| y[i]));
+        path.v.push(std::array::from_fn(|i| y[N + i]));
+        extra.push(y[2 * N..].to_vec());
+    }
+    Ok((path, extra, observed))
+}
+
+/// Geodesic sampled at the accepted steps, with `g(ẋ, ẋ)` at each of them
+#[derive(Debug, Clone)]
+pub struct GeodesicPath<const N: usize> {
+    pub tau: Vec<f64>,
+    pub x: Vec<[f64; N]>,
+    pub v: Vec<[f64; N]>,
+    pub norm: Vec<f64>,
+    names: [[Var; N]; 2],
+}
+
+impl<const N: usize> GeodesicPath<N> {
+    /// Largest departure of `g(ẋ, ẋ)` from its initial value, which vanishes along an
+    /// exact geodesic
+    pub fn norm_drift(&self) -> f64 {
+        self.norm
+            .iter()
+            .map(|n| (n - self.norm[0]).abs())
+            .fold(0.0, f64::max)
+    }
+
+    /// Trajectory as CSV, one row per step, with the coordinates named after `sys`
+    pub fn csv(&self, sys: &System) -> String {
+        let [x, v] = self.names;
+        let mut f = String::from("tau");
+        for x in x {
+            f += &format!(",{}", sys.str(e!(x)));
+        }
+        for v in v {
+            f += &format!(",{}", sys.str(e!(v)));
+        }
+        f += ",norm\n";
+        for i in 0..self.tau.len() {
+            f += &self.tau[i].to_string();
+            for y in self.x[i].iter().chain(&self.v[i]) {
+                f += &format!(",{y}");
+            }
+            f += &format!(",{}\n", self.norm[i]);
+        }
+        f
+    }
+
+    pub fn write_csv(
+        &self,
+        sys: &System,
+        path: impl AsRef<std::path::Path>,
+    ) -> std::io::Result<()> {
+        std::fs::write(path, self.csv(sys))
+    }
+}
+
+/// Geodesic with a deviation vector `ξ` carried along it, sampled at the same steps
+#[derive(Debug, Clone)]
+pub struct DeviationPath<const N: usize> {
+    pub geodesic: GeodesicPath<N>,
+    pub xi: Vec<[f64; N]>,
+    /// `ξ̇`
+    pub w: Vec<[f64; N]>,
+    /// `√|g(ξ, ξ)|`
+    pub separation: Vec<f64>,
+}
@@ -0,0 +1,49 @@
+use crate::{c, e, Expression, SqMatrix, Tensor, Var, Variance};
+
+use super::{covariant_derivative, Christoffel};
+
+/// `L_ξ T` along the vector field `xi`, through partial derivatives only:
+/// `ξ^c ∂_c T - T^..c.. ∂_c ξ^a + T_..c.. ∂_b ξ^c` over every upper `a` and lower `b`
+pub fn lie_derivative<const N: usize>(
+    t: &Tensor<N>,
+    xi: &[Expression; N],
+    x: [Var; N],
+) -> Tensor<N> {
+    Tensor::from_fn(t.variance().to_vec(), |i| {
+        let mut sum = e!(c!());
+        for c in 0..N {
+            sum = sum + xi[c].clone() * t[i].clone().diff(x[c]);
+        }
+        for (k, v) in t.variance().iter().enumerate() {
+            let mut j = i.to_vec();
+            for c in 0..N {
+                j[k] = c;
+                sum = match v {
+                    Variance::Up => sum - t[&j[..]].clone() * xi[i[k]].clone().diff(x[c]),
+                    Variance::Down => sum + t[&j[..]].clone() * xi[c].clone().diff(x[i[k]]),
+                };
+            }
+        }
+        sum
+    })
+}
+
+/// Killing equations `∇_μ ξ_ν + ∇_ν ξ_μ = 0` for `μ <= ν`. Components of `xi` built from
+/// undefined functions give the system of PDEs for the Killing vectors.
+pub fn killing_equations<const N: usize>(
+    g: &SqMatrix<N>,
+    gamma: &Christoffel<N>,
+    xi: &[Expression; N],
+    x: [Var; N],
+) -> Vec<Expression> {
+    let xi = Tensor::from_fn(vec![Variance::Up], |i| xi[i[0]].clone()).lower(0, g);
+    let d = covariant_derivative(&xi, gamma, x);
+    let mut equations = Vec::new();
+    for m in 0..N {
+        for n in m..N {
+            // `d[[n, m]] = ∇_m ξ_n`
+            equations.push((d[[n, m]].clone() + d[[m, n]].clone()).simplify());
+        }
+    }
+    equations
+}
@@ -0,0 +1,163 @@
+//! Common metrics, each returned with its coordinates, which are created in `sys`. Fails when
+//! a coordinate name is already taken in `sys`. Parameters such as masses are taken as
+//! expressions, either numbers or variables of `sys`.
+
+use crate::{cos, e, sin, Expression, SqMatrix, System, Var};
+
+fn diag<const N: usize>(d: [Expression; N]) -> SqMatrix<N> {
+    let mut g = SqMatrix::zeroes();
+    for (i, d) in d.into_iter().enumerate() {
+        g[i][i] = d;
+    }
+    g
+}
+
+/// `-f dt² + dr²/f + r² dΩ²` for `f` given in terms of `r`
+fn static_spherical(
+    sys: &mut System,
+    f: impl FnOnce(Var) -> Expression,
+) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    let [t, r, th, ph] = sys.symbols("t r θ φ")?;
+    let f = f(r);
+    let g = diag([
+        -f.clone(),
+        f.inv(),
+        e!(r).pow(2.0),
+        e!(r).pow(2.0) * sin(th).pow(2.0),
+    ]);
+    Ok((g, [t, r, th, ph]))
+}
+
+/// Minkowski metric in `t x y z`
+pub fn minkowski(sys: &mut System) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    let x = sys.symbols("t x y z")?;
+    Ok((diag([e!(-1.0), e!(1.0), e!(1.0), e!(1.0)]), x))
+}
+
+/// Minkowski metric in spherical coordinates `t r θ φ`
+pub fn minkowski_spherical(sys: &mut System) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    static_spherical(sys, |_| e!(1.0))
+}
+
+/// Minkowski metric in cylindrical coordinates `t ρ φ z`
+pub fn minkowski_cylindrical(sys: &mut System) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    let [t, rho, ph, z] = sys.symbols("t ρ φ z")?;
+    let g = diag([e!(-1.0), e!(1.0), e!(rho).pow(2.0), e!(1.0)]);
+    Ok((g, [t, rho, ph, z]))
+}
+
+/// Minkowski metric in light-cone coordinates `u v y z`, `u = t - x` and `v = t + x`
+pub fn minkowski_light_cone(sys: &mut System) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    let x = sys.symbols("u v y z")?;
+    let mut g = diag([e!(0.0), e!(0.0), e!(1.0), e!(1.0)]);
+    g[0][1] = e!(-0.5);
+    g[1][0] = e!(-0.5);
+    Ok((g, x))
+}
+
+/// Schwarzschild metric of mass `mass` in `t r θ φ`
+pub fn schwarzschild(
+    sys: &mut System,
+    mass: Expression,
+) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    static_spherical(sys, |r| e!(1.0) - e!(2.0) * mass / e!(r))
+}
+
+/// Reissner–Nordström metric of mass `mass` and charge `charge` in `t r θ φ`, geometrized
+/// Gaussian units
+pub fn reissner_nordstrom(
+    sys: &mut System,
+    mass: Expression,
+    charge: Expression,
+) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    static_spherical(sys, |r| {
+        e!(1.0) - e!(2.0) * mass / e!(r) + charge.pow(2.0) / e!(r).pow(2.0)
+    })
+}
+
+/// Kerr metric of mass `mass` and spin `a = J/M` in Boyer–Lindquist coordinates `t r θ φ`
+pub fn kerr(
+    sys: &mut System,
+    mass: Expression,
+    a: Expression,
+) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    kerr_newman(sys, mass, a, e!(0.0))
+}
+
+/// Kerr–Newman metric of mass `mass`, spin `a = J/M` and charge `charge` in Boyer–Lindquist
+/// coordinates `t r θ φ`
+pub fn kerr_newman(
+    sys: &mut System,
+    mass: Expression,
+    a: Expression,
+    charge: Expression,
+) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    let [t, r, th, ph] = sys.symbols("t r θ φ")?;
+    let (r2, a2) = (e!(r).pow(2.0), a.clone().pow(2.0));
+    let sin2 = sin(th).pow(2.0);
+    // `Σ = r² + a² cos²θ`, `Δ = r² - 2Mr + a² + Q²`
+    let sigma = r2.clone() + a2.clone() * cos(th).pow(2.0);
+    let source = e!(2.0) * mass * e!(r) - charge.pow(2.0);
+    let delta = r2.clone() - source.clone() + a2.clone();
+    let mut g = diag([
+        -(e!(1.0) - source.clone() / sigma.clone()),
+        sigma.clone() / delta,
+        sigma.clone(),
+        (r2 + a2.clone() + source.clone() * a2 * sin2.clone() / sigma.clone()) * sin2.clone(),
+    ]);
+    g[0][3] = -(source * a * sin2 / sigma);
+    g[3][0] = g[0][3].clone();
+    Ok((g, [t, r, th, ph]))
+}
+
+/// de Sitter metric with cosmological constant `lambda` in static coordinates `t r θ φ`,
+/// anti-de Sitter for negative `lambda`
+pub fn de_sitter(sys: &mut System, lambda: Expression) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    static_spherical(sys, |r| e!(1.0) - lambda * e!(r).pow(2.0) / e!(3.0))
+}
+
+/// Anti-de Sitter metric of curvature radius `length` in static coordinates `t r θ φ`
+pub fn anti_de_sitter(
+    sys: &mut System,
+    length: Expression,
+) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    static_spherical(sys, |r| e!(1.0) + e!(r).pow(2.0) / length.pow(2.0))
+}
+
+/// FLRW metric `-dt² + a² (dr²/(1 - k r²) + r² dΩ²)` in `t r θ φ`, with the scale factor
+/// given by `a(t)` and the spatial curvature by `k`
+pub fn flrw(
+    sys: &mut System,
+    a: impl FnOnce(Var) -> Expression,
+    k: Expression,
+) -> Result<(SqMatrix<4>, [Var; 4]), String> {
+    let [t, r, th, ph] = sys.symbols("t r θ φ")?;
+    let a2 = a(t).pow(2.0);
+    let g = diag([
+        e!(-1.0),
+        a2.clone() / (e!(1.0) - k * e!(r).pow(2.0)),
+        a2.clone() * e!(r).pow(2.0),
+        a2 * e!(r).pow(2.0) * sin(th).pow(2.0),
+    ]);
+    Ok((g, [t, r, th, ph]))
+}
+
+/// Round `N`-sphere of radius `radius` in the angles `θ1 … θN-1 φ`, with
+/// `dΩ²_N = dθ1² + sin²θ1 dΩ²_N-1`
+pub fn sphere<const N: usize>(
+    sys: &mut System,
+    radius: Expression,
+) -> Result<(SqMatrix<N>, [Var; N]), String> {
+    let names = (1..N)
+        .map(|i| format!("θ{i} "))
+        .chain(["φ".to_string()])
+        .collect::<String>();
+    let x = sys.symbols(&names)?;
+    let mut factor = radius.pow(2.0);
+    let mut d = Vec::with_capacity(N);
+    for &x in &x {
+        d.push(factor.clone());
+        factor = factor * sin(x).pow(2.0);
+    }
+    Ok((diag(d.try_into().unwrap()), x))
+}
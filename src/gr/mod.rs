@@ -0,0 +1,265 @@
+pub mod cartan;
+pub mod components;
+pub mod covariant;
+pub mod curvature;
+pub mod deviation;
+pub mod embedding;
+pub mod geodesic;
+pub mod lie;
+pub mod metrics;
+pub mod transform;
+pub use cartan::*;
+pub use components::*;
+pub use covariant::*;
+pub use curvature::*;
+pub use deviation::*;
+pub use embedding::*;
+pub use geodesic::*;
+pub use lie::*;
+pub use metrics::*;
+pub use transform::*;
+
+use std::cell::OnceCell;
+
+use crate::{e, Expression, Method, SqMatrix, Tensor, Var};
+
+/// Metric `g` in the coordinates `x`. The inverse and every curvature quantity are
+/// computed on first use and kept, each one built from the cached ones before it.
+#[derive(Debug, Clone)]
+pub struct Metric<const N: usize> {
+    g: SqMatrix<N>,
+    x: [Var; N],
+    g_inv: OnceCell<SqMatrix<N>>,
+    christoffel: OnceCell<Christoffel<N>>,
+    riemann: OnceCell<RiemannCurvature<N>>,
+    ricci: OnceCell<RicciCurvature<N>>,
+    scalar: OnceCell<Expression>,
+    einstein: OnceCell<EinsteinTensor<N>>,
+    weyl: OnceCell<RiemannCurvature<N>>,
+    kretschmann: OnceCell<Expression>,
+    ricci_squared: OnceCell<Expression>,
+    weyl_squared: OnceCell<Expression>,
+}
+
+impl<const N: usize> Metric<N> {
+    pub fn new(g: SqMatrix<N>, x: [Var; N]) -> Self {
+        Metric {
+            g: g.simplify(),
+            x,
+            g_inv: OnceCell::new(),
+            christoffel: OnceCell::new(),
+            riemann: OnceCell::new(),
+            ricci: OnceCell::new(),
+            scalar: OnceCell::new(),
+            einstein: OnceCell::new(),
+            weyl: OnceCell::new(),
+            kretschmann: OnceCell::new(),
+            ricci_squared: OnceCell::new(),
+            weyl_squared: OnceCell::new(),
+        }
+    }
+
+    pub fn g(&self) -> &SqMatrix<N> {
+        &self.g
+    }
+
+    pub fn coords(&self) -> [Var; N] {
+        self.x
+    }
+
+    pub fn g_inv(&self) -> &SqMatrix<N> {
+        self.g_inv.get_or_init(|| self.g.inv().simplify())
+    }
+
+    pub fn christoffel(&self) -> &Christoffel<N> {
+        self.christoffel.get_or_init(|| {
+            christoffel(self.g.clone(), self.g_inv().clone(), self.x).map(|e| e.simplify())
+        })
+    }
+
+    pub fn riemann(&self) -> &RiemannCurvature<N> {
+        self.riemann.get_or_init(|| {
+            riemann_tensor(self.g.clone(), self.christoffel(), self.x).map(|e| e.simplify())
+        })
+    }
+
+    pub fn ricci(&self) -> &RicciCurvature<N> {
+        self.ricci
+            .get_or_init(|| ricci_tensor(self.riemann(), self.g_inv().clone()).map(simplify1))
+    }
+
+    pub fn scalar_curvature(&self) -> &Expression {
+        self.scalar
+            .get_or_init(|| scalar_curvature_einsum(self.ricci(), self.g_inv()).simplify())
+    }
+
+    pub fn einstein(&self) -> &EinsteinTensor<N> {
+        self.einstein.get_or_init(|| {
+            einstein_tensor(
+                self.ricci(),
+                self.scalar_curvature().clone(),
+                self.g.clone(),
+            )
+            .map(simplify1)
+        })
+    }
+
+    pub fn trace_free_ricci(&self) -> RicciCurvature<N> {
+        trace_free_ricci(
+            self.ricci(),
+            self.scalar_curvature().clone(),
+            self.g.clone(),
+        )
+        .map(simplify1)
+    }
+
+    pub fn weyl(&self) -> &RiemannCurvature<N> {
+        self.weyl.get_or_init(|| {
+            weyl_tensor(
+                self.riemann(),
+                self.ricci(),
+                self.scalar_curvature().clone(),
+                self.g.clone(),
+            )
+            .map(|e| e.simplify())
+        })
+    }
+
+    /// `R_abcd R^abcd`
+    pub fn kretschmann(&self) -> &Expression {
+        self.kretschmann
+            .get_or_init(|| square_rank4(self.riemann(), self.g_inv().clone()).simplify())
+    }
+
+    /// `R_mn R^mn`
+    pub fn ricci_squared(&self) -> &Expression {
+        self.ricci_squared
+            .get_or_init(|| ricci_squared(self.ricci(), self.g_inv().clone()).simplify())
+    }
+
+    /// `C_abcd C^abcd`
+    pub fn weyl_squared(&self) -> &Expression {
+        self.weyl_squared.get_or_init(|| {
+            weyl_squared::<N, _>(
+                self.kretschmann().clone(),
+                self.ricci_squared().clone(),
+                self.scalar_curvature().clone(),
+            )
+            .simplify()
+        })
+    }
+
+    /// `T^mn` from `T_mn`
+    pub fn raise(&self, t: &[[Expression; N]; N]) -> [[Expression; N]; N] {
+        raise_indices(t, self.g_inv().clone()).map(simplify1)
+    }
+
+    /// `T_mn` from `T^mn`
+    pub fn lower(&self, t: &[[Expression; N]; N]) -> [[Expression; N]; N] {
+        lower_indices(t, self.g.clone()).map(simplify1)
+    }
+
+    /// `∇_c T`, the derivative index appended last
+    pub fn covariant_derivative(&self, t: &Tensor<N>) -> Tensor<N> {
+        covariant_derivative(t, self.christoffel(), self.x).simplify()
+    }
+
+    /// `∇_a T^..a..` over the index `index`
+    #[track_caller]
+    pub fn divergence(&self, t: &Tensor<N>, index: usize) -> Tensor<N> {
+        divergence(t, index, self.christoffel(), self.g_inv(), self.x).simplify()
+    }
+
+    /// `L_ξ T` along the vector field `xi`
+    pub fn lie_derivative(&self, t: &Tensor<N>, xi: &[Expression; N]) -> Tensor<N> {
+        lie_derivative(t, xi, self.x).simplify()
+    }
+
+    /// Killing equations `∇_μ ξ_ν + ∇_ν ξ_μ = 0` for `μ <= ν`
+    pub fn killing_equations(&self, xi: &[Expression; N]) -> Vec<Expression> {
+        killing_equations(&self.g, self.christoffel(), xi, self.x)
+    }
+
+    /// Whether `xi` satisfies the Killing equations, the metric being invariant along it
+    pub fn is_killing(&self, xi: &[Expression; N]) -> bool {
+        self.killing_equations(xi).iter().all(|e| e.is_zero())
+    }
+
+    /// `g(ξ, ẋ)`, constant along geodesics for a Killing vector `xi`, `v` standing for the
+    /// velocities
+    pub fn conserved_quantity(&self, xi: &[Expression; N], v: [Var; N]) -> Expression {
+        let mut sum = e!(0.0);
+        for (row, xi) in self.g.0.iter().zip(xi) {
+            for (g, &v) in row.iter().zip(&v) {
+                sum = sum + g.clone() * xi.clone() * e!(v);
+            }
+        }
+        sum.simplify()
+    }
+
+    /// Orthonormal frame of the observer with velocity `u`, see `observer_frame`
+    pub fn observer_frame(&self, u: &[Expression; N]) -> SqMatrix<N> {
+        observer_frame(&self.g, u)
+    }
+
+    /// Tidal tensor `E_ij = R_i0j0` seen by the observer with velocity `u`, in their frame
+    pub fn tidal_tensor(&self, u: &[Expression; N]) -> SqMatrix<N> {
+        tidal_tensor(self.riemann(), &self.observer_frame(u))
+    }
+
+    /// Geodesic deviation `D²ξ^μ/dτ² = -R^μ_νρσ u^ν ξ^ρ u^σ`, see `deviation_equations`
+    pub fn deviation_equations(&self, u: &[Expression; N], xi: [Var; N]) -> [Expression; N] {
+        deviation_equations(self.riemann(), self.g_inv(), u, xi)
+    }
+
+    pub fn geodesic_equations(&self, v: [Var; N]) -> [Expression; N] {
+        geodesic_equations(self.christoffel(), v)
+    }
+
+    /// Geodesic through `x0` with velocity `v0`, see `geodesic`
+    pub fn geodesic(
+        &self,
+        tau: Var,
+        v: [Var; N],
+        method: Method,
+        span: (f64, f64),
+        x0: [f64; N],
+        v0: [f64; N],
+    ) -> Result<GeodesicPath<N>, String> {
+        geodesic(
+            &self.g,
+            self.christoffel(),
+            tau,
+            self.x,
+            v,
+            method,
+            span,
+            x0,
+            v0,
+        )
+    }
+
+    /// Geodesic carrying a deviation vector, see `geodesic_deviation`
+    pub fn geodesic_deviation(
+        &self,
+        tau: Var,
+        vars: [[Var; N]; 4],
+        method: Method,
+        span: (f64, f64),
+        initial: [[f64; N]; 4],
+    ) -> Result<DeviationPath<N>, String> {
+        geodesic_deviation(
+            &self.g,
+            self.christoffel(),
+            tau,
+            vars,
+            method,
+            span,
+            initial,
+        )
+    }
+}
+
+fn simplify1<const N: usize>(a: [Expression; N]) -> [Expression; N] {
+    a.map(|e| e.simplify())
+}
@@ -0,0 +1,154 @@
+use std::{array, cell::OnceCell};
+
+use crate::{expression::ExprKind, Expression, SqMatrix, Tensor, Var, Variance};
+
+use super::Metric;
+
+/// Change of coordinates `x'^μ = f^μ(x)` from `old` to `new`
+#[derive(Debug, Clone)]
+pub struct Transformation<const N: usize> {
+    old: [Var; N],
+    new: [Var; N],
+    /// `x^μ` in the new coordinates, where known
+    inverse: [Option<Expression>; N],
+    jacobian: SqMatrix<N>,
+    jacobian_inv: OnceCell<SqMatrix<N>>,
+}
+
+impl<const N: usize> Transformation<N> {
+    /// Transformation given by `forward[μ] = x'^μ` in terms of `old`. Old coordinates that
+    /// are carried over unchanged are substituted by their new names, the rest stay until
+    /// `with_inverse`.
+    pub fn new(old: [Var; N], new: [Var; N], forward: [Expression; N]) -> Self {
+        let jacobian = SqMatrix(array::from_fn(|m| {
+            array::from_fn(|n| forward[m].clone().diff(old[n]).simplify())
+        }));
+        let mut inverse = array::from_fn(|_| None);
+        for (f, &new) in forward.iter().zip(&new) {
+            if let ExprKind::Var(v) = f.kind() {
+                if let Some(j) = old.iter().position(|&o| o == v) {
+                    inverse[j] = Some(Expression::from(new));
+                }
+            }
+        }
+        Transformation {
+            jacobian_inv: OnceCell::new(),
+            old,
+            new,
+            inverse,
+            jacobian,
+        }
+    }
+
+    /// Supplies `inverse[μ] = x^μ` in terms of the new coordinates, so that results are
+    /// written in the new coordinates only
+    pub fn with_inverse(mut self, inverse: [Expression; N]) -> Self {
+        self.inverse = inverse.map(Some);
+        self.jacobian_inv = OnceCell::new();
+        self
+    }
+
+    pub fn old(&self) -> [Var; N] {
+        self.old
+    }
+
+    pub fn new_coords(&self) -> [Var; N] {
+        self.new
+    }
+
+    /// `∂x'^μ/∂x^ν`, in the old coordinates
+    pub fn jacobian(&self) -> &SqMatrix<N> {
+        &self.jacobian
+    }
+
+    /// `∂x^μ/∂x'^ν`, differentiated from the inverse map when it is fully known and inverted
+    /// from the Jacobian otherwise
+    pub fn jacobian_inv(&self) -> &SqMatrix<N> {
+        self.jacobian_inv
+            .get_or_init(|| match self.inverse.iter().all(Option::is_some) {
+                true => SqMatrix(array::from_fn(|m| {
+                    let inverse = self.inverse[m].clone().unwrap();
+                    array::from_fn(|n| inverse.clone().diff(self.new[n]).simplify())
+                })),
+                false => {
+                    let inv = self.jacobian.inv();
+                    SqMatrix(inv.0.map(|row| row.map(|e| self.substitute(e))))
+                }
+            })
+    }
+
+    /// `ex` with the old coordinates replaced wherever their inverse is known
+    pub fn substitute(&self, ex: Expression) -> Expression {
+        let known = self.old.iter().zip(&self.inverse);
+        let (old, inverse): (Vec<_>, Vec<_>) = known
+            .filter_map(|(&old, inverse)| Some((old, inverse.clone()?)))
+            .unzip();
+        substitute(ex, &old, &inverse)
+    }
+
+    /// Components of `t` in the new coordinates, upper indices taking `∂x'/∂x` and lower
+    /// ones `∂x/∂x'`
+    pub fn tensor(&self, t: &Tensor<N>) -> Tensor<N> {
+        // Everything in the new coordinates first, which may reuse names of the old ones
+        let mut t = Tensor::from_fn(t.variance().to_vec(), |i| self.substitute(t[i].clone()));
+        let variance = t.variance().to_vec();
+        if variance.contains(&Variance::Up) {
+            let jacobian = self
+                .jacobian
+                .0
+                .clone()
+                .map(|r| r.map(|e| self.substitute(e)));
+            let jacobian = SqMatrix(jacobian);
+            for (k, _) in variance
+                .iter()
+                .enumerate()
+                .filter(|(_, &v)| v == Variance::Up)
+            {
+                t = t.transform(k, &jacobian, Variance::Up).simplify();
+            }
+        }
+        if variance.contains(&Variance::Down) {
+            let jacobian_inv = self.jacobian_inv().transpose();
+            for (k, _) in variance
+                .iter()
+                .enumerate()
+                .filter(|(_, &v)| v == Variance::Down)
+            {
+                t = t.transform(k, &jacobian_inv, Variance::Down).simplify();
+            }
+        }
+        t
+    }
+
+    /// Metric `g'_ab = (∂x^μ/∂x'^a) (∂x^ν/∂x'^b) g_μν`
+    pub fn metric(&self, g: &SqMatrix<N>) -> SqMatrix<N> {
+        let t = self.tensor(&Tensor::matrix(&g.0, [Variance::Down, Variance::Down]));
+        SqMatrix(array::from_fn(|i| array::from_fn(|j| t[[i, j]].clone())))
+    }
+}
+
+impl<const N: usize> Metric<N> {
+    /// Same metric in the coordinates of `transformation`, which must start from the
+    /// coordinates of `self`
+    #[track_caller]
+    pub fn transform(&self, transformation: &Transformation<N>) -> Metric<N> {
+        assert!(
+            transformation.old == self.coords(),
+            "Transformation does not start from the coordinates of the metric"
+        );
+        Metric::new(transformation.metric(self.g()), transformation.new)
+    }
+}
+
+/// `ex` with every `vars[i]` replaced by `values[i]` at once, through dummies as the values may
+/// contain the variables themselves
+pub(crate) fn substitute(ex: Expression, vars: &[Var], values: &[Expression]) -> Expression {
+    let mut ex = ex;
+    for (i, &x) in vars.iter().enumerate() {
+        ex = ex.subs(x, Var::slot(i));
+    }
+    for (i, value) in values.iter().enumerate() {
+        ex = ex.subs(Var::slot(i), value.clone());
+    }
+    ex.simplify()
+}
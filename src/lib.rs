@@ -1,11 +1,16 @@
 pub mod expression;
+pub mod form;
+pub mod gr;
 pub mod matrix;
+pub mod tensor;
 pub mod tree;
 
 use crate::tree::{NodeId, Tree};
 pub use expression::*;
+pub use form::*;
 pub use matrix::*;
 pub use num_complex::Complex64;
+pub use tensor::*;
 
 pub const TOL: f64 = 1e-15;
 
@@ -54,6 +59,7 @@ pub fn printc(c: Complex64) {
 #[derive(Default, Clone, Debug)]
 pub struct System {
     variables: Vec<String>,
+    functions: Vec<String>,
 }
 
 impl System {
@@ -80,15 +86,49 @@ impl System {
         Ok(vars)
     }
 
+    /// Declares an undefined function of `arity` arguments
+    pub fn function(&mut self, ident: &str, arity: usize) -> Result<Func, String> {
+        if self.functions.iter().any(|f| f == ident) {
+            return Err(format!("Function with the name {ident} already exists"));
+        }
+        self.functions.push(ident.to_string());
+        Ok(Func {
+            id: self.functions.len() - 1,
+            arity,
+        })
+    }
+
+    fn name(&self, x: Var) -> String {
+        match self.variables.get(x.id) {
+            Some(name) => name.clone(),
+            None => format!("ξ{}", usize::MAX - x.id + 1),
+        }
+    }
+
     pub fn str<T: Clone>(&self, exp: Expressable<T>) -> String
+    where
+        Expression: From<Expressable<T>>,
+    {
+        self.write(exp, false)
+    }
+
+    /// Like `str`, but with derivatives of one argument functions in prime notation
+    pub fn strprime<T: Clone>(&self, exp: Expressable<T>) -> String
+    where
+        Expression: From<Expressable<T>>,
+    {
+        self.write(exp, true)
+    }
+
+    fn write<T: Clone>(&self, exp: Expressable<T>, prime: bool) -> String
     where
         Expression: From<Expressable<T>>,
     {
         let tree = exp.tree();
         let mut f = String::new();
-        fn write_children(vars: &[String], tree: &Tree, id: NodeId, f: &mut String) {
+        fn write_children(sys: &System, prime: bool, tree: &Tree, id: NodeId, f: &mut String) {
             match tree.node(id).kind() {
-                ExprKind::Var(x) => *f += &vars[x.id],
+                ExprKind::Var(x) => *f += &sys.name(x),
                 ExprKind::Const(c) => match (c.re, c.im) {
                     (re, im) if re.abs() > TOL && im.abs() > TOL => {
                         f.push_str(&format!("{re:.3e}+{im:.3e}i"))
@@ -100,52 +140,130 @@ impl System {
                 ExprKind::Add => {
                     *f += "(";
                     let mut iter = tree.node(id).children().iter();
-                    write_children(vars, tree, *iter.next().unwrap(), f);
+                    write_children(sys, prime, tree, *iter.next().unwrap(), f);
                     for &child in iter {
                         *f += "+";
-                        write_children(vars, tree, child, f);
+                        write_children(sys, prime, tree, child, f);
                     }
                     *f += ")";
                 }
                 ExprKind::Mul => {
                     *f += " ";
                     let mut iter = tree.node(id).children().iter();
-                    write_children(vars, tree, *iter.next().unwrap(), f);
+                    write_children(sys, prime, tree, *iter.next().unwrap(), f);
                     for &child in iter {
                         *f += "*";
-                        write_children(vars, tree, child, f);
+                        write_children(sys, prime, tree, child, f);
                     }
                     *f += " ";
                 }
                 ExprKind::Exp => {
                     *f += " e^";
                     let mut iter = tree.node(id).children().iter();
-                    write_children(vars, tree, *iter.next().unwrap(), f);
+                    write_children(sys, prime, tree, *iter.next().unwrap(), f);
                     assert!(iter.next().is_none());
                     *f += " ";
                 }
                 ExprKind::Ln => {
                     *f += "ln(";
                     let mut iter = tree.node(id).children().iter();
-                    write_children(vars, tree, *iter.next().unwrap(), f);
+                    write_children(sys, prime, tree, *iter.next().unwrap(), f);
                     assert!(iter.next().is_none());
                     *f += ")";
                 }
                 ExprKind::Abs => {
                     *f += "|";
                     let mut iter = tree.node(id).children().iter();
-                    write_children(vars, tree, *iter.next().unwrap(), f);
+                    write_children(sys, prime, tree, *iter.next().unwrap(), f);
                     assert!(iter.next().is_none());
                     *f += "|";
                 }
+                ExprKind::Integral(x) => {
+                    *f += "∫(";
+                    let mut iter = tree.node(id).children().iter();
+                    write_children(sys, prime, tree, *iter.next().unwrap(), f);
+                    *f += &format!(")d{}", sys.name(x));
+                    let p = *iter.next().unwrap();
+                    if tree.node(p).kind() != ExprKind::Var(x) {
+                        *f += &format!("|{}=", sys.name(x));
+                        write_children(sys, prime, tree, p, f);
+                    }
+                }
+                ExprKind::Func(g) => {
+                    *f += &sys.functions[g.id];
+                    *f += "(";
+                    for (i, &child) in tree.node(id).children().iter().enumerate() {
+                        if i > 0 {
+                            *f += ",";
+                        }
+                        write_children(sys, prime, tree, child, f);
+                    }
+                    *f += ")";
+                }
+                ExprKind::Derivative(x, n) => {
+                    let [mut inner, p] = tree.node(id).children()[..] else {
+                        unreachable!()
+                    };
+                    let power = |n: usize| match n {
+                        1 => String::new(),
+                        n => format!("^{n}"),
+                    };
+                    let own = tree.node(p).kind() == ExprKind::Var(x);
+                    if let ExprKind::Func(g) = tree.node(inner).kind() {
+                        let args = tree.node(inner).children();
+                        if prime && g.arity == 1 && tree.node(args[0]).kind() == ExprKind::Var(x) {
+                            *f += &sys.functions[g.id];
+                            *f += &match n {
+                                1..=3 => "'".repeat(n),
+                                n => format!("^({n})"),
+                            };
+                            *f += "(";
+                            write_children(sys, prime, tree, p, f);
+                            *f += ")";
+                            return;
+                        }
+                    }
+                    // Nested derivatives at their own variables print as one mixed partial
+                    let mut by = vec![(x, n)];
+                    while let ExprKind::Derivative(u, m) = tree.node(inner).kind() {
+                        let node = tree.node(inner);
+                        if !own || tree.node(node.children()[1]).kind() != ExprKind::Var(u) {
+                            break;
+                        }
+                        by.push((u, m));
+                        inner = node.children()[0];
+                    }
+                    let d = match tree.node(inner).kind() {
+                        ExprKind::Func(g) if g.arity == 1 => "d",
+                        _ => "∂",
+                    };
+                    let order = by.iter().map(|&(_, m)| m).sum();
+                    *f += &format!("{d}{}", power(order));
+                    match tree.node(inner).kind() {
+                        ExprKind::Func(_) => write_children(sys, prime, tree, inner, f),
+                        _ => {
+                            *f += "(";
+                            write_children(sys, prime, tree, inner, f);
+                            *f += ")";
+                        }
+                    }
+                    *f += "/";
+                    for (u, m) in by {
+                        *f += &format!("{d}{}{}", sys.name(u), power(m));
+                    }
+                    if !own {
+                        *f += &format!("|{}=", sys.name(x));
+                        write_children(sys, prime, tree, p, f);
+                    }
+                }
                 ExprKind::ROOT => {
                     for &child in tree.node(id).children() {
-                        write_children(vars, tree, child, f);
+                        write_children(sys, prime, tree, child, f);
                     }
                 }
             }
         }
-        write_children(&self.variables, &tree, NodeId::ROOT, &mut f);
+        write_children(self, prime, &tree, NodeId::ROOT, &mut f);
         f
     }
 
@@ -161,6 +279,21 @@ impl System {
         f
     }
 
+    pub fn strseries(&self, series: Series) -> String {
+        let order = series.order();
+        let h = match &series.point {
+            Point::Finite(p) if p.is_zero() => self.str(e!(series.x)),
+            Point::Finite(p) => format!("({})", self.str((e!(series.x) - p.clone()).simplify())),
+            Point::PosInf => format!("(1/{})", self.str(e!(series.x))),
+            Point::NegInf => format!("(-1/{})", self.str(e!(series.x))),
+        };
+        let mut f = self.str(Expression::from(series));
+        if let Some(order) = order {
+            f += &format!(" + O({h}^{order})");
+        }
+        f
+    }
+
     pub fn eval<const N: usize, T: Clone>(
         &self,
         exp: Expressable<T>,
@@ -1,9 +1,5 @@
 #![allow(dead_code, non_snake_case)]
-use std::f64::consts::PI;
-
 use symrs::*;
-mod tensor;
-mod curvature;
 
 fn main() {
     let mut sys = System::default();
@@ -16,6 +12,10 @@ fn main() {
     println!("{}", sys.str(A.clone()));
     println!("{}", sys.str(E.clone()));
 
+    if let Ok(Point::Finite(a)) = A.clone().limit(s, Point::Finite(e!(ct)), Direction::Minus) {
+        println!("{}", sys.str(a));
+    }
+
     // let v = [c!(PI / 4.0)];
     // printc(sys.eval(y, v));
     // printc(sys.eval(dy, v));
@@ -3,14 +3,14 @@ use std::{
     ops::{Add, Div, Index, IndexMut, Mul, Sub},
 };
 
-use crate::{c, e, Expressable, Expression, Var};
+use crate::{c, e, Expressable, Expression, Point, Scalar, Var};
 
 #[derive(Debug, Clone)]
-pub struct SqMatrix<const N: usize>(pub [[Expression; N]; N]);
+pub struct SqMatrix<const N: usize, T = Expression>(pub [[T; N]; N]);
 
-impl<const N: usize> SqMatrix<N> {
-    pub fn tr(&self) -> Expression {
-        let mut sum = c!().into();
+impl<const N: usize, T: Scalar> SqMatrix<N, T> {
+    pub fn tr(&self) -> T {
+        let mut sum = T::constant(c!());
         for i in 0..N {
             sum = sum + self.0[i][i].clone();
         }
@@ -18,22 +18,22 @@ impl<const N: usize> SqMatrix<N> {
     }
 
     pub fn identity() -> Self {
-        let mut m: [[Expression; N]; N] = array::from_fn(|_| array::from_fn(|_| c!().into()));
+        let mut m: [[T; N]; N] = array::from_fn(|_| array::from_fn(|_| T::constant(c!())));
         for (i, v) in m.iter_mut().enumerate() {
-            v[i] = c!(+).into();
+            v[i] = T::constant(c!(+));
         }
         SqMatrix(m)
     }
 
     pub fn zeroes() -> Self {
-        SqMatrix(array::from_fn(|_| array::from_fn(|_| c!().into())))
+        SqMatrix(array::from_fn(|_| array::from_fn(|_| T::constant(c!()))))
     }
 
     pub fn diff(&self, x: Var) -> Self {
         let mut new = Self::zeroes();
         for i in 0..N {
             for j in 0..N {
-                new.0[i][j] = self.0[i][j].clone().diff(x);
+                new.0[i][j] = self.0[i][j].diff(x);
             }
         }
         new
@@ -43,26 +43,39 @@ impl<const N: usize> SqMatrix<N> {
         let mut new = Self::zeroes();
         for i in 0..N {
             for j in 0..N {
-                new.0[i][j] = self.0[i][j].clone().simplify();
+                new.0[i][j] = self.0[i][j].simplify();
             }
         }
         new
     }
 
+    pub fn transpose(&self) -> Self {
+        let mut trans = Self::zeroes();
+        for i in 0..N {
+            for j in 0..N {
+                trans[i][j] = self[j][i].clone();
+            }
+        }
+        trans
+    }
+
     pub fn pow(&self, n: usize) -> Self {
         (0..n - 1)
             .map(|_| self.clone())
             .fold(self.clone(), Mul::mul)
     }
+}
 
-    pub fn transpose(&self) -> Self {
-        let mut trans = Self::zeroes();
+impl<const N: usize> SqMatrix<N> {
+    /// Truncates every entry to its series in `x` about `point`, dropping the remainder
+    pub fn series(&self, x: Var, point: Point, order: i64) -> Result<Self, String> {
+        let mut new = Self::zeroes();
         for i in 0..N {
             for j in 0..N {
-                trans[i][j] = self[j][i].clone();
+                new.0[i][j] = self.0[i][j].clone().series(x, point.clone(), order)?.into();
             }
         }
-        trans
+        Ok(new)
     }
 
     fn get_cofactor(&self, temp: &mut Self, p: usize, q: usize, n: usize) {
@@ -124,18 +137,47 @@ impl<const N: usize> SqMatrix<N> {
         self.adj().transpose()
     }
 
+    /// Inverse by Gauss–Jordan elimination, pivoting on nonzero constants where possible and
+    /// otherwise on entries that do not simplify to zero. Only structural zeros are skipped in
+    /// the elimination, which keeps sparse matrices such as diagonal metrics from expanding
+    /// into cofactors.
     pub fn inv(&self) -> Self {
-        self.adj() / self.det()
+        let mut a = self.simplify();
+        let mut inv = Self::identity();
+        for k in 0..N {
+            let constant = (k..N).find(|&i| a[i][k].as_const().is_some_and(|c| c != c!()));
+            let Some(p) = constant.or_else(|| (k..N).find(|&i| !a[i][k].is_zero())) else {
+                return self.adj() / self.det();
+            };
+            a.0.swap(k, p);
+            inv.0.swap(k, p);
+            let pivot = a[k][k].clone();
+            for j in 0..N {
+                a[k][j] = (a[k][j].clone() / pivot.clone()).simplify();
+                inv[k][j] = (inv[k][j].clone() / pivot.clone()).simplify();
+            }
+            for i in (0..N).filter(|&i| i != k) {
+                let r = a[i][k].clone();
+                if r.as_const() == Some(c!()) {
+                    continue;
+                }
+                for j in 0..N {
+                    a[i][j] = (a[i][j].clone() - r.clone() * a[k][j].clone()).simplify();
+                    inv[i][j] = (inv[i][j].clone() - r.clone() * inv[k][j].clone()).simplify();
+                }
+            }
+        }
+        inv
     }
 }
 
-impl<const N: usize> Mul for SqMatrix<N> {
-    type Output = SqMatrix<N>;
+impl<const N: usize, T: Scalar> Mul for SqMatrix<N, T> {
+    type Output = SqMatrix<N, T>;
     fn mul(self, rhs: Self) -> Self::Output {
-        let mut c = std::array::from_fn(|_| std::array::from_fn(|_| c!().into()));
+        let mut c = std::array::from_fn(|_| std::array::from_fn(|_| T::constant(c!())));
         for (i, c) in c.iter_mut().enumerate() {
             for (j, c) in c.iter_mut().enumerate() {
-                let mut sum = c!().into();
+                let mut sum = T::constant(c!());
                 for k in 0..N {
                     sum = sum + self.0[i][k].clone() * rhs.0[k][j].clone();
                 }
@@ -146,10 +188,10 @@ impl<const N: usize> Mul for SqMatrix<N> {
     }
 }
 
-impl<const N: usize> Add for SqMatrix<N> {
-    type Output = SqMatrix<N>;
+impl<const N: usize, T: Scalar> Add for SqMatrix<N, T> {
+    type Output = SqMatrix<N, T>;
     fn add(self, rhs: Self) -> Self::Output {
-        let mut c = std::array::from_fn(|_| std::array::from_fn(|_| c!().into()));
+        let mut c = std::array::from_fn(|_| std::array::from_fn(|_| T::constant(c!())));
         for (i, c) in c.iter_mut().enumerate() {
             for (j, c) in c.iter_mut().enumerate() {
                 *c = self.0[i][j].clone() + rhs.0[i][j].clone();
@@ -159,10 +201,10 @@ impl<const N: usize> Add for SqMatrix<N> {
     }
 }
 
-impl<const N: usize> Sub for SqMatrix<N> {
-    type Output = SqMatrix<N>;
+impl<const N: usize, T: Scalar> Sub for SqMatrix<N, T> {
+    type Output = SqMatrix<N, T>;
     fn sub(self, rhs: Self) -> Self::Output {
-        let mut c = std::array::from_fn(|_| std::array::from_fn(|_| c!().into()));
+        let mut c = std::array::from_fn(|_| std::array::from_fn(|_| T::constant(c!())));
         for (i, c) in c.iter_mut().enumerate() {
             for (j, c) in c.iter_mut().enumerate() {
                 *c = self.0[i][j].clone() - rhs.0[i][j].clone();
@@ -204,14 +246,14 @@ where
     }
 }
 
-impl<const N: usize> Index<usize> for SqMatrix<N> {
-    type Output = [Expression; N];
+impl<const N: usize, T> Index<usize> for SqMatrix<N, T> {
+    type Output = [T; N];
     fn index(&self, index: usize) -> &Self::Output {
         &self.0[index]
     }
 }
 
-impl<const N: usize> IndexMut<usize> for SqMatrix<N> {
+impl<const N: usize, T> IndexMut<usize> for SqMatrix<N, T> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         &mut self.0[index]
     }
@@ -1,3 +1,535 @@
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
+
+use crate::{c, e, Expressable, Expression, SqMatrix, Var};
+
+/// Position of a tensor index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variance {
+    Up,
+    Down,
+}
+
+/// Tensor in `N` dimensions with a variance for each of its indices, stored as all `N^rank`
+/// components with the last index varying fastest
+#[derive(Debug, Clone)]
 pub struct Tensor<const N: usize> {
+    variance: Vec<Variance>,
+    components: Vec<Expression>,
+}
+
+impl<const N: usize> Tensor<N> {
+    /// Tensor with the components `f(indices)`
+    pub fn from_fn(variance: Vec<Variance>, mut f: impl FnMut(&[usize]) -> Expression) -> Self {
+        let components = indices::<N>(variance.len()).map(|i| f(&i)).collect();
+        Tensor {
+            variance,
+            components,
+        }
+    }
+
+    pub fn zeroes(variance: Vec<Variance>) -> Self {
+        Self::from_fn(variance, |_| e!(c!()))
+    }
+
+    pub fn scalar<T: Clone>(value: Expressable<T>) -> Self
+    where
+        Expression: From<Expressable<T>>,
+    {
+        Tensor {
+            variance: Vec::new(),
+            components: vec![value.into()],
+        }
+    }
+
+    /// Rank 2 tensor with the components of `m`
+    pub fn matrix(m: &[[Expression; N]; N], variance: [Variance; 2]) -> Self {
+        Self::from_fn(variance.to_vec(), |i| m[i[0]][i[1]].clone())
+    }
+
+    pub fn rank(&self) -> usize {
+        self.variance.len()
+    }
+
+    pub fn variance(&self) -> &[Variance] {
+        &self.variance
+    }
+
+    /// Components with their indices
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<usize>, &Expression)> {
+        indices::<N>(self.rank()).zip(&self.components)
+    }
+
+    #[track_caller]
+    fn offset(&self, index: &[usize]) -> usize {
+        assert!(
+            index.len() == self.rank(),
+            "Inadequate amount of indices, expected {} got {}",
+            self.rank(),
+            index.len()
+        );
+        index.iter().fold(0, |offset, &i| {
+            assert!(i < N, "Index {i} out of range for {N} dimensions");
+            offset * N + i
+        })
+    }
+
+    pub fn simplify(&self) -> Self {
+        self.map(|c| c.clone().simplify())
+    }
+
+    /// Componentwise partial derivative, not a tensor unless the components are constant
+    /// along the coordinates
+    pub fn diff(&self, x: Var) -> Self {
+        self.map(|c| c.clone().diff(x))
+    }
+
+    fn map(&self, f: impl FnMut(&Expression) -> Expression) -> Self {
+        Tensor {
+            variance: self.variance.clone(),
+            components: self.components.iter().map(f).collect(),
+        }
+    }
+
+    /// Tensor product, the indices of `self` followed by those of `other`
+    pub fn outer(&self, other: &Self) -> Self {
+        let variance = [self.variance(), other.variance()].concat();
+        let components = self
+            .components
+            .iter()
+            .flat_map(|a| other.components.iter().map(|b| a.clone() * b.clone()))
+            .collect();
+        Tensor {
+            variance,
+            components,
+        }
+    }
+
+    /// Sum over the indices `a` and `b`, one of them up and the other down
+    #[track_caller]
+    pub fn contract(&self, a: usize, b: usize) -> Self {
+        assert!(
+            a != b && a < self.rank() && b < self.rank(),
+            "Cannot contract indices {a} and {b} of a rank {} tensor",
+            self.rank()
+        );
+        assert!(
+            self.variance[a] != self.variance[b],
+            "Cannot contract indices {a} and {b} of the same variance"
+        );
+        let variance = (0..self.rank())
+            .filter(|&i| i != a && i != b)
+            .map(|i| self.variance[i])
+            .collect();
+        Self::from_fn(variance, |rest| {
+            let mut index = rest.to_vec();
+            let (first, second) = (a.min(b), a.max(b));
+            index.insert(first, 0);
+            index.insert(second, 0);
+            let mut sum = e!(c!());
+            for k in 0..N {
+                index[a] = k;
+                index[b] = k;
+                sum = sum + self[&index[..]].clone();
+            }
+            sum
+        })
+    }
+
+    /// Index `index` contracted with the first index of `m`, taking it to `variance`
+    pub(crate) fn transform(&self, index: usize, m: &SqMatrix<N>, variance: Variance) -> Self {
+        let mut new = self.variance.clone();
+        new[index] = variance;
+        Self::from_fn(new, |i| {
+            let mut j = i.to_vec();
+            let mut sum = e!(c!());
+            for k in 0..N {
+                j[index] = k;
+                sum = sum + m[i[index]][k].clone() * self[&j[..]].clone();
+            }
+            sum
+        })
+    }
+
+    /// Raises the lower index `index` with the inverse metric
+    #[track_caller]
+    pub fn raise(&self, index: usize, g_inv: &SqMatrix<N>) -> Self {
+        assert!(
+            self.variance.get(index) == Some(&Variance::Down),
+            "Index {index} is not a lower index"
+        );
+        self.transform(index, g_inv, Variance::Up)
+    }
+
+    /// Lowers the upper index `index` with the metric
+    #[track_caller]
+    pub fn lower(&self, index: usize, g: &SqMatrix<N>) -> Self {
+        assert!(
+            self.variance.get(index) == Some(&Variance::Up),
+            "Index {index} is not an upper index"
+        );
+        self.transform(index, g, Variance::Down)
+    }
+
+    /// Average over the permutations of the indices in `slots`, with the sign of each
+    /// permutation when `antisymmetric`
+    #[track_caller]
+    fn average(&self, slots: &[usize], antisymmetric: bool) -> Self {
+        assert!(
+            slots.iter().all(|&s| s < self.rank()),
+            "Slots {slots:?} out of range for a rank {} tensor",
+            self.rank()
+        );
+        assert!(
+            slots
+                .iter()
+                .all(|&s| self.variance[s] == self.variance[slots[0]]),
+            "Cannot mix upper and lower indices in {slots:?}"
+        );
+        let perms = permutations(slots.len());
+        let weight = 1.0 / perms.len() as f64;
+        Self::from_fn(self.variance.clone(), |i| {
+            let mut sum = e!(c!());
+            for (perm, odd) in &perms {
+                let mut j = i.to_vec();
+                for (k, &p) in perm.iter().enumerate() {
+                    j[slots[k]] = i[slots[p]];
+                }
+                let sign = match antisymmetric && *odd {
+                    true => -weight,
+                    false => weight,
+                };
+                sum = sum + e!(sign) * self[&j[..]].clone();
+            }
+            sum
+        })
+    }
+
+    /// Symmetric part in the indices `slots`
+    #[track_caller]
+    pub fn symmetrize(&self, slots: &[usize]) -> Self {
+        self.average(slots, false)
+    }
+
+    /// Antisymmetric part in the indices `slots`
+    #[track_caller]
+    pub fn antisymmetrize(&self, slots: &[usize]) -> Self {
+        self.average(slots, true)
+    }
+
+    /// Same tensor with its indices reordered, index `k` of the result being index
+    /// `order[k]` of `self`
+    #[track_caller]
+    pub fn permute(&self, order: &[usize]) -> Self {
+        let mut sorted = order.to_vec();
+        sorted.sort();
+        assert!(
+            sorted == (0..self.rank()).collect::<Vec<_>>(),
+            "{order:?} is not a permutation of the {} indices",
+            self.rank()
+        );
+        let variance = order.iter().map(|&k| self.variance[k]).collect();
+        Self::from_fn(variance, |i| {
+            let mut j = vec![0; i.len()];
+            for (k, &o) in order.iter().enumerate() {
+                j[o] = i[k];
+            }
+            self[&j[..]].clone()
+        })
+    }
+
+    /// Tensor labelled for Einstein summation, `labels` giving each index as a character
+    /// after `^` for upper and `_` for lower indices, like `"^a_bc"`
+    ///
+    /// A method rather than `t["^a_bc"]`, as `Index` has to return a reference into `self`
+    /// and cannot hand out the new, relabelled and contracted tensor.
+    #[track_caller]
+    pub fn ix(&self, labels: &str) -> Indexed<N> {
+        let mut variance = None;
+        let mut parsed = Vec::new();
+        for l in labels.chars().filter(|l| !l.is_whitespace()) {
+            match l {
+                '^' => variance = Some(Variance::Up),
+                '_' => variance = Some(Variance::Down),
+                l => {
+                    let Some(v) = variance else {
+                        panic!("Index {l} in {labels:?} is neither upper nor lower");
+                    };
+                    parsed.push((l, v));
+                }
+            }
+        }
+        assert!(
+            parsed.len() == self.rank(),
+            "Inadequate amount of indices, expected {} got {}",
+            self.rank(),
+            parsed.len()
+        );
+        for (k, &(l, v)) in parsed.iter().enumerate() {
+            assert!(
+                v == self.variance[k],
+                "Index {l} has the wrong variance, expected {:?}",
+                self.variance[k]
+            );
+        }
+        Indexed::new(self.clone(), parsed.into_iter().map(|(l, _)| l).collect())
+    }
+
+    #[track_caller]
+    fn zip(&self, other: &Self, f: impl Fn(&Expression, &Expression) -> Expression) -> Self {
+        assert!(
+            self.variance == other.variance,
+            "Mismatched variances, {:?} and {:?}",
+            self.variance,
+            other.variance
+        );
+        Tensor {
+            variance: self.variance.clone(),
+            components: self
+                .components
+                .iter()
+                .zip(&other.components)
+                .map(|(a, b)| f(a, b))
+                .collect(),
+        }
+    }
+}
+
+/// All index tuples of a rank `rank` tensor, in storage order
+fn indices<const N: usize>(rank: usize) -> impl Iterator<Item = Vec<usize>> {
+    (0..N.pow(rank as u32)).map(move |mut offset| {
+        let mut index = vec![0; rank];
+        for i in index.iter_mut().rev() {
+            *i = offset % N;
+            offset /= N;
+        }
+        index
+    })
+}
+
+/// Permutations of `0..n`, each with whether it is odd
+pub(crate) fn permutations(n: usize) -> Vec<(Vec<usize>, bool)> {
+    match n {
+        0 => vec![(Vec::new(), false)],
+        _ => permutations(n - 1)
+            .into_iter()
+            .flat_map(|(perm, odd)| {
+                // Inserting `n - 1` at position `k` takes `n - 1 - k` transpositions
+                (0..n).map(move |k| {
+                    let mut p = perm.clone();
+                    p.insert(k, n - 1);
+                    (p, odd ^ ((n - 1 - k) % 2 == 1))
+                })
+            })
+            .collect(),
+    }
+}
+
+impl<const N: usize> Index<&[usize]> for Tensor<N> {
+    type Output = Expression;
+
+    #[track_caller]
+    fn index(&self, index: &[usize]) -> &Expression {
+        &self.components[self.offset(index)]
+    }
+}
+
+impl<const N: usize> IndexMut<&[usize]> for Tensor<N> {
+    #[track_caller]
+    fn index_mut(&mut self, index: &[usize]) -> &mut Expression {
+        let offset = self.offset(index);
+        &mut self.components[offset]
+    }
+}
+
+impl<const N: usize, const K: usize> Index<[usize; K]> for Tensor<N> {
+    type Output = Expression;
+
+    #[track_caller]
+    fn index(&self, index: [usize; K]) -> &Expression {
+        &self[&index[..]]
+    }
+}
+
+impl<const N: usize, const K: usize> IndexMut<[usize; K]> for Tensor<N> {
+    #[track_caller]
+    fn index_mut(&mut self, index: [usize; K]) -> &mut Expression {
+        &mut self[&index[..]]
+    }
+}
+
+impl<const N: usize> Add for Tensor<N> {
+    type Output = Tensor<N>;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip(&rhs, |a, b| a.clone() + b.clone())
+    }
+}
+
+impl<const N: usize> Sub for Tensor<N> {
+    type Output = Tensor<N>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip(&rhs, |a, b| a.clone() - b.clone())
+    }
+}
+
+impl<const N: usize> Neg for Tensor<N> {
+    type Output = Tensor<N>;
+    fn neg(self) -> Self::Output {
+        self.map(|a| -a.clone())
+    }
+}
+
+impl<const N: usize, T: Clone> Mul<Expressable<T>> for Tensor<N>
+where
+    Expression: From<Expressable<T>>,
+{
+    type Output = Tensor<N>;
+    fn mul(self, rhs: Expressable<T>) -> Self::Output {
+        scale(&self, rhs.into())
+    }
+}
+
+fn scale<const N: usize>(t: &Tensor<N>, k: Expression) -> Tensor<N> {
+    t.map(|a| a.clone() * k.clone())
+}
+
+/// Tensor with a label on each index, multiplied with implicit summation over repeated labels
+#[derive(Debug, Clone)]
+pub struct Indexed<const N: usize> {
+    tensor: Tensor<N>,
+    labels: Vec<char>,
+}
+
+impl<const N: usize> Indexed<N> {
+    /// Contracts every label that appears twice
+    #[track_caller]
+    fn new(mut tensor: Tensor<N>, mut labels: Vec<char>) -> Self {
+        while let Some((a, b)) = (0..labels.len()).find_map(|a| {
+            Some((
+                a,
+                a + 1 + labels[a + 1..].iter().position(|&l| l == labels[a])?,
+            ))
+        }) {
+            assert!(
+                !labels[b + 1..].contains(&labels[a]),
+                "Index {} appears more than twice",
+                labels[a]
+            );
+            assert!(
+                tensor.variance[a] != tensor.variance[b],
+                "Index {} is summed over with the same variance twice",
+                labels[a]
+            );
+            tensor = tensor.contract(a, b);
+            labels.remove(b);
+            labels.remove(a);
+        }
+        Indexed { tensor, labels }
+    }
+
+    /// Free labels, in the order of the indices
+    pub fn labels(&self) -> &[char] {
+        &self.labels
+    }
+
+    /// The tensor with its free indices in the order of `labels`
+    #[track_caller]
+    pub fn to(&self, labels: &str) -> Tensor<N> {
+        let order = self.order(&labels.chars().collect::<Vec<_>>());
+        self.tensor.permute(&order)
+    }
+
+    /// Positions in `self` of the labels `labels`
+    #[track_caller]
+    fn order(&self, labels: &[char]) -> Vec<usize> {
+        assert!(
+            labels.len() == self.labels.len(),
+            "Mismatched indices, {:?} and {:?}",
+            labels,
+            self.labels
+        );
+        labels
+            .iter()
+            .map(|l| {
+                self.labels
+                    .iter()
+                    .position(|m| m == l)
+                    .unwrap_or_else(|| panic!("Index {l} is not free in {:?}", self.labels))
+            })
+            .collect()
+    }
+
+    pub fn tensor(self) -> Tensor<N> {
+        self.tensor
+    }
+}
+
+impl<const N: usize> Mul for Indexed<N> {
+    type Output = Indexed<N>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let labels = [self.labels, rhs.labels].concat();
+        Indexed::new(self.tensor.outer(&rhs.tensor), labels)
+    }
+}
+
+impl<const N: usize> Add for Indexed<N> {
+    type Output = Indexed<N>;
+    fn add(self, rhs: Self) -> Self::Output {
+        let rhs = rhs.tensor.permute(&rhs.order(&self.labels));
+        Indexed {
+            tensor: self.tensor + rhs,
+            labels: self.labels,
+        }
+    }
+}
+
+impl<const N: usize> Sub for Indexed<N> {
+    type Output = Indexed<N>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        let rhs = rhs.tensor.permute(&rhs.order(&self.labels));
+        Indexed {
+            tensor: self.tensor - rhs,
+            labels: self.labels,
+        }
+    }
+}
+
+/// Product of `tensors` summed over repeated indices, after `spec` of the form `"ab,b->a"`
+/// naming the indices of each tensor and those of the result. The variances come from the
+/// tensors, a summed index having to be upper on one side and lower on the other.
+#[track_caller]
+pub fn einsum<const N: usize>(spec: &str, tensors: &[&Tensor<N>]) -> Tensor<N> {
+    let (inputs, output) = spec
+        .split_once("->")
+        .unwrap_or_else(|| panic!("Missing result indices in {spec:?}"));
+    let inputs = inputs.split(',').map(str::trim).collect::<Vec<_>>();
+    assert!(
+        inputs.len() == tensors.len(),
+        "Inadequate amount of tensors, expected {} got {}",
+        inputs.len(),
+        tensors.len()
+    );
+    let product = inputs
+        .iter()
+        .zip(tensors)
+        .map(|(labels, &t)| {
+            let labels = labels.chars().collect::<Vec<_>>();
+            assert!(
+                labels.len() == t.rank(),
+                "Inadequate amount of indices, expected {} got {}",
+                t.rank(),
+                labels.len()
+            );
+            Indexed::new(t.clone(), labels)
+        })
+        .reduce(|a, b| a * b)
+        .unwrap_or_else(|| Indexed::new(Tensor::scalar(e!(c!(+))), Vec::new()));
+    product.to(output.trim())
+}
 
-}
\ No newline at end of file
+/// `einsum!("ab,b->a", t, v)` for `einsum("ab,b->a", &[&t, &v])`
+#[macro_export]
+macro_rules! einsum {
+    ($spec:expr, $($t:expr),+ $(,)?) => {
+        $crate::einsum($spec, &[$(&$t),+])
+    };
+}
@@ -0,0 +1,55 @@
+use symrs::*;
+
+/// Checks `d` against `expected` within `tol` and within a few times its reported error
+#[track_caller]
+fn assert_close(sys: &System, d: Definite, expected: f64, tol: f64) {
+    let value = sys.eval(d.value, [c!(); 3]);
+    let err = (value - c!(expected)).norm();
+    assert!(err <= tol, "{value} != {expected}");
+    assert!(
+        err <= 5.0 * d.error + 1e-12,
+        "{value} != {expected}, error {}",
+        d.error
+    );
+}
+
+fn setup() -> (System, [Var; 3]) {
+    let mut sys = System::default();
+    let x = sys.symbols("x y z").unwrap();
+    (sys, x)
+}
+
+#[test]
+fn unit_box() {
+    let (sys, [x, y, _]) = setup();
+    let d = (e!(x) * e!(y))
+        .cubature([x, y], Region::Box([0.0, 0.0], [1.0, 1.0]))
+        .unwrap();
+    assert_close(&sys, d, 0.25, 1e-10);
+}
+
+#[test]
+fn triangle() {
+    let (sys, [x, y, _]) = setup();
+    let triangle = || Region::Simplex(vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+    let d = e!(1.0).cubature([x, y], triangle()).unwrap();
+    assert_close(&sys, d, 0.5, 1e-10);
+    // `∫∫ x dA` over the triangle is `1/6`
+    let d = e!(x).cubature([x, y], triangle()).unwrap();
+    assert_close(&sys, d, 1.0 / 6.0, 1e-10);
+}
+
+#[test]
+fn gaussian() {
+    let (sys, x) = setup();
+    let r2 = x.iter().fold(e!(0.0), |r2, &x| r2 + e!(x) * e!(x));
+    let f = (-r2).exp();
+    // `π^(3/2)` up to the tails beyond the box, `erf(4)³ = 1 - 4.6e-8`
+    let expected = std::f64::consts::PI.powf(1.5) * (1.0 - 4.6e-8);
+    let region = || Region::Box([-4.0; 3], [4.0; 3]);
+    let d = f.clone().cubature(x, region()).unwrap();
+    assert_close(&sys, d, expected, 1e-6);
+    let d = f.quasi_monte_carlo(x, region(), 1 << 14).unwrap();
+    assert!(d.error > 0.0);
+    assert_close(&sys, d, expected, 2e-2);
+}
@@ -0,0 +1,90 @@
+use symrs::*;
+
+fn setup() -> (System, [Var; 3], Func, Func) {
+    let mut sys = System::default();
+    let x = sys.symbols("x y t").unwrap();
+    let f = sys.function("f", 1).unwrap();
+    let g = sys.function("g", 2).unwrap();
+    (sys, x, f, g)
+}
+
+/// Compares `a` and `b`, free of undefined functions, at a few points
+#[track_caller]
+fn assert_equal(sys: &System, a: Expression, b: Expression) {
+    for p in [0.3, 0.9, 1.7] {
+        let x = [c!(p), c!(p + 0.5), c!(2.0 * p)];
+        let (va, vb) = (sys.eval(a.clone(), x), sys.eval(b.clone(), x));
+        assert!(
+            (va - vb).norm() <= 1e-9 * va.norm().max(1.0),
+            "{va} != {vb} at {p}"
+        );
+    }
+}
+
+#[test]
+fn declaring_and_calling() {
+    let (mut sys, [x, y, _], f, g) = setup();
+    assert_eq!((f.arity(), g.arity()), (1, 2));
+    assert!(sys.function("f", 2).is_err());
+    assert_eq!(sys.str(g.call([e!(x), e!(y)])), "g(x,y)");
+}
+
+#[test]
+#[should_panic(expected = "Inadequate amount of arguments, expected 2 got 1")]
+fn call_checks_arity() {
+    let (_, [x, ..], _, g) = setup();
+    g.call([e!(x)]);
+}
+
+#[test]
+fn derivative_nodes() {
+    let (sys, [x, y, _], f, g) = setup();
+    let fx = || f.call([e!(x)]);
+    assert_eq!(sys.str(fx().diff(x).simplify()), "df(x)/dx");
+    let gxy = || g.call([e!(x), e!(y)]);
+    let mixed = sys.str(gxy().diff(x).diff(y).simplify());
+    assert_eq!(mixed, "∂^2g(x,y)/∂x∂y");
+    assert_eq!(sys.str(gxy().diff(y).diff(x).simplify()), mixed);
+    assert_eq!(sys.str(f.call([e!(y)]).diff(x)), sys.str(e!(0.0)));
+}
+
+#[test]
+fn chain_rule_through_dummies() {
+    let (sys, [x, ..], f, g) = setup();
+    let d = f.call([e!(x) * e!(x)]).diff(x).simplify();
+    assert!(sys.str(d.clone()).starts_with(" df(ξ1)/dξ1|ξ1="));
+    assert!(sys.strprime(d).starts_with(" f'("));
+    // Both slots of `g(x, x)` depend on `x`, so each derivative goes through its own dummy
+    let d = sys.str(g.call([e!(x), e!(x)]).diff(x).simplify());
+    assert!(d.contains("∂g(ξ1,x)/∂ξ1|ξ1=x") && d.contains("∂g(x,ξ2)/∂ξ2|ξ2=x"));
+}
+
+#[test]
+fn prime_notation() {
+    let (sys, [x, ..], f, _) = setup();
+    let d = |n: usize| {
+        let d = (0..n).fold(f.call([e!(x)]), |d, _| d.diff(x));
+        sys.strprime(d.simplify())
+    };
+    assert_eq!(d(1), "f'(x)");
+    assert_eq!(d(3), "f'''(x)");
+    assert_eq!(d(4), "f^(4)(x)");
+}
+
+#[test]
+fn doit_and_subs_func() {
+    let (sys, [x, y, t], f, g) = setup();
+    let d = e!(x).pow(e!(3.0)).derivative([(x, 2)]);
+    assert!(sys.str(d.clone()).starts_with("∂^2("));
+    assert_equal(&sys, d.doit(), e!(6.0) * e!(x));
+    let d = f.call([e!(x)]).derivative([(x, 1)]).doit();
+    assert_eq!(sys.str(d), "df(x)/dx");
+
+    let d = f.call([e!(x) * e!(x)]).diff(x);
+    let d = d.subs_func(f, [t], sin(e!(t))).doit();
+    assert_equal(&sys, d, e!(2.0) * e!(x) * cos(e!(x) * e!(x)));
+    // Arguments naming the other parameter are not substituted twice
+    let body = e!(x) - e!(2.0) * e!(y);
+    let swapped = g.call([e!(y), e!(x)]).subs_func(g, [x, y], body);
+    assert_equal(&sys, swapped, e!(y) - e!(2.0) * e!(x));
+}
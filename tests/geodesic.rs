@@ -0,0 +1,128 @@
+use symrs::{gr::*, *};
+
+const ADAPTIVE: Method = Method::Rk45 {
+    rtol: 1e-10,
+    atol: 1e-12,
+};
+
+#[track_caller]
+fn assert_close(a: f64, b: f64, tol: f64) {
+    assert!((a - b).abs() <= tol, "{a} != {b}");
+}
+
+#[test]
+fn straight_lines_in_minkowski() {
+    let mut sys = System::default();
+    let (g, x) = minkowski(&mut sys).unwrap();
+    let [tau] = sys.symbols("τ").unwrap();
+    let v = sys.symbols("ṫ ẋ ẏ ż").unwrap();
+    let m = Metric::new(g, x);
+    let v0 = [1.25, 0.75, 0.0, 0.0];
+    let path = m
+        .geodesic(tau, v, ADAPTIVE, (0.0, 4.0), [0.0; 4], v0)
+        .unwrap();
+    let (x1, v1) = (path.x.last().unwrap(), path.v.last().unwrap());
+    assert_close(x1[0], 5.0, 1e-9);
+    assert_close(x1[1], 3.0, 1e-9);
+    assert_eq!(*v1, v0);
+    assert_close(path.norm[0], -1.0, 1e-12);
+    assert!(path.norm_drift() < 1e-12);
+}
+
+#[test]
+fn circular_orbit_in_schwarzschild() {
+    let mut sys = System::default();
+    let (g, x) = schwarzschild(&mut sys, e!(1.0)).unwrap();
+    let [tau] = sys.symbols("τ").unwrap();
+    let v = sys.symbols("ṫ ṙ θ̇ φ̇").unwrap();
+    let m = Metric::new(g, x);
+    // `u^t = 1/√(1 - 3M/r)` and `dφ/dt = √(M/r³)` at `r = 10`
+    let r: f64 = 10.0;
+    let ut = (1.0 - 3.0 / r).powf(-0.5);
+    let omega = r.powf(-1.5);
+    let x0 = [0.0, r, std::f64::consts::FRAC_PI_2, 0.0];
+    let v0 = [ut, 0.0, 0.0, omega * ut];
+    let path = m.geodesic(tau, v, ADAPTIVE, (0.0, 100.0), x0, v0).unwrap();
+    for x in &path.x {
+        assert_close(x[1], r, 1e-7);
+        assert_close(x[3], omega * x[0], 1e-7);
+    }
+    assert_close(path.x.last().unwrap()[0], 100.0 * ut, 1e-7);
+    assert_close(path.norm[0], -1.0, 1e-12);
+    assert!(path.norm_drift() < 1e-8, "{}", path.norm_drift());
+
+    let csv = path.csv(&sys);
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("tau,t,r,θ,φ,ṫ,ṙ,θ̇,φ̇,norm"));
+    assert_eq!(lines.count(), path.tau.len());
+}
+
+#[test]
+fn geodesic_equations_of_the_sphere() {
+    let mut sys = System::default();
+    let (g, x) = sphere::<2>(&mut sys, e!(1.0)).unwrap();
+    let [th, _] = x;
+    let v = sys.symbols("θ̇ φ̇").unwrap();
+    let m = Metric::new(g, x);
+    // `θ̈ = sin θ cos θ φ̇²`, `φ̈ = -2 cot θ θ̇ φ̇`
+    let [a, b] = m.geodesic_equations(v);
+    let expected_a = sin(th) * cos(th) * e!(v[1]).pow(2.0);
+    let expected_b = e!(-2.0) * cot(th) * e!(v[0]) * e!(v[1]);
+    for p in [[0.4, 0.1, 0.3, 0.7], [1.3, 2.0, -1.0, 0.5]] {
+        let p = p.map(|p| c!(p));
+        let da = sys.eval(a.clone() - expected_a.clone(), p).norm();
+        let db = sys.eval(b.clone() - expected_b.clone(), p).norm();
+        assert!(da < 1e-12 && db < 1e-12, "{da:e} {db:e}");
+    }
+}
+
+fn diag<const N: usize>(entries: [Expression; N]) -> SqMatrix<N> {
+    let mut g = SqMatrix::<N>::zeroes();
+    for (i, e) in entries.into_iter().enumerate() {
+        g[i][i] = e;
+    }
+    g
+}
+
+#[test]
+fn straight_line_in_polar_coordinates() {
+    // The plane as `dr² + r² dφ²`, built by hand and passed to the free functions
+    let mut sys = System::default();
+    let [tau, r, phi] = sys.symbols("τ r φ").unwrap();
+    let v = sys.symbols("ṙ φ̇").unwrap();
+    let x = [r, phi];
+    let g = diag([e!(1.0), e!(r).pow(2.0)]);
+    let g_inv = diag([e!(1.0), e!(r).pow(-2.0)]);
+    let gamma = christoffel(g.clone(), g_inv, x).map(|e| e.simplify());
+
+    // `r̈ = r φ̇²`, `φ̈ = -2 ṙ φ̇ / r`
+    let [a, b] = geodesic_equations(&gamma, v);
+    let expected_a = e!(r) * e!(v[1]).pow(2.0);
+    let expected_b = e!(-2.0) * e!(v[0]) * e!(v[1]) / e!(r);
+    for p in [[0.0, 0.4, 0.1, 0.3, 0.7], [0.0, 1.3, 2.0, -1.0, 0.5]] {
+        let p = p.map(|p| c!(p));
+        let da = sys.eval(a.clone() - expected_a.clone(), p).norm();
+        let db = sys.eval(b.clone() - expected_b.clone(), p).norm();
+        assert!(da < 1e-12 && db < 1e-12, "{da:e} {db:e}");
+    }
+
+    // The line `x = 1`, `y = τ` has `r = √(1 + τ²)` and `φ = atan τ`
+    let path = geodesic(
+        &g,
+        &gamma,
+        tau,
+        x,
+        v,
+        ADAPTIVE,
+        (0.0, 3.0),
+        [1.0, 0.0],
+        [0.0, 1.0],
+    )
+    .unwrap();
+    for (t, x) in path.tau.iter().zip(&path.x) {
+        assert_close(x[0], (1.0 + t * t).sqrt(), 1e-8);
+        assert_close(x[1], t.atan(), 1e-8);
+    }
+    assert_close(path.norm[0], 1.0, 1e-12);
+    assert!(path.norm_drift() < 1e-8, "{}", path.norm_drift());
+}
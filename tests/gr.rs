@@ -0,0 +1,589 @@
+use symrs::{gr::*, *};
+
+/// Compares `a` and `b` at each of the sample points
+fn assert_equal<const N: usize>(sys: &System, a: &Expression, b: &Expression, points: &[[f64; N]]) {
+    let d = a.clone() - b.clone();
+    for p in points {
+        let p = p.map(|p| c!(p));
+        let err = sys.eval(d.clone(), p).norm();
+        let scale = sys.eval(b.clone(), p).norm().max(1.0);
+        assert!(
+            err <= 1e-9 * scale,
+            "{} against {} at {p:?}: {err:e}",
+            sys.str(a.clone()),
+            sys.str(b.clone())
+        );
+    }
+}
+
+fn diag<const N: usize>(d: [Expression; N]) -> SqMatrix<N> {
+    let mut g = SqMatrix::zeroes();
+    for (i, d) in d.into_iter().enumerate() {
+        g[i][i] = d;
+    }
+    g
+}
+
+/// Metric of a static, spherically symmetric spacetime `-f dt² + dr²/f + r² dΩ²`
+fn static_spherical(f: Expression, [t, r, th, ph]: [Var; 4]) -> Metric<4> {
+    let g = diag([
+        -f.clone(),
+        f.inv(),
+        e!(r).pow(2.0),
+        e!(r).pow(2.0) * sin(th).pow(2.0),
+    ]);
+    Metric::new(g, [t, r, th, ph])
+}
+
+#[test]
+fn minkowski_is_flat() {
+    let mut sys = System::default();
+    let x = sys.symbols("t x y z").unwrap();
+    let m = Metric::new(diag([e!(-1.0), e!(1.0), e!(1.0), e!(1.0)]), x);
+    let points = [[0.0, 1.0, 2.0, 3.0]];
+    for (_, r) in m.riemann().iter() {
+        assert_equal(&sys, r, &e!(0.0), &points);
+    }
+    assert_equal(&sys, m.scalar_curvature(), &e!(0.0), &points);
+}
+
+#[test]
+fn schwarzschild_is_ricci_flat() {
+    let mut sys = System::default();
+    let [t, r, th, ph, mass] = sys.symbols("t r θ φ M").unwrap();
+    let f = e!(1.0) - e!(2.0) * e!(mass) / e!(r);
+    let m = static_spherical(f, [t, r, th, ph]);
+    let points = [[0.0, 3.0, 0.4, 0.1, 1.0], [1.0, 7.5, 1.3, 2.0, 0.5]];
+    for ric in m.ricci().iter().flatten() {
+        assert_equal(&sys, ric, &e!(0.0), &points);
+    }
+    assert_equal(&sys, m.scalar_curvature(), &e!(0.0), &points);
+}
+
+#[test]
+fn de_sitter_is_einstein() {
+    let mut sys = System::default();
+    let [t, r, th, ph, l] = sys.symbols("t r θ φ Λ").unwrap();
+    let f = e!(1.0) - e!(l) * e!(r).pow(2.0) / e!(3.0);
+    let m = static_spherical(f, [t, r, th, ph]);
+    let points = [[0.0, 0.5, 0.4, 0.1, 1.0], [1.0, 2.0, 1.3, 2.0, 0.3]];
+    for i in 0..4 {
+        for j in 0..4 {
+            let expected = e!(l) * m.g()[i][j].clone();
+            assert_equal(&sys, &m.ricci()[i][j], &expected, &points);
+        }
+    }
+    assert_equal(&sys, m.scalar_curvature(), &(e!(4.0) * e!(l)), &points);
+}
+
+#[test]
+fn two_sphere() {
+    let mut sys = System::default();
+    let [th, ph, a] = sys.symbols("θ φ a").unwrap();
+    let g = diag([e!(a).pow(2.0), e!(a).pow(2.0) * sin(th).pow(2.0)]);
+    let m = Metric::new(g, [th, ph]);
+    let points = [[0.4, 0.1, 1.0], [1.3, 2.0, 2.5]];
+    assert_equal(
+        &sys,
+        &m.christoffel()[(0, 1, 1)],
+        &(-sin(th) * cos(th)),
+        &points,
+    );
+    assert_equal(&sys, &m.christoffel()[(1, 0, 1)], &cot(th), &points);
+    let expected = e!(2.0) / e!(a).pow(2.0);
+    assert_equal(&sys, m.scalar_curvature(), &expected, &points);
+}
+
+#[test]
+fn flat_flrw() {
+    let mut sys = System::default();
+    let [t, x, y, z, p] = sys.symbols("t x y z p").unwrap();
+    // Power law expansion `a = t^p`
+    let a2 = e!(t).pow(e!(2.0) * e!(p));
+    let m = Metric::new(
+        diag([e!(-1.0), a2.clone(), a2.clone(), a2.clone()]),
+        [t, x, y, z],
+    );
+    let points = [[1.0, 0.0, 0.0, 0.0, 0.5], [2.5, 1.0, -1.0, 0.3, 2.0 / 3.0]];
+    let t2 = e!(t).pow(2.0);
+    // `R_00 = -3 ä/a`, `R_ij = (a ä + 2 ȧ²) δ_ij`, `R = 6 (ä/a + ȧ²/a²)`
+    let r00 = e!(-3.0) * e!(p) * (e!(p) - e!(1.0)) / t2.clone();
+    let rii = a2 * (e!(3.0) * e!(p) * e!(p) - e!(p)) / t2.clone();
+    let r = e!(6.0) * e!(p) * (e!(2.0) * e!(p) - e!(1.0)) / t2;
+    assert_equal(&sys, &m.ricci()[0][0], &r00, &points);
+    for i in 1..4 {
+        assert_equal(&sys, &m.ricci()[i][i], &rii, &points);
+        assert_equal(&sys, &m.ricci()[0][i], &e!(0.0), &points);
+    }
+    assert_equal(&sys, m.scalar_curvature(), &r, &points);
+}
+
+#[test]
+fn metric_is_covariantly_constant() {
+    let mut sys = System::default();
+    let [t, r, th, ph, mass] = sys.symbols("t r θ φ M").unwrap();
+    let f = e!(1.0) - e!(2.0) * e!(mass) / e!(r);
+    let m = static_spherical(f, [t, r, th, ph]);
+    let g = Tensor::matrix(&m.g().0, [Variance::Down, Variance::Down]);
+    let points = [[0.0, 3.0, 0.4, 0.1, 1.0]];
+    for (_, c) in m.covariant_derivative(&g).iter() {
+        assert_equal(&sys, c, &e!(0.0), &points);
+    }
+}
+
+#[test]
+fn einstein_is_divergence_free() {
+    let mut sys = System::default();
+    let [t, r, th, ph, mass, q] = sys.symbols("t r θ φ M Q").unwrap();
+    // Reissner–Nordström, which is not a vacuum solution
+    let f = e!(1.0) - e!(2.0) * e!(mass) / e!(r) + e!(q).pow(2.0) / e!(r).pow(2.0);
+    let m = static_spherical(f, [t, r, th, ph]);
+    let g = Tensor::matrix(m.einstein(), [Variance::Down, Variance::Down]);
+    let points = [
+        [0.0, 3.0, 0.4, 0.1, 1.0, 0.5],
+        [1.0, 7.5, 1.3, 2.0, 0.5, 0.3],
+    ];
+    for (_, c) in m.divergence(&g, 0).iter() {
+        assert_equal(&sys, c, &e!(0.0), &points);
+    }
+
+    let mut sys = System::default();
+    let [t, x, y, z, p] = sys.symbols("t x y z p").unwrap();
+    let a2 = e!(t).pow(e!(2.0) * e!(p));
+    let m = Metric::new(diag([e!(-1.0), a2.clone(), a2.clone(), a2]), [t, x, y, z]);
+    let g = Tensor::matrix(m.einstein(), [Variance::Down, Variance::Down]);
+    let points = [[1.0, 0.0, 0.0, 0.0, 0.5], [2.5, 1.0, -1.0, 0.3, 2.0 / 3.0]];
+    for (_, c) in m.divergence(&g, 0).iter() {
+        assert_equal(&sys, c, &e!(0.0), &points);
+    }
+}
+
+#[test]
+fn tetrad_matches_riemann_tensor() {
+    let mut sys = System::default();
+    let [mass] = sys.symbols("M").unwrap();
+    let (g, x) = schwarzschild(&mut sys, e!(mass)).unwrap();
+    let tetrad = Tetrad::diagonal(&g, [-1.0, 1.0, 1.0, 1.0], x);
+    let m = Metric::new(g, x);
+    let riemann = riemann_tensor(m.g().clone(), m.christoffel(), x);
+    let points = [[1.0, 0.0, 3.0, 0.4, 0.1], [0.5, 1.0, 7.5, 1.3, 2.0]];
+    let coordinate = tetrad.coordinate_riemann();
+    for (i, r) in riemann.iter() {
+        assert_equal(&sys, &coordinate.get(i), r, &points);
+    }
+    // Radial tidal stretching `R_trtr = -2M/r³` in the static frame
+    let [_, r, _, _] = x;
+    let expected = e!(-2.0) * e!(mass) / e!(r).pow(3.0);
+    assert_equal(
+        &sys,
+        &tetrad.riemann().get([0, 1, 0, 1]),
+        &expected,
+        &points,
+    );
+}
+
+#[test]
+fn painleve_gullstrand_tetrad() {
+    let mut sys = System::default();
+    let [t, r, th, ph, mass] = sys.symbols("t r θ φ M").unwrap();
+    // Coframe of observers falling from rest at infinity, `θ^1 = dr + √(2M/r) dt`
+    let mut e = diag([e!(1.0), e!(1.0), e!(r), e!(r) * sin(th)]);
+    e[1][0] = (e!(2.0) * e!(mass) / e!(r)).pow(0.5);
+    let eta = diag([e!(-1.0), e!(1.0), e!(1.0), e!(1.0)]);
+    let x = [t, r, th, ph];
+    let tetrad = Tetrad::new(e, eta, x);
+    let m = tetrad.metric();
+    let riemann = riemann_tensor(m.g().clone(), m.christoffel(), x);
+    let points = [[0.0, 3.0, 0.4, 0.1, 1.0], [1.0, 7.5, 1.3, 2.0, 0.5]];
+    let coordinate = tetrad.coordinate_riemann();
+    for (i, r) in riemann.iter() {
+        assert_equal(&sys, &coordinate.get(i), r, &points);
+    }
+    for t in torsion(&tetrad.theta(), tetrad.connection_forms(), x) {
+        for (_, c) in t.iter() {
+            assert_equal(&sys, c, &e!(0.0), &points);
+        }
+    }
+}
+
+#[test]
+fn curvature_as_string() {
+    let mut sys = System::default();
+    let [th, ph] = sys.symbols("θ φ").unwrap();
+    let g = diag([e!(1.0), sin(th).pow(2.0)]);
+    let m = Metric::new(g, [th, ph]);
+    let f = strcurvature(m.ricci().clone(), &sys);
+    let rows = f.lines().collect::<Vec<_>>();
+    assert_eq!(rows.len(), 2);
+    for (row, ric) in rows.into_iter().zip(m.ricci()) {
+        let expected = ric
+            .iter()
+            .map(|r| format!("{:5}", sys.str(r.clone().simplify())))
+            .collect::<String>();
+        assert_eq!(row, format!("[{expected}]"));
+    }
+}
+
+#[test]
+fn schwarzschild_invariants() {
+    let mut sys = System::default();
+    let [t, r, th, ph, mass] = sys.symbols("t r θ φ M").unwrap();
+    let f = e!(1.0) - e!(2.0) * e!(mass) / e!(r);
+    let m = static_spherical(f, [t, r, th, ph]);
+    let points = [[0.0, 3.0, 0.4, 0.1, 1.0], [1.0, 7.5, 1.3, 2.0, 0.5]];
+    // `48 M²/r⁶`, all of it Weyl curvature in vacuum
+    let expected = e!(48.0) * e!(mass).pow(2.0) / e!(r).pow(6.0);
+    assert_equal(&sys, m.kretschmann(), &expected, &points);
+    assert_equal(&sys, m.weyl_squared(), &expected, &points);
+    assert_equal(&sys, m.ricci_squared(), &e!(0.0), &points);
+    for g in m.einstein().iter().flatten() {
+        assert_equal(&sys, g, &e!(0.0), &points);
+    }
+}
+
+#[test]
+fn de_sitter_einstein_tensor() {
+    let mut sys = System::default();
+    let [l] = sys.symbols("Λ").unwrap();
+    let (g, x) = de_sitter(&mut sys, e!(l)).unwrap();
+    let m = Metric::new(g, x);
+    let points = [[0.3, 0.0, 0.5, 0.4, 0.1], [1.0, 1.0, 2.0, 1.3, 2.0]];
+    // `R_μν = Λ g_μν` and `R = 4Λ` leave `G_μν + Λ g_μν = 0`
+    for i in 0..4 {
+        for j in 0..4 {
+            let sum = m.einstein()[i][j].clone() + e!(l) * m.g()[i][j].clone();
+            assert_equal(&sys, &sum, &e!(0.0), &points);
+        }
+    }
+    // Maximally symmetric, so conformally flat
+    assert_equal(&sys, m.weyl_squared(), &e!(0.0), &points);
+    let expected = e!(8.0) * e!(l).pow(2.0) / e!(3.0);
+    assert_equal(&sys, m.kretschmann(), &expected, &points);
+}
+
+/// Every independent component is read back from its own slot
+fn assert_slots<const N: usize>() {
+    let riemann =
+        RiemannCurvature::<N>::from_fn(|i| e!(i.iter().fold(0.0, |k, &i| k * N as f64 + i as f64)));
+    let sys = System::default();
+    let mut count = 0;
+    for (i, r) in riemann.iter() {
+        assert_eq!(sys.str(riemann.get(i)), sys.str(r.clone()), "{i:?}");
+        count += 1;
+    }
+    assert_eq!(count, N * N * (N * N - 1) / 12);
+}
+
+#[test]
+fn riemann_slots() {
+    assert_slots::<2>();
+    assert_slots::<3>();
+    assert_slots::<4>();
+    assert_slots::<5>();
+    assert_slots::<6>();
+}
+
+#[test]
+fn schwarzschild_killing_vectors() {
+    let mut sys = System::default();
+    let [mass] = sys.symbols("M").unwrap();
+    let (g, x) = schwarzschild(&mut sys, e!(mass)).unwrap();
+    let m = Metric::new(g, x);
+    let unit = |k: usize| std::array::from_fn(|i| e!(if i == k { 1.0 } else { 0.0 }));
+    assert!(m.is_killing(&unit(0)));
+    assert!(m.is_killing(&unit(3)));
+    assert!(!m.is_killing(&unit(1)));
+    // `L_ξ g = 0` along `∂t`
+    let g = Tensor::matrix(&m.g().0, [Variance::Down, Variance::Down]);
+    let points = [[1.0, 0.0, 3.0, 0.4, 0.1]];
+    for (_, c) in m.lie_derivative(&g, &unit(0)).iter() {
+        assert_equal(&sys, c, &e!(0.0), &points);
+    }
+    // Rotation about the x axis, `-sin φ ∂θ - cot θ cos φ ∂φ`
+    let [_, _, th, ph] = x;
+    let rotation = [e!(0.0), e!(0.0), -sin(ph), -(cot(th) * cos(ph))];
+    assert!(m.is_killing(&rotation));
+}
+
+#[test]
+fn ingoing_eddington_finkelstein() {
+    let mut sys = System::default();
+    let [mass] = sys.symbols("M").unwrap();
+    let (g, [t, r, th, ph]) = schwarzschild(&mut sys, e!(mass)).unwrap();
+    let [v] = sys.symbols("v").unwrap();
+    let m = Metric::new(g, [t, r, th, ph]);
+    // `v = t + r*`, `r* = r + 2M ln(r/2M - 1)`
+    let tortoise = e!(r) + e!(2.0) * e!(mass) * (e!(r) / (e!(2.0) * e!(mass)) - e!(1.0)).ln();
+    let forward = [e!(t) + tortoise.clone(), e!(r), e!(th), e!(ph)];
+    let transformation = Transformation::new([t, r, th, ph], [v, r, th, ph], forward)
+        .with_inverse([e!(v) - tortoise, e!(r), e!(th), e!(ph)]);
+    let ef = m.transform(&transformation);
+    let points = [
+        [1.0, 0.0, 3.0, 0.4, 0.1, 2.0],
+        [0.5, 1.0, 7.5, 1.3, 2.0, -1.0],
+    ];
+    let f = e!(1.0) - e!(2.0) * e!(mass) / e!(r);
+    let g = ef.g();
+    assert_equal(&sys, &g[0][0], &-f, &points);
+    assert_equal(&sys, &g[0][1], &e!(1.0), &points);
+    assert_equal(&sys, &g[1][0], &e!(1.0), &points);
+    assert_equal(&sys, &g[1][1], &e!(0.0), &points);
+    assert_equal(&sys, &g[2][2], &e!(r).pow(2.0), &points);
+    assert_equal(&sys, &g[0][3], &e!(0.0), &points);
+}
+
+#[test]
+fn kerr_reduces_to_schwarzschild() {
+    let mut sys = System::default();
+    let (kerr, _) = kerr(&mut sys, e!(1.0), e!(0.0)).unwrap();
+    let (schwarzschild, _) = schwarzschild(&mut System::default(), e!(1.0)).unwrap();
+    let points = [[0.0, 3.0, 0.4, 0.1], [1.0, 7.5, 1.3, 2.0]];
+    for (a, b) in kerr
+        .0
+        .iter()
+        .flatten()
+        .zip(schwarzschild.0.iter().flatten())
+    {
+        assert_equal(&sys, a, b, &points);
+    }
+}
+
+#[test]
+fn kerr_is_ricci_flat() {
+    let mut sys = System::default();
+    let (g, x) = kerr(&mut sys, e!(1.0), e!(0.6)).unwrap();
+    // Only the metric is simplified, the curvature being checked numerically as simplifying
+    // every stage is slow for Kerr
+    let g = g.simplify();
+    let g_inv = g.inv();
+    let riemann = riemann_tensor(g.clone(), &christoffel(g, g_inv.clone(), x), x);
+    let ricci = ricci_tensor(&riemann, g_inv);
+    let points = [[0.0, 3.0, 0.4, 0.1], [1.0, 7.5, 1.3, 2.0]];
+    for (i, j) in [(0, 0), (0, 3), (1, 1), (1, 2), (2, 2), (3, 3)] {
+        for p in points {
+            let r = sys.eval(ricci[i][j].clone(), p.map(|p| c!(p))).norm();
+            assert!(r < 1e-9, "R_{i}{j} = {r:e} at {p:?}");
+        }
+    }
+}
+
+#[test]
+fn kerr_newman_reduces_to_reissner_nordstrom() {
+    let mut sys = System::default();
+    let (kerr_newman, _) = kerr_newman(&mut sys, e!(1.0), e!(0.0), e!(0.5)).unwrap();
+    let (rn, _) = reissner_nordstrom(&mut System::default(), e!(1.0), e!(0.5)).unwrap();
+    let points = [[0.0, 3.0, 0.4, 0.1], [1.0, 7.5, 1.3, 2.0]];
+    for (a, b) in kerr_newman.0.iter().flatten().zip(rn.0.iter().flatten()) {
+        assert_equal(&sys, a, b, &points);
+    }
+}
+
+#[test]
+fn anti_de_sitter_is_einstein() {
+    let mut sys = System::default();
+    let [l] = sys.symbols("L").unwrap();
+    let (g, x) = anti_de_sitter(&mut sys, e!(l)).unwrap();
+    let m = Metric::new(g, x);
+    let points = [[2.0, 0.0, 0.5, 0.4, 0.1], [0.7, 1.0, 2.0, 1.3, 2.0]];
+    // `R_mn = -3 g_mn / L²`, `R = -12 / L²`
+    for i in 0..4 {
+        for j in 0..4 {
+            let expected = e!(-3.0) * m.g()[i][j].clone() / e!(l).pow(2.0);
+            assert_equal(&sys, &m.ricci()[i][j], &expected, &points);
+        }
+    }
+    let expected = e!(-12.0) / e!(l).pow(2.0);
+    assert_equal(&sys, m.scalar_curvature(), &expected, &points);
+}
+
+#[test]
+fn curved_flrw() {
+    let mut sys = System::default();
+    let [p, k] = sys.symbols("p k").unwrap();
+    // Power law expansion `a = t^p`
+    let (g, x) = flrw(&mut sys, |t| e!(t).pow(e!(p)), e!(k)).unwrap();
+    let [t, r, th, _] = x;
+    let m = Metric::new(g, x);
+    let points = [
+        [0.5, 1.0, 1.5, 0.5, 0.4, 0.1],
+        [2.0 / 3.0, -1.0, 2.5, 1.2, 1.3, 2.0],
+    ];
+    let t2 = e!(t).pow(2.0);
+    // `R_00 = -3 ä/a`, `R_ij = (a ä + 2 ȧ² + 2k) γ_ij`, `R = 6 (ä/a + ȧ²/a² + k/a²)`
+    let r00 = e!(-3.0) * e!(p) * (e!(p) - e!(1.0)) / t2.clone();
+    let spatial = e!(p) * (e!(3.0) * e!(p) - e!(1.0)) * e!(t).pow(e!(2.0) * e!(p) - e!(2.0))
+        + e!(2.0) * e!(k);
+    let rrr = spatial.clone() / (e!(1.0) - e!(k) * e!(r).pow(2.0));
+    let rthth = spatial.clone() * e!(r).pow(2.0);
+    let rphph = rthth.clone() * sin(th).pow(2.0);
+    let scalar =
+        e!(6.0) * (e!(p) * (e!(2.0) * e!(p) - e!(1.0)) / t2 + e!(k) * e!(t).pow(e!(-2.0) * e!(p)));
+    assert_equal(&sys, &m.ricci()[0][0], &r00, &points);
+    assert_equal(&sys, &m.ricci()[1][1], &rrr, &points);
+    assert_equal(&sys, &m.ricci()[2][2], &rthth, &points);
+    assert_equal(&sys, &m.ricci()[3][3], &rphph, &points);
+    for (i, j) in [(0, 1), (0, 2), (1, 2), (2, 3)] {
+        assert_equal(&sys, &m.ricci()[i][j], &e!(0.0), &points);
+    }
+    assert_equal(&sys, m.scalar_curvature(), &scalar, &points);
+}
+
+#[test]
+fn minkowski_charts_are_flat() {
+    let points = [[0.0, 1.0, 2.0, 3.0], [1.0, 0.4, -1.3, 0.7]];
+    for chart in [minkowski_cylindrical, minkowski_light_cone] {
+        let mut sys = System::default();
+        let (g, x) = chart(&mut sys).unwrap();
+        let m = Metric::new(g, x);
+        for (_, r) in m.riemann().iter() {
+            assert_equal(&sys, r, &e!(0.0), &points);
+        }
+    }
+}
+
+#[test]
+fn three_sphere() {
+    let mut sys = System::default();
+    let [a] = sys.symbols("a").unwrap();
+    let (g, x) = sphere::<3>(&mut sys, e!(a)).unwrap();
+    let m = Metric::new(g, x);
+    let points = [[1.0, 0.4, 0.7, 0.1], [2.5, 1.3, 2.0, 2.0]];
+    let expected = e!(6.0) / e!(a).pow(2.0);
+    assert_equal(&sys, m.scalar_curvature(), &expected, &points);
+}
+
+#[test]
+fn embedded_sphere() {
+    let mut sys = System::default();
+    let x = sys.symbols("x y z").unwrap();
+    let [th, ph, radius] = sys.symbols("θ φ R").unwrap();
+    let flat = Metric::new(diag([e!(1.0), e!(1.0), e!(1.0)]), x);
+    let map = [
+        e!(radius) * sin(th) * cos(ph),
+        e!(radius) * sin(th) * sin(ph),
+        e!(radius) * cos(th),
+    ];
+    let sphere = Embedding::new(&flat, map, [th, ph]);
+    let points = [
+        [0.0, 0.0, 0.0, 0.4, 0.1, 2.0],
+        [0.0, 0.0, 0.0, 1.3, 2.0, 0.5],
+    ];
+    let h = sphere.induced_metric();
+    assert_equal(&sys, &h[0][0], &e!(radius).pow(2.0), &points);
+    assert_equal(&sys, &h[0][1], &e!(0.0), &points);
+    let expected = e!(radius).pow(2.0) * sin(th).pow(2.0);
+    assert_equal(&sys, &h[1][1], &expected, &points);
+    assert_equal(
+        &sys,
+        &sphere.gaussian_curvature(),
+        &e!(radius).pow(-2.0),
+        &points,
+    );
+    assert_equal(&sys, &sphere.mean_curvature(), &e!(radius).inv(), &points);
+}
+
+/// Compares every component of the forms `a` and `b`
+fn assert_form<const N: usize, const M: usize>(
+    sys: &System,
+    a: &Form<N>,
+    b: &Form<N>,
+    points: &[[f64; M]],
+) {
+    assert_eq!(a.degree(), b.degree());
+    for (i, c) in a.iter() {
+        assert_equal(sys, c, &b.get(&i), points);
+    }
+}
+
+#[test]
+fn forms_in_flat_space() {
+    let mut sys = System::default();
+    let [x, y, z] = sys.symbols("x y z").unwrap();
+    let g = diag([e!(1.0), e!(1.0), e!(1.0)]);
+    let dx = Form::<3>::dx;
+    let points = [[0.3, 1.2, -0.7], [2.0, -0.5, 1.1]];
+    assert_form(&sys, &dx(1).hodge(&g), &dx(2).wedge(&dx(0)), &points);
+    assert_form(&sys, &dx(0).wedge(&dx(1)).hodge(&g), &dx(2), &points);
+    assert_form(
+        &sys,
+        &Form::scalar(e!(1.0)).hodge(&g),
+        &dx(0).wedge(&dx(1)).wedge(&dx(2)),
+        &points,
+    );
+
+    let f = Form::<3>::scalar(e!(x).pow(2.0) * e!(y) * sin(z));
+    let df = f.d([x, y, z]);
+    assert_equal(
+        &sys,
+        &df.get(&[0]),
+        &(e!(2.0) * e!(x) * e!(y) * sin(z)),
+        &points,
+    );
+    assert_form(&sys, &df.d([x, y, z]), &Form::zero(2), &points);
+}
+
+#[test]
+fn static_tidal_tensor() {
+    let mut sys = System::default();
+    let [mass] = sys.symbols("M").unwrap();
+    let (g, x) = schwarzschild(&mut sys, e!(mass)).unwrap();
+    let [_, r, _, _] = x;
+    let m = Metric::new(g, x);
+    let f = e!(1.0) - e!(2.0) * e!(mass) / e!(r);
+    let u = [f.pow(-0.5), e!(0.0), e!(0.0), e!(0.0)];
+    let tidal = m.tidal_tensor(&u);
+    let points = [[1.0, 0.0, 3.0, 0.4, 0.1], [0.5, 1.0, 7.5, 1.3, 2.0]];
+    let m_r3 = e!(mass) / e!(r).pow(3.0);
+    // Stretched radially, squeezed sideways
+    assert_equal(&sys, &tidal[1][1], &(e!(-2.0) * m_r3.clone()), &points);
+    assert_equal(&sys, &tidal[2][2], &m_r3, &points);
+    assert_equal(&sys, &tidal[3][3], &m_r3, &points);
+    assert_equal(&sys, &tidal[1][2], &e!(0.0), &points);
+    assert_equal(&sys, &tidal[0][0], &e!(0.0), &points);
+}
+
+#[test]
+fn radial_geodesic_deviation() {
+    let mut sys = System::default();
+    let (g, x) = schwarzschild(&mut sys, e!(1.0)).unwrap();
+    let [tau] = sys.symbols("τ").unwrap();
+    let v = sys.symbols("ṫ ṙ θ̇ φ̇").unwrap();
+    let xi = sys.symbols("ξt ξr ξθ ξφ").unwrap();
+    let w = sys.symbols("ẇt ẇr ẇθ ẇφ").unwrap();
+    let m = Metric::new(g, x);
+    // Falling from rest at `r0`, next to the geodesic falling from rest at `r0 + ξ`
+    let r0: f64 = 10.0;
+    let f = 1.0 - 2.0 / r0;
+    let initial = [
+        [0.0, r0, std::f64::consts::FRAC_PI_2, 0.0],
+        [f.powf(-0.5), 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [-f.powf(-1.5) / (r0 * r0), 0.0, 0.0, 0.0],
+    ];
+    let method = Method::Rk45 {
+        rtol: 1e-10,
+        atol: 1e-12,
+    };
+    let path = m
+        .geodesic_deviation(tau, [x, v, xi, w], method, (0.0, 20.0), initial)
+        .unwrap();
+    // `r = r0 (1 + cos η) / 2` at `τ = √(r0³/8M) (η + sin η)`, so that
+    // `∂r/∂r0 = (1 + cos η) / 2 + 3τ sin η / (4 √(r0³/8M) (1 + cos η))` at fixed `τ`
+    let scale = (r0.powi(3) / 8.0).sqrt();
+    for (&tau, xi) in path.geodesic.tau.iter().zip(&path.xi) {
+        let mut eta = tau / scale / 2.0;
+        for _ in 0..50 {
+            eta -= (scale * (eta + eta.sin()) - tau) / (scale * (1.0 + eta.cos()));
+        }
+        let c = 1.0 + eta.cos();
+        let expected = c / 2.0 + 3.0 * tau * eta.sin() / (4.0 * scale * c);
+        assert!(
+            (xi[1] - expected).abs() < 1e-6,
+            "{} != {expected} at {tau}",
+            xi[1]
+        );
+    }
+    // Stretched along the fall
+    assert!(path.xi.last().unwrap()[1] > 1.1);
+    assert!(path.geodesic.norm_drift() < 1e-8);
+}
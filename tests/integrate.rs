@@ -0,0 +1,183 @@
+use symrs::*;
+
+/// Differentiates the antiderivative back and compares it to `f` at a few points
+fn assert_antiderivative(sys: &System, f: Expression, x: Var) {
+    let fi = f.clone().integrate(x);
+    let d = (fi.clone().diff(x) - f.clone()).simplify();
+    for p in [0.3, 0.7, 1.3, 2.1] {
+        let err = sys.eval(d.clone(), [c!(p), c!(1.7)]).norm();
+        let scale = sys.eval(f.clone(), [c!(p), c!(1.7)]).norm().max(1.0);
+        assert!(
+            err <= 1e-9 * scale,
+            "{} at {p}: {err:e}",
+            sys.str(fi.clone())
+        );
+    }
+}
+
+fn setup() -> (System, Var, Var) {
+    let mut sys = System::default();
+    let [x, a] = sys.symbols("x a").unwrap();
+    (sys, x, a)
+}
+
+#[test]
+fn polynomials_and_powers() {
+    let (sys, x, a) = setup();
+    assert_antiderivative(&sys, e!(x).pow(e!(3.0)) * e!(2.0) + e!(x) + e!(5.0), x);
+    assert_antiderivative(&sys, e!(x).inv(), x);
+    assert_antiderivative(&sys, e!(x).pow(e!(-3.0)), x);
+    assert_antiderivative(&sys, e!(x).pow(e!(0.5)), x);
+    assert_antiderivative(&sys, e!(x).pow(e!(a)), x);
+    assert_antiderivative(&sys, (e!(2.0) * e!(x) + e!(1.0)).pow(e!(0.5)), x);
+}
+
+#[test]
+fn partial_fractions() {
+    let (sys, x, a) = setup();
+    let x2 = || e!(x) * e!(x);
+    assert_antiderivative(&sys, (x2() + e!(1.0)).inv(), x);
+    assert_antiderivative(&sys, (x2() + e!(a) * e!(a)).inv(), x);
+    assert_antiderivative(&sys, e!(x) / (x2() - e!(1.0)), x);
+    assert_antiderivative(&sys, (e!(x).pow(e!(3.0)) - e!(1.0)).inv(), x);
+    assert_antiderivative(&sys, (e!(x) * (e!(x) + e!(1.0)).pow(e!(2.0))).inv(), x);
+    assert_antiderivative(&sys, (e!(x).pow(e!(3.0)) + e!(1.0)) / (x2() + e!(x)), x);
+}
+
+#[test]
+fn exponentials_and_logarithms() {
+    let (sys, x, a) = setup();
+    assert_antiderivative(&sys, (e!(3.0) * e!(x)).exp(), x);
+    assert_antiderivative(&sys, e!(a).pow(e!(x)), x);
+    assert_antiderivative(&sys, e!(x) * e!(x).exp(), x);
+    assert_antiderivative(&sys, e!(x).pow(e!(3.0)) * (e!(2.0) * e!(x)).exp(), x);
+    assert_antiderivative(&sys, e!(x).ln(), x);
+    assert_antiderivative(&sys, e!(x) * e!(x) * e!(x).ln(), x);
+    assert_antiderivative(&sys, e!(x).ln().pow(e!(2.0)), x);
+    assert_antiderivative(&sys, (e!(x).exp() + e!(1.0)).inv(), x);
+}
+
+#[test]
+fn trigonometric() {
+    let (sys, x, _) = setup();
+    assert_antiderivative(&sys, sin(e!(x)), x);
+    assert_antiderivative(&sys, sin(e!(x)) * cos(e!(x)), x);
+    assert_antiderivative(&sys, sin(e!(x)).pow(e!(2.0)), x);
+    assert_antiderivative(&sys, e!(x) * sin(e!(x)), x);
+    assert_antiderivative(&sys, e!(x).exp() * sin(e!(x)), x);
+    assert_antiderivative(&sys, tan(e!(x)), x);
+    assert_antiderivative(&sys, sec(e!(x)), x);
+    assert_antiderivative(&sys, e!(x) * atan(e!(x)), x);
+}
+
+#[test]
+fn substitution() {
+    let (sys, x, _) = setup();
+    assert_antiderivative(&sys, e!(x) * (e!(x) * e!(x)).exp(), x);
+    assert_antiderivative(&sys, e!(x).ln() / e!(x), x);
+    assert_antiderivative(
+        &sys,
+        e!(2.0) * e!(x) * (e!(x) * e!(x) + e!(1.0)).pow(e!(-3.0)),
+        x,
+    );
+}
+
+#[test]
+fn unevaluated() {
+    let (sys, x, _) = setup();
+    let f = (e!(x) * e!(x)).exp();
+    let fi = f.clone().integrate(x);
+    assert!(sys.str(fi.clone()).starts_with('∫'));
+    assert_antiderivative(&sys, f + e!(x), x);
+}
+
+/// Checks a definite integral over `[a, b]` against `expected`, within its reported error
+#[track_caller]
+fn assert_definite(sys: &System, f: Expression, x: Var, (a, b): (Point, Point), expected: f64) {
+    let d = f.integrate_definite(x, a, b).unwrap();
+    let value = sys.eval(d.value, [c!(); 2]);
+    let err = (value - c!(expected)).norm();
+    assert!(
+        err <= 1e-8 + 10.0 * d.error,
+        "{value} != {expected}, error {}",
+        d.error
+    );
+}
+
+fn finite(p: f64) -> Point {
+    Point::Finite(e!(p))
+}
+
+#[test]
+fn definite() {
+    let (sys, x, _) = setup();
+    let pi = std::f64::consts::PI;
+    let unit = || (finite(0.0), finite(1.0));
+    assert_definite(&sys, e!(x).ln(), x, unit(), -1.0);
+    assert_definite(&sys, e!(x).pow(e!(-0.5)), x, unit(), 2.0);
+    let gaussian = (-e!(x) * e!(x)).exp();
+    assert_definite(&sys, gaussian, x, (Point::NegInf, Point::PosInf), pi.sqrt());
+    let lorentzian = || (e!(1.0) + e!(x) * e!(x)).inv();
+    assert_definite(
+        &sys,
+        lorentzian(),
+        x,
+        (finite(0.0), Point::PosInf),
+        pi / 2.0,
+    );
+    assert_definite(
+        &sys,
+        lorentzian(),
+        x,
+        (Point::PosInf, finite(0.0)),
+        -pi / 2.0,
+    );
+    assert_definite(
+        &sys,
+        e!(x) * e!(x),
+        x,
+        (finite(1.0), finite(0.0)),
+        -1.0 / 3.0,
+    );
+}
+
+#[test]
+fn definite_symbolic() {
+    let (sys, x, a) = setup();
+    let d = e!(x)
+        .integrate_definite(x, finite(0.0), Point::Finite(e!(a)))
+        .unwrap();
+    let value = sys.eval(d.value, [c!(), c!(3.0)]);
+    assert!((value - c!(4.5)).norm() < 1e-12 && d.error == 0.0);
+}
+
+#[test]
+fn definite_interior_singularities() {
+    let (sys, x, _) = setup();
+    let span = || (finite(-1.0), finite(1.0));
+    assert!(e!(x)
+        .pow(e!(-2.0))
+        .integrate_definite(x, span().0, span().1)
+        .is_err());
+    assert!(e!(x)
+        .inv()
+        .integrate_definite(x, span().0, span().1)
+        .is_err());
+    let f = (e!(x) * e!(x) - e!(2.0)).inv();
+    assert!(f.integrate_definite(x, finite(0.0), finite(2.0)).is_err());
+    let f = (e!(x) - e!(0.5)).pow(e!(-2.0));
+    assert!(f.integrate_definite(x, finite(2.0), finite(0.0)).is_err());
+
+    assert_definite(&sys, e!(x).abs().pow(e!(-0.5)), x, span(), 4.0);
+    let expected = 2.0 * 2f64.ln() - 3.0;
+    assert_definite(
+        &sys,
+        e!(x).abs().ln(),
+        x,
+        (finite(-1.0), finite(2.0)),
+        expected,
+    );
+    let f = (e!(x) * e!(x) - e!(2.0)).abs().pow(e!(-0.5));
+    let expected = (2f64.sqrt() + 1.0).ln() + std::f64::consts::FRAC_PI_2;
+    assert_definite(&sys, f, x, (finite(0.0), finite(2.0)), expected);
+}
@@ -0,0 +1,112 @@
+use symrs::*;
+
+fn setup() -> (System, Var, Var) {
+    let mut sys = System::default();
+    let [x, a] = sys.symbols("x a").unwrap();
+    (sys, x, a)
+}
+
+#[track_caller]
+fn assert_finite(sys: &System, limit: Result<Point, String>, expected: f64) {
+    match limit {
+        Ok(Point::Finite(v)) => {
+            let v = sys.eval(v, [c!(); 2]);
+            assert!(
+                (v - c!(expected)).norm() < 1e-9,
+                "expected {expected} got {v}"
+            );
+        }
+        other => panic!("expected {expected} got {other:?}"),
+    }
+}
+
+#[test]
+fn standard_limits() {
+    let (sys, x, _) = setup();
+    let zero = || Point::Finite(e!(0.0));
+    let lim = sin(e!(x)) / e!(x);
+    assert_finite(&sys, lim.limit(x, zero(), Direction::Both), 1.0);
+    let lim = (e!(1.0) + e!(x).inv()).pow(e!(x));
+    assert_finite(
+        &sys,
+        lim.limit(x, Point::PosInf, Direction::Both),
+        1f64.exp(),
+    );
+    let lim = e!(x) * e!(x).ln();
+    assert_finite(&sys, lim.limit(x, zero(), Direction::Plus), 0.0);
+    let lim = (cos(e!(x)) - e!(1.0)) / e!(x).pow(e!(2.0));
+    assert_finite(&sys, lim.limit(x, zero(), Direction::Both), -0.5);
+}
+
+#[test]
+fn one_sided_limits() {
+    let (_, x, _) = setup();
+    let zero = || Point::Finite(e!(0.0));
+    let f = || e!(x).inv();
+    assert!(matches!(
+        f().limit(x, zero(), Direction::Plus),
+        Ok(Point::PosInf)
+    ));
+    assert!(matches!(
+        f().limit(x, zero(), Direction::Minus),
+        Ok(Point::NegInf)
+    ));
+    assert!(f().limit(x, zero(), Direction::Both).is_err());
+    let f = || e!(x).pow(e!(-2.0));
+    assert!(matches!(
+        f().limit(x, zero(), Direction::Both),
+        Ok(Point::PosInf)
+    ));
+    let f = e!(x).inv().exp();
+    assert!(matches!(
+        f.limit(x, zero(), Direction::Minus),
+        Ok(Point::Finite(_))
+    ));
+}
+
+#[test]
+fn exponential_beats_power() {
+    let (sys, x, _) = setup();
+    let f = e!(x).exp() / e!(x).pow(e!(100.0));
+    assert!(matches!(
+        f.limit(x, Point::PosInf, Direction::Both),
+        Ok(Point::PosInf)
+    ));
+    let f = e!(x).pow(e!(100.0)) * (-e!(x)).exp();
+    assert_finite(&sys, f.limit(x, Point::PosInf, Direction::Both), 0.0);
+}
+
+#[test]
+fn sign_of_free_parameter() {
+    let (_, x, a) = setup();
+    let f = e!(a) * e!(x);
+    assert!(f.limit(x, Point::PosInf, Direction::Both).is_err());
+    let f = e!(a).pow(e!(2.0)) * e!(x) + e!(1.0);
+    assert!(matches!(
+        f.limit(x, Point::PosInf, Direction::Both),
+        Ok(Point::PosInf)
+    ));
+}
+
+#[test]
+fn left_limits_mirror_the_variable() {
+    let (sys, x, _) = setup();
+    let one = || Point::Finite(e!(1.0));
+    let f = || (e!(x) - e!(1.0)).abs() / (e!(x) - e!(1.0));
+    assert_finite(&sys, f().limit(x, one(), Direction::Plus), 1.0);
+    assert_finite(&sys, f().limit(x, one(), Direction::Minus), -1.0);
+    let f = || (e!(1.0) - e!(x)).ln();
+    assert!(matches!(
+        f().limit(x, one(), Direction::Minus),
+        Ok(Point::NegInf)
+    ));
+    let f = (e!(x) - e!(1.0)).inv().exp();
+    assert_finite(&sys, f.limit(x, one(), Direction::Minus), 0.0);
+    let f = e!(x).exp() * e!(x);
+    assert_finite(&sys, f.limit(x, Point::NegInf, Direction::Both), 0.0);
+    let f = e!(x).pow(e!(3.0));
+    assert!(matches!(
+        f.limit(x, Point::NegInf, Direction::Both),
+        Ok(Point::NegInf)
+    ));
+}
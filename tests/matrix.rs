@@ -0,0 +1,96 @@
+use symrs::*;
+
+/// Checks `a a⁻¹ = 1` at each of the sample points
+#[track_caller]
+fn assert_inverse<const N: usize>(sys: &System, a: &SqMatrix<N>, points: &[[f64; 2]]) {
+    let product = a.clone() * a.inv();
+    for p in points {
+        let p = p.map(|p| c!(p));
+        for i in 0..N {
+            for j in 0..N {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                let err = (sys.eval(product[i][j].clone(), p) - c!(expected)).norm();
+                assert!(err < 1e-9, "{}", sys.strmat(a.inv()));
+            }
+        }
+    }
+}
+
+fn setup() -> (System, Var, Var) {
+    let mut sys = System::default();
+    let [x, y] = sys.symbols("x y").unwrap();
+    (sys, x, y)
+}
+
+const POINTS: [[f64; 2]; 2] = [[0.7, 1.3], [2.1, -0.4]];
+
+#[test]
+fn diagonal_stays_sparse() {
+    let (sys, x, y) = setup();
+    let mut a = SqMatrix::<3>::zeroes();
+    a[0][0] = e!(x);
+    a[1][1] = e!(y).pow(2.0);
+    a[2][2] = e!(x) * sin(y);
+    assert_inverse(&sys, &a, &POINTS);
+    let inv = a.inv();
+    for i in 0..3 {
+        for j in (0..3).filter(|&j| j != i) {
+            assert_eq!(sys.str(inv[i][j].clone()), sys.str(e!(0.0)));
+        }
+    }
+}
+
+#[test]
+fn dense() {
+    let (sys, x, y) = setup();
+    let mut a = SqMatrix::<3>::identity();
+    a[0][1] = e!(x);
+    a[0][2] = e!(2.0);
+    a[1][0] = e!(y);
+    a[2][1] = e!(x) * e!(y);
+    a[2][2] = e!(x).exp();
+    assert_inverse(&sys, &a, &POINTS);
+}
+
+#[test]
+fn symbolically_zero_pivot() {
+    let (sys, x, y) = setup();
+    // The first pivot `sin²x + cos²x - 1` vanishes, so rows have to be swapped
+    let mut a = SqMatrix::<3>::zeroes();
+    a[0][0] = sin(x).pow(2.0) + cos(x).pow(2.0) - e!(1.0);
+    a[0][1] = e!(1.0);
+    a[1][0] = e!(y);
+    a[1][2] = e!(x);
+    a[2][1] = e!(x);
+    a[2][2] = e!(1.0);
+    assert_inverse(&sys, &a, &POINTS);
+
+    // Light-cone metric with no nonzero diagonal entry
+    let mut a = SqMatrix::<2>::zeroes();
+    a[0][1] = e!(-0.5);
+    a[1][0] = e!(-0.5);
+    assert_inverse(&sys, &a, &POINTS);
+    assert_eq!(sys.str(a.inv()[0][0].clone()), sys.str(e!(0.0)));
+}
+
+#[test]
+fn entries_vanishing_only_for_positive_values() {
+    let (sys, x, y) = setup();
+    // `x - |x|` samples as zero but is `2x` for negative `x`, so it still has to be eliminated
+    let mut a = SqMatrix::<3>::identity();
+    a[1][0] = e!(x) - e!(x).abs();
+    a[2][0] = e!(y);
+    a[2][1] = e!(x) - e!(x).abs();
+    let negative = [[-0.7, 1.3], [-2.1, -0.4]];
+    assert_inverse(&sys, &a, &negative);
+    assert_inverse(&sys, &a, &POINTS);
+
+    // Nor is it taken as a pivot, which would divide by zero for positive `x`
+    let mut a = SqMatrix::<2>::zeroes();
+    a[0][0] = e!(x) - e!(x).abs();
+    a[0][1] = e!(1.0);
+    a[1][0] = e!(y);
+    a[1][1] = e!(x);
+    assert_inverse(&sys, &a, &negative);
+    assert_inverse(&sys, &a, &POINTS);
+}
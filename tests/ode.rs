@@ -0,0 +1,170 @@
+use symrs::*;
+
+struct Setup {
+    sys: System,
+    x: Var,
+    t: Var,
+    w: Var,
+    y: Func,
+}
+
+fn setup() -> Setup {
+    let mut sys = System::default();
+    let [x, t, w] = sys.symbols("x t w").unwrap();
+    let y = sys.function("y", 1).unwrap();
+    Setup { sys, x, t, w, y }
+}
+
+/// Checks that `ex` vanishes for a few values of every variable, constants included
+#[track_caller]
+fn assert_zero(ex: Expression) {
+    let f = ex.compile();
+    for k in 0..3 {
+        let values = (0..16)
+            .map(|i| c!(0.3 + 0.37 * ((i * 7 + k * 5) % 11) as f64 / 11.0))
+            .collect::<Vec<_>>();
+        let v = f.eval(&values);
+        assert!(v.norm() < 1e-8, "residual {v}");
+    }
+}
+
+impl Setup {
+    fn call(&self) -> Expression {
+        self.y.call([self.x])
+    }
+
+    fn d(&self, n: usize) -> Expression {
+        self.call().derivative([(self.x, n)])
+    }
+
+    fn solve(&mut self, ode: Expression) -> Expression {
+        match self.sys.dsolve(ode, self.y, self.x).unwrap() {
+            Solution::Explicit(sol) => sol,
+            Solution::Implicit(f) => panic!("implicit solution {}", self.sys.str(f)),
+        }
+    }
+
+    #[track_caller]
+    fn assert_series(&mut self, ode: Expression, x0: f64) {
+        let sol = self
+            .sys
+            .dsolve_series(ode.clone(), self.y, self.x, e!(x0), 10);
+        assert_small_near(self.residual(ode, sol.unwrap()), x0);
+    }
+
+    /// `ode` with `y` replaced by `sol`, in terms of `x`
+    fn residual(&self, ode: Expression, sol: Expression) -> Expression {
+        ode.subs_func(self.y, [self.t], sol.subs(self.x, self.t))
+            .doit()
+    }
+}
+
+#[test]
+fn first_order_linear() {
+    let mut s = setup();
+    let ode = s.d(1) - s.call();
+    let sol = s.solve(ode.clone());
+    assert_zero(s.residual(ode, sol));
+    let ode = s.d(1) + e!(2.0) * e!(s.x) * s.call() - e!(s.x);
+    let sol = s.solve(ode.clone());
+    assert_zero(s.residual(ode, sol));
+}
+
+#[test]
+fn constant_coefficients() {
+    let mut s = setup();
+    // Harmonic oscillator, `C1 cos x + C2 sin x`
+    let ode = s.d(2) + s.call();
+    let sol = s.solve(ode.clone());
+    assert_zero(s.residual(ode, sol));
+    // Critical damping, a repeated root giving `(C1 + C2 x) e^-x`
+    let ode = s.d(2) + e!(2.0) * s.d(1) + s.call();
+    let sol = s.solve(ode.clone());
+    assert_zero(s.residual(ode.clone(), sol.clone()));
+    assert!(s.sys.str(sol).contains('x'));
+    // Forced, through variation of parameters
+    let ode = s.d(2) - s.call() - e!(s.x);
+    let sol = s.solve(ode.clone());
+    assert_zero(s.residual(ode, sol));
+}
+
+#[test]
+fn bernoulli() {
+    let mut s = setup();
+    // `y' = y + x y²`, not separable
+    let ode = s.d(1) - s.call() - e!(s.x) * s.call().pow(e!(2.0));
+    let sol = s.solve(ode.clone());
+    assert_zero(s.residual(ode, sol));
+}
+
+#[test]
+fn exact() {
+    let mut s = setup();
+    // `(2x + y) dx + (x + 2y) dy = 0`, with `x² + xy + y² = C` as the solution
+    let x = s.x;
+    let m = |y: Expression| e!(2.0) * e!(x) + y;
+    let n = |y: Expression| e!(x) + e!(2.0) * y;
+    let ode = m(s.call()) + n(s.call()) * s.d(1);
+    let Solution::Implicit(f) = s.sys.dsolve(ode, s.y, s.x).unwrap() else {
+        panic!("expected an implicit solution");
+    };
+    // `F(x, w)` is constant along `w' = -M/N`
+    let f = f.subs_func(s.y, [s.t], e!(s.w));
+    let along = f.clone().diff(s.x) * n(e!(s.w)) - f.diff(s.w) * m(e!(s.w));
+    assert_zero(along);
+}
+
+/// Checks that `ex` vanishes to high order in `x`, the first variable, about `x0`, for a few
+/// values of the others
+#[track_caller]
+fn assert_small_near(ex: Expression, x0: f64) {
+    let f = ex.compile();
+    for k in 0..3 {
+        let mut values = (0..16)
+            .map(|i| c!(0.3 + 0.37 * ((i * 7 + k * 5) % 11) as f64 / 11.0))
+            .collect::<Vec<_>>();
+        values[0] = c!(x0 + 0.05);
+        let v = f.eval(&values);
+        assert!(v.norm() < 1e-9, "residual {v}");
+    }
+}
+
+#[test]
+fn series_solutions() {
+    let mut s = setup();
+    let ode = s.d(2) + s.call();
+    s.assert_series(ode, 0.0);
+    // Airy, `y'' = x y`
+    let ode = s.d(2) - e!(s.x) * s.call();
+    s.assert_series(ode, 0.0);
+    // Nonlinear, about a point away from the origin
+    let ode = s.d(1) - s.call().pow(e!(2.0)) - e!(s.x);
+    s.assert_series(ode, 1.0);
+    let singular = s.d(2) + s.call() / e!(s.x);
+    assert!(s.sys.dsolve_series(singular, s.y, s.x, e!(0.0), 4).is_err());
+}
+
+#[test]
+fn solutions_in_the_callers_system() {
+    let mut sys = System::default();
+    let [x] = sys.symbols("x").unwrap();
+    let y = sys.function("y", 1).unwrap();
+    // Only `C1` is declared, so the solution evaluates with `[x, C1]`
+    let ode = y.call([x]).diff(x) - y.call([x]);
+    let Solution::Explicit(sol) = sys.dsolve(ode, y, x).unwrap() else {
+        panic!("expected an explicit solution");
+    };
+    let v = sys.eval(sol, [c!(0.5), c!(2.0)]);
+    assert!((v - c!(2.0 * 0.5f64.exp())).norm() < 1e-9, "{v}");
+    // A second equation declares `C2` and `C3`, skipping the `C1` in use
+    let ode = y.call([x]).diff(x).diff(x) + y.call([x]);
+    let Solution::Explicit(sol) = sys.dsolve(ode, y, x).unwrap() else {
+        panic!("expected an explicit solution");
+    };
+    let residual = sol.clone().diff(x).diff(x) + sol;
+    let v = sys.eval(residual, [c!(0.5), c!(2.0), c!(-1.0), c!(0.7)]);
+    assert!(v.norm() < 1e-9, "{v}");
+
+    let f = sys.function("f", 2).unwrap();
+    assert!(sys.dsolve(f.call([x, x]), f, x).is_err());
+}
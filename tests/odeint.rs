@@ -0,0 +1,127 @@
+use std::f64::consts::PI;
+
+use symrs::*;
+
+const ADAPTIVE: Method = Method::Rk45 {
+    rtol: 1e-10,
+    atol: 1e-12,
+};
+
+/// `y' = -y`
+fn decay() -> OdeSystem {
+    let mut sys = System::default();
+    let [t, y] = sys.symbols("t y").unwrap();
+    OdeSystem::new(t, vec![y], vec![-e!(y)]).unwrap()
+}
+
+/// `x'' = -x` as `x' = v`, `v' = -x`
+fn oscillator() -> (OdeSystem, [Var; 3]) {
+    let mut sys = System::default();
+    let [t, x, v] = sys.symbols("t x v").unwrap();
+    let ode = OdeSystem::new(t, vec![x, v], vec![e!(v), -e!(x)]).unwrap();
+    (ode, [t, x, v])
+}
+
+#[track_caller]
+fn assert_close(a: f64, b: f64, tol: f64) {
+    assert!((a - b).abs() <= tol, "{a} != {b}");
+}
+
+#[test]
+fn exponential_decay() {
+    let ode = decay();
+    for method in [Method::Rk4(0.01), ADAPTIVE] {
+        let sol = ode.solve(method, 0.0, vec![1.0], 2.0, &[]).unwrap();
+        assert_close(*sol.t.last().unwrap(), 2.0, 1e-12);
+        for (t, y) in sol.t.iter().zip(&sol.y) {
+            assert_close(y[0], (-t).exp(), 1e-8);
+        }
+    }
+}
+
+#[test]
+fn harmonic_oscillator_period() {
+    let (ode, _) = oscillator();
+    for method in [Method::Rk4(1e-3), ADAPTIVE] {
+        let sol = ode
+            .solve(method, 0.0, vec![1.0, 0.0], 2.0 * PI, &[])
+            .unwrap();
+        let y = sol.y.last().unwrap();
+        assert_close(y[0], 1.0, 1e-8);
+        assert_close(y[1], 0.0, 1e-8);
+    }
+}
+
+#[test]
+fn dense_output() {
+    let ode = decay();
+    for method in [ADAPTIVE, Method::Rk4(0.1)] {
+        let sol = ode.solve(method, 0.0, vec![1.0], 3.0, &[]).unwrap();
+        for k in 0..=30 {
+            let t = 0.1 * k as f64 + 0.037;
+            if t <= 3.0 {
+                assert_close(sol.at(t)[0], (-t).exp(), 1e-5);
+            }
+        }
+    }
+    // Integrating backwards
+    let sol = ode.solve(ADAPTIVE, 1.0, vec![1.0], 0.0, &[]).unwrap();
+    assert_close(sol.at(0.5)[0], 0.5f64.exp(), 1e-6);
+}
+
+#[test]
+fn stiff() {
+    // `y' = -1000 (y - cos t)`, which follows `cos t` after a fast transient
+    let mut sys = System::default();
+    let [t, y] = sys.symbols("t y").unwrap();
+    let rhs = e!(-1000.0) * (e!(y) - cos(t));
+    let ode = OdeSystem::new(t, vec![y], vec![rhs]).unwrap();
+    let method = Method::Stiff {
+        rtol: 1e-6,
+        atol: 1e-9,
+    };
+    let sol = ode.solve(method, 0.0, vec![0.0], 2.0, &[]).unwrap();
+    assert!(sol.t.len() < 1000, "{} steps", sol.t.len());
+    // Slow manifold `y ≈ cos t + sin t / 1000`
+    let expected = 2f64.cos() + 2f64.sin() / 1000.0;
+    assert_close(sol.y.last().unwrap()[0], expected, 1e-5);
+}
+
+#[test]
+fn events() {
+    let (ode, [_, x, v]) = oscillator();
+    // `x = cos t` first crosses zero at `π/2`
+    let events = [Event {
+        g: e!(x),
+        terminal: true,
+    }];
+    let sol = ode
+        .solve(ADAPTIVE, 0.0, vec![1.0, 0.0], 10.0, &events)
+        .unwrap();
+    assert_eq!(sol.crossings.len(), 1);
+    let crossing = &sol.crossings[0];
+    assert_close(crossing.t, PI / 2.0, 1e-8);
+    assert_close(crossing.y[1], -1.0, 1e-8);
+    assert_close(*sol.t.last().unwrap(), PI / 2.0, 1e-8);
+
+    // Non-terminal events record every crossing of `v = -sin t`
+    let events = [Event {
+        g: e!(v),
+        terminal: false,
+    }];
+    let sol = ode
+        .solve(ADAPTIVE, 0.0, vec![1.0, 0.0], 10.0, &events)
+        .unwrap();
+    let times = sol.crossings.iter().map(|c| c.t).collect::<Vec<_>>();
+    assert_eq!(times.len(), 3, "{times:?}");
+    for (k, t) in times.into_iter().enumerate() {
+        assert_close(t, PI * (k + 1) as f64, 1e-8);
+    }
+}
+
+#[test]
+fn foreign_variables() {
+    let mut sys = System::default();
+    let [t, y, a] = sys.symbols("t y a").unwrap();
+    assert!(OdeSystem::new(t, vec![y], vec![e!(a) * e!(y)]).is_err());
+}
@@ -0,0 +1,138 @@
+use symrs::*;
+
+fn setup() -> (System, Var, Var, Var) {
+    let mut sys = System::default();
+    let [x, y, eps] = sys.symbols("x y ε").unwrap();
+    (sys, x, y, eps)
+}
+
+/// Compares the coefficients of `p` to `expected`, and the next one to zero, at a few points
+#[track_caller]
+fn assert_coeffs(sys: &System, p: &Perturbation, expected: &[Expression]) {
+    let expected = expected.iter().cloned().chain([e!(0.0)]);
+    for (k, c) in expected.enumerate() {
+        for v in [0.3, 1.7] {
+            let at = [c!(v), c!(0.9), c!(0.0)];
+            let (a, b) = (sys.eval(p.coeff(k), at), sys.eval(c.clone(), at));
+            assert!((a - b).norm() < 1e-9, "coefficient {k}: {a} != {b}");
+        }
+    }
+}
+
+#[test]
+fn expansion_and_truncation() {
+    let (sys, x, _, eps) = setup();
+    let p = Perturbation::new((e!(1.0) - e!(eps) * e!(x)).inv(), eps, 3).unwrap();
+    assert_eq!(p.order(), Some(3));
+    assert_coeffs(&sys, &p, &[e!(1.0), e!(x), e!(x) * e!(x)]);
+
+    let q = Perturbation::from_coeffs(vec![e!(1.0), e!(1.0)], 2);
+    let square = q.clone() * q;
+    assert_eq!(square.order(), Some(2));
+    assert_coeffs(&sys, &square, &[e!(1.0), e!(2.0)]);
+    let mixed = p * Perturbation::from_coeffs(vec![e!(1.0), e!(-1.0)], 2);
+    assert_eq!(mixed.order(), Some(2));
+    assert_coeffs(&sys, &mixed, &[e!(1.0), e!(x) - e!(1.0)]);
+}
+
+#[test]
+fn constants_are_exact() {
+    let (sys, x, _, _) = setup();
+    let c = Perturbation::constant(c!(2.0));
+    assert_eq!(c.order(), None);
+    let p = Perturbation::from_coeffs(vec![e!(x), e!(1.0), e!(x)], 3);
+    let sum = p.clone() + c.clone();
+    assert_eq!(sum.order(), Some(3));
+    assert_coeffs(&sys, &sum, &[e!(x) + e!(2.0), e!(1.0), e!(x)]);
+    assert_eq!((c.clone() * c).order(), None);
+    assert_coeffs(
+        &sys,
+        &(p * Perturbation::constant(c!(3.0))),
+        &[e!(3.0) * e!(x), e!(3.0), e!(3.0) * e!(x)],
+    );
+}
+
+#[test]
+fn inverse() {
+    let (sys, x, _, _) = setup();
+    let p = Perturbation::from_coeffs(vec![e!(1.0), e!(x)], 4);
+    let inv = p.inv();
+    let expected = [e!(1.0), -e!(x), e!(x).pow(e!(2.0)), -e!(x).pow(e!(3.0))];
+    assert_coeffs(&sys, &inv, &expected);
+    assert_coeffs(&sys, &(p.clone() / p), &[e!(1.0)]);
+}
+
+#[test]
+fn matrix_inverse() {
+    let (sys, x, _, eps) = setup();
+    let mut g = SqMatrix::<2>::zeroes();
+    g[0][0] = e!(x);
+    g[0][1] = e!(eps);
+    g[1][0] = e!(eps);
+    g[1][1] = e!(1.0);
+    let g = SqMatrix::perturbation(&g, eps, 3).unwrap();
+    let inv = g.inv();
+    // `1/(x - ε²) [[1, -ε], [-ε, x]]` to second order
+    let x2 = || e!(x).pow(e!(2.0));
+    assert_coeffs(&sys, &inv[0][0], &[e!(x).inv(), e!(0.0), x2().inv()]);
+    assert_coeffs(&sys, &inv[0][1], &[e!(0.0), -e!(x).inv()]);
+    assert_coeffs(&sys, &inv[1][0], &[e!(0.0), -e!(x).inv()]);
+    assert_coeffs(&sys, &inv[1][1], &[e!(1.0), e!(0.0), e!(x).inv()]);
+    assert_eq!(inv[0][0].order(), Some(3));
+}
+
+#[test]
+fn multivariate_taylor() {
+    let (sys, x, y, _) = setup();
+    let f = e!(x).exp() * cos(e!(y));
+    let t = f.taylor([x, y], [e!(0.0), e!(0.0)], 3);
+    let expected = e!(1.0) + e!(x) + (e!(x).pow(e!(2.0)) - e!(y).pow(e!(2.0))) / e!(2.0);
+    for p in [[0.3, 0.2], [-0.5, 1.1], [2.0, -0.4]] {
+        let at = [c!(p[0]), c!(p[1]), c!(0.0)];
+        let (a, b) = (sys.eval(t.clone(), at), sys.eval(expected.clone(), at));
+        assert!((a - b).norm() < 1e-9, "{a} != {b} at {p:?}");
+    }
+    let t = e!(x).ln().taylor([x, y], [e!(1.0), e!(0.0)], 2);
+    let at = [c!(1.5), c!(0.0), c!(0.0)];
+    assert!((sys.eval(t, at) - c!(0.5)).norm() < 1e-9);
+}
+
+#[test]
+fn linearized_ricci_of_a_weak_field() {
+    // `ds² = -(1 + 2εΦ) dt² + (1 - 2εΦ) dx²` has `R_tt = ∇²Φ` and `R_ij = δ_ij ∇²Φ` to first order
+    let mut sys = System::default();
+    let x = sys.symbols("t x y z").unwrap();
+    let [eps] = sys.symbols("ε").unwrap();
+    let [_, x1, y1, z1] = x;
+    let phi = e!(x1).pow(e!(2.0)) * e!(y1) + e!(z1).pow(e!(3.0));
+    let laplacian = e!(2.0) * e!(y1) + e!(6.0) * e!(z1);
+    let mut g = SqMatrix::<4>::zeroes();
+    g[0][0] = -(e!(1.0) + e!(2.0) * e!(eps) * phi.clone());
+    for i in 1..4 {
+        g[i][i] = e!(1.0) - e!(2.0) * e!(eps) * phi.clone();
+    }
+    let g = SqMatrix::perturbation(&g, eps, 2).unwrap();
+    let g_inv = g.inv();
+    let gamma = gr::christoffel(g.clone(), g_inv.clone(), x);
+    let riemann = gr::riemann_tensor(g, &gamma, x);
+    let ricci = gr::ricci_tensor(&riemann, g_inv.clone());
+    let r = gr::scalar_curvature(&ricci, g_inv);
+    for (m, row) in ricci.iter().enumerate() {
+        for (n, rmn) in row.iter().enumerate() {
+            let expected = match m == n {
+                true => laplacian.clone(),
+                false => e!(0.0),
+            };
+            for p in [[0.3, 0.7, -1.2, 0.4, 0.0], [1.1, -0.5, 0.8, 2.0, 0.0]] {
+                let at = p.map(|p| c!(p));
+                let (a, b) = (sys.eval(rmn.coeff(1), at), sys.eval(expected.clone(), at));
+                assert!((a - b).norm() < 1e-9, "R_{m}{n}: {a} != {b}");
+                assert!(sys.eval(rmn.coeff(0), at).norm() < 1e-9);
+            }
+        }
+    }
+    // `R = -R_tt + δ^ij R_ij`
+    let at = [0.3, 0.7, -1.2, 0.4, 0.0].map(|p| c!(p));
+    let expected = sys.eval(e!(2.0) * laplacian, at);
+    assert!((sys.eval(r.coeff(1), at) - expected).norm() < 1e-9);
+}
@@ -0,0 +1,106 @@
+use symrs::*;
+
+/// Compares the terms of `series` to `(exponent, coefficient)` pairs and its remainder order
+#[track_caller]
+fn assert_terms(sys: &System, series: Series, expected: &[(f64, f64)], order: f64) {
+    let terms = series.terms().collect::<Vec<_>>();
+    assert!(
+        terms.len() == expected.len(),
+        "Inadequate amount of terms, expected {} got {}",
+        expected.len(),
+        terms.len()
+    );
+    for ((k, c), &(ek, ec)) in terms.into_iter().zip(expected) {
+        let c = sys.eval(c, [c!()]);
+        assert!(k == ek, "expected exponent {ek} got {k}");
+        assert!((c - c!(ec)).norm() < 1e-12, "expected {ec} got {c} at {k}");
+    }
+    assert_eq!(series.order(), Some(order));
+}
+
+fn setup() -> (System, Var) {
+    let mut sys = System::default();
+    let [x] = sys.symbols("x").unwrap();
+    (sys, x)
+}
+
+fn zero() -> Point {
+    Point::Finite(e!(0.0))
+}
+
+#[test]
+fn taylor() {
+    let (sys, x) = setup();
+    let series = e!(x).exp().series(x, zero(), 5).unwrap();
+    let expected = [
+        (0.0, 1.0),
+        (1.0, 1.0),
+        (2.0, 0.5),
+        (3.0, 1.0 / 6.0),
+        (4.0, 1.0 / 24.0),
+    ];
+    assert_terms(&sys, series, &expected, 5.0);
+    let series = sin(e!(x)).series(x, Point::Finite(e!(1.0)), 3).unwrap();
+    let (s, c) = (1f64.sin(), 1f64.cos());
+    assert_terms(&sys, series, &[(0.0, s), (1.0, c), (2.0, -s / 2.0)], 3.0);
+}
+
+#[test]
+fn laurent() {
+    let (sys, x) = setup();
+    let series = (sin(e!(x)) / e!(x).pow(e!(3.0)))
+        .series(x, zero(), 3)
+        .unwrap();
+    assert_terms(
+        &sys,
+        series,
+        &[(-2.0, 1.0), (0.0, -1.0 / 6.0), (2.0, 1.0 / 120.0)],
+        3.0,
+    );
+}
+
+#[test]
+fn puiseux() {
+    let (sys, x) = setup();
+    let f = (e!(x) + e!(x).pow(e!(2.0))).pow(e!(0.5));
+    let series = f.series(x, zero(), 3).unwrap();
+    assert_terms(&sys, series, &[(0.5, 1.0), (1.5, 0.5), (2.5, -0.125)], 3.0);
+}
+
+#[test]
+fn at_infinity() {
+    let (sys, x) = setup();
+    let f = e!(x) / (e!(x) + e!(1.0));
+    let series = f.series(x, Point::PosInf, 3).unwrap();
+    assert_terms(&sys, series, &[(0.0, 1.0), (1.0, -1.0), (2.0, 1.0)], 3.0);
+    let f = (e!(x).inv()).exp() * e!(x);
+    let series = f.series(x, Point::NegInf, 2).unwrap();
+    assert_terms(&sys, series, &[(-1.0, -1.0), (0.0, 1.0), (1.0, -0.5)], 2.0);
+}
+
+#[test]
+fn remainder_in_the_expansion_variable() {
+    let (sys, x) = setup();
+    let f = e!(x) / (e!(x) + e!(1.0));
+    let at = |point| sys.strseries(f.clone().series(x, point, 2).unwrap());
+    assert!(
+        at(Point::PosInf).ends_with("O((1/x)^2)"),
+        "{}",
+        at(Point::PosInf)
+    );
+    assert!(
+        at(Point::NegInf).ends_with("O((-1/x)^2)"),
+        "{}",
+        at(Point::NegInf)
+    );
+    assert!(at(zero()).ends_with("O(x^2)"), "{}", at(zero()));
+}
+
+#[test]
+#[should_panic(expected = "different points")]
+fn combining_series_about_different_points() {
+    let (_, x) = setup();
+    let a = e!(x).exp().series(x, zero(), 3).unwrap();
+    let b = e!(x).exp().series(x, Point::Finite(e!(1.0)), 3).unwrap();
+    let _ = a + b;
+}
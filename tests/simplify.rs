@@ -0,0 +1,133 @@
+use symrs::*;
+
+fn setup() -> (System, Var, Var) {
+    let mut sys = System::default();
+    let [x, y] = sys.symbols("x y").unwrap();
+    (sys, x, y)
+}
+
+/// Checks that `ex` and `expected` simplify to the same form
+#[track_caller]
+fn assert_simplifies(sys: &System, ex: Expression, expected: Expression) {
+    let (ex, expected) = (sys.str(ex.simplify()), sys.str(expected.simplify()));
+    assert_eq!(ex, expected);
+}
+
+/// Checks that simplifying `ex` keeps its value at a few points, off the positive reals too
+#[track_caller]
+fn assert_preserved(sys: &System, ex: Expression) {
+    let simplified = ex.clone().simplify();
+    let points = [
+        [c!(0.3), c!(1.7)],
+        [c!(1.3), c!(0.4)],
+        [c!(-0.6), c!(-1.3)],
+        [c!(-0.7), c!(2.1)],
+        [c!(0.4; 1.2), c!(-1.5; -0.6)],
+    ];
+    for x in points {
+        let (a, b) = (sys.eval(ex.clone(), x), sys.eval(simplified.clone(), x));
+        assert!(
+            (a - b).norm() <= 1e-9 * a.norm().max(1.0),
+            "{a} != {b} at {x:?}"
+        );
+    }
+}
+
+#[test]
+fn collects_terms() {
+    let (sys, x, y) = setup();
+    assert_simplifies(&sys, e!(x) + e!(x) - e!(2.0) * e!(x), e!(0.0));
+    assert_simplifies(&sys, e!(x) + e!(y) + e!(x), e!(y) + e!(2.0) * e!(x));
+    assert_simplifies(&sys, e!(x) * e!(y) - e!(y) * e!(x), e!(0.0));
+    assert_simplifies(&sys, e!(1.5) + e!(x) * e!(0.0) + e!(2.5), e!(4.0));
+}
+
+#[test]
+fn collects_powers() {
+    let (sys, x, y) = setup();
+    assert_simplifies(&sys, e!(x) * e!(x) / e!(x).pow(e!(2.0)), e!(1.0));
+    let merged = e!(x).pow(e!(y)) * e!(x).pow(e!(2.0)) / e!(x).pow(e!(y) + e!(2.0));
+    assert_simplifies(&sys, merged, e!(1.0));
+    assert_simplifies(&sys, e!(2.0).exp() * e!(-2.0).exp(), e!(1.0));
+    assert_simplifies(
+        &sys,
+        e!(x).exp() * e!(y).exp() / (e!(x) + e!(y)).exp(),
+        e!(1.0),
+    );
+}
+
+#[test]
+fn expands_integer_powers_of_sums() {
+    let (sys, x, y) = setup();
+    let square = (e!(x) + e!(y)).pow(e!(2.0));
+    let expanded = e!(x) * e!(x) + e!(2.0) * e!(x) * e!(y) + e!(y) * e!(y);
+    assert_simplifies(&sys, square - expanded, e!(0.0));
+    let cube = (e!(x) + e!(1.0)).pow(e!(3.0)) - (e!(x) + e!(1.0)) * (e!(x) + e!(1.0)).pow(e!(2.0));
+    assert_simplifies(&sys, cube, e!(0.0));
+}
+
+#[test]
+fn splits_logarithms() {
+    let (sys, x, y) = setup();
+    assert_simplifies(&sys, (e!(2.0) * e!(x)).ln(), e!(2.0).ln() + e!(x).ln());
+    assert_simplifies(&sys, e!(x).exp().ln(), e!(x));
+    let ln = e!(2.0).pow(e!(y)).ln() - e!(y) * e!(2.0).ln();
+    assert_simplifies(&sys, ln, e!(0.0));
+    // Not for bases that may be negative or complex
+    let ln = (e!(x) * e!(y)).ln() - e!(x).ln() - e!(y).ln();
+    assert!(sys.str(ln.simplify()) != sys.str(e!(0.0)));
+    let ln = e!(x).pow(e!(y)).ln() - e!(y) * e!(x).ln();
+    assert!(sys.str(ln.simplify()) != sys.str(e!(0.0)));
+}
+
+#[test]
+fn merges_integer_powers() {
+    let (sys, x, y) = setup();
+    assert_simplifies(&sys, e!(x).pow(e!(0.5)).pow(e!(2.0)), e!(x));
+    assert_simplifies(
+        &sys,
+        (e!(x) * e!(y)).pow(e!(-2.0)) * e!(x).pow(e!(2.0)),
+        e!(y).pow(e!(-2.0)),
+    );
+    assert_simplifies(&sys, e!(x).exp().pow(e!(3.0)), (e!(3.0) * e!(x)).exp());
+    // `(x²)^½ = |x|`, not `x`
+    let root = e!(x).pow(e!(2.0)).pow(e!(0.5)).simplify();
+    assert_eq!(sys.eval(root, [c!(-1.0), c!()]), c!(1.0));
+}
+
+#[test]
+fn preserves_values() {
+    let (sys, x, y) = setup();
+    assert_preserved(&sys, (e!(x) + e!(y)).pow(e!(3.0)) / (e!(x) - e!(y)));
+    assert_preserved(
+        &sys,
+        e!(x).pow(e!(0.5)) * e!(x).pow(e!(1.5)) + e!(y).ln() * e!(x),
+    );
+    assert_preserved(&sys, (e!(x) * e!(y).exp()).ln() * sin(e!(x) * e!(2.0)));
+    assert_preserved(
+        &sys,
+        (e!(x) + e!(1.0)).pow(e!(-2.0)) * (e!(x) + e!(1.0)).pow(e!(y)),
+    );
+    assert_preserved(&sys, (e!(x) * e!(y)).ln());
+    assert_preserved(&sys, e!(x).pow(e!(2.0)).pow(e!(0.5)));
+    assert_preserved(
+        &sys,
+        e!(x).pow(e!(3.0)).pow(e!(y)) * (e!(x) * e!(y)).pow(e!(-1.0)),
+    );
+    assert_preserved(
+        &sys,
+        (e!(x).pow(e!(2.0)) * e!(y)).ln() + e!(y).exp().pow(e!(2.0)).ln(),
+    );
+}
+
+#[test]
+fn zero_to_positive_powers() {
+    let (sys, x, y) = setup();
+    let at_zero = |ex: Expression| ex.subs(x, e!(0.0));
+    assert_simplifies(&sys, at_zero(e!(x).pow(e!(2.0)) * e!(y)), e!(0.0));
+    assert_simplifies(&sys, at_zero(e!(x).pow(e!(0.5)) + e!(y)), e!(y));
+    assert_simplifies(&sys, at_zero(e!(x).pow(e!(y) * e!(0.0))), e!(1.0));
+    // Only positive powers vanish
+    let blowup = at_zero(e!(x).pow(e!(-1.0))).simplify();
+    assert!(sys.eval(blowup, [c!(), c!(1.0)]).norm() > 1.0);
+}
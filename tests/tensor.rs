@@ -0,0 +1,150 @@
+use symrs::{gr::*, *};
+
+use Variance::{Down, Up};
+
+/// Compares every component of `a` and `b` at the sample point
+#[track_caller]
+fn assert_tensor<const N: usize>(sys: &System, a: &Tensor<N>, b: &Tensor<N>, point: [f64; 2]) {
+    assert_eq!(a.variance(), b.variance());
+    for ((i, a), (_, b)) in a.iter().zip(b.iter()) {
+        let err = sys.eval(a.clone() - b.clone(), point.map(|p| c!(p))).norm();
+        assert!(
+            err < 1e-9,
+            "{i:?}: {} against {}",
+            sys.str(a.clone()),
+            sys.str(b.clone())
+        );
+    }
+}
+
+fn setup() -> (System, Var, Var) {
+    let mut sys = System::default();
+    let [x, y] = sys.symbols("x y").unwrap();
+    (sys, x, y)
+}
+
+const POINT: [f64; 2] = [0.7, 1.3];
+
+fn vector(a: [Expression; 2], variance: Variance) -> Tensor<2> {
+    Tensor::from_fn(vec![variance], |i| a[i[0]].clone())
+}
+
+/// `M_ij = x i + 2y j + i j`, not symmetric
+fn matrix(x: Var, y: Var, variance: [Variance; 2]) -> Tensor<2> {
+    Tensor::from_fn(variance.to_vec(), |i| {
+        e!(x) * e!(i[0] as f64) + e!(y) * e!(2.0 * i[1] as f64) + e!((i[0] * i[1]) as f64)
+    })
+}
+
+#[test]
+fn outer_and_contract() {
+    let (sys, x, y) = setup();
+    let u = vector([e!(x), e!(y)], Up);
+    let w = vector([e!(2.0), e!(x) * e!(y)], Down);
+    let uw = u.outer(&w);
+    assert_eq!(uw.variance(), [Up, Down]);
+    let expected = Tensor::from_fn(vec![Up, Down], |i| u[[i[0]]].clone() * w[[i[1]]].clone());
+    assert_tensor(&sys, &uw, &expected, POINT);
+    // `u^a w_a`
+    let dot = e!(2.0) * e!(x) + e!(x) * e!(y) * e!(y);
+    assert_tensor(&sys, &uw.contract(0, 1), &Tensor::scalar(dot), POINT);
+    let m = matrix(x, y, [Up, Down]);
+    let trace = m[[0, 0]].clone() + m[[1, 1]].clone();
+    assert_tensor(&sys, &m.contract(1, 0), &Tensor::scalar(trace), POINT);
+}
+
+#[test]
+#[should_panic(expected = "same variance")]
+fn contract_same_variance() {
+    let (_, x, y) = setup();
+    matrix(x, y, [Down, Down]).contract(0, 1);
+}
+
+#[test]
+fn raise_and_lower() {
+    let (sys, x, y) = setup();
+    // Sphere of radius 2, with `x y` for `θ φ`
+    let g = SqMatrix([[e!(4.0), e!(0.0)], [e!(0.0), e!(4.0) * sin(x).pow(2.0)]]);
+    let g_inv = g.inv();
+    let m = matrix(x, y, [Down, Down]);
+    let up = m.raise(1, &g_inv);
+    assert_eq!(up.variance(), [Down, Up]);
+    let expected = Tensor::from_fn(vec![Down, Up], |i| {
+        m[[i[0], i[1]]].clone() * g_inv[i[1]][i[1]].clone()
+    });
+    assert_tensor(&sys, &up, &expected, POINT);
+    assert_tensor(&sys, &up.lower(1, &g), &m, POINT);
+}
+
+#[test]
+fn symmetrize_and_permute() {
+    let (sys, x, y) = setup();
+    let m = matrix(x, y, [Down, Down]);
+    let transposed = m.permute(&[1, 0]);
+    let expected = Tensor::from_fn(vec![Down, Down], |i| m[[i[1], i[0]]].clone());
+    assert_tensor(&sys, &transposed, &expected, POINT);
+    let sym = m.symmetrize(&[0, 1]);
+    let anti = m.antisymmetrize(&[0, 1]);
+    assert_tensor(
+        &sys,
+        &sym,
+        &((m.clone() + transposed.clone()) * e!(0.5)),
+        POINT,
+    );
+    assert_tensor(&sys, &anti, &((m.clone() - transposed) * e!(0.5)), POINT);
+    assert_tensor(&sys, &(sym + anti), &m, POINT);
+
+    // Mixed variances move with their indices
+    let t = vector([e!(x), e!(y)], Up).outer(&matrix(x, y, [Down, Down]));
+    let p = t.permute(&[2, 0, 1]);
+    assert_eq!(p.variance(), [Down, Up, Down]);
+    let d = sys.eval(
+        p[[1, 0, 1]].clone() - t[[0, 1, 1]].clone(),
+        POINT.map(|p| c!(p)),
+    );
+    assert!(d.norm() < 1e-12);
+}
+
+#[test]
+fn curvature_as_tensor() {
+    let mut sys = System::default();
+    let (g, x) = sphere::<2>(&mut sys, e!(1.0)).unwrap();
+    let m = Metric::new(g, x);
+    let gamma = Tensor::from(m.christoffel());
+    assert_eq!(gamma.variance(), [Up, Down, Down]);
+    assert_tensor(&sys, &gamma, &gamma.permute(&[0, 2, 1]), POINT);
+    let riemann = Tensor::from(m.riemann());
+    // `R_abcd = -R_bacd = R_cdab`
+    assert_tensor(&sys, &riemann, &-riemann.permute(&[1, 0, 2, 3]), POINT);
+    assert_tensor(&sys, &riemann, &riemann.permute(&[2, 3, 0, 1]), POINT);
+}
+
+#[test]
+fn einsum_matches_loops() {
+    let (sys, x, y) = setup();
+    let m = matrix(x, y, [Up, Down]);
+    let v = vector([e!(x), e!(y).exp()], Up);
+    let expected = Tensor::from_fn(vec![Up], |i| {
+        m[[i[0], 0]].clone() * v[[0]].clone() + m[[i[0], 1]].clone() * v[[1]].clone()
+    });
+    assert_tensor(&sys, &einsum!("ab,b->a", m, v), &expected, POINT);
+    assert_tensor(&sys, &(m.ix("^a_b") * v.ix("^b")).to("a"), &expected, POINT);
+    // Free indices come out in the order asked for
+    let t = einsum!("ab->ba", m);
+    assert_tensor(&sys, &t, &m.permute(&[1, 0]), POINT);
+}
+
+#[test]
+#[should_panic(expected = "same variance")]
+fn einsum_two_upper_indices() {
+    let (_, x, y) = setup();
+    let u = vector([e!(x), e!(y)], Up);
+    einsum!("a,a->", u, u);
+}
+
+#[test]
+#[should_panic(expected = "wrong variance")]
+fn labels_check_variance() {
+    let (_, x, y) = setup();
+    matrix(x, y, [Up, Down]).ix("_a_b");
+}